@@ -0,0 +1,881 @@
+//! Merges lower-timeframe bars into higher-timeframe buckets by wall-clock
+//! time (e.g. `M15 -> H1`), the same "committed vs. pending" shape used by
+//! the incremental indicators in [`crate::indicator::exec`]: `push` advances
+//! to a new source bar, `update_last` revises the most recent one in place.
+//!
+//! There's no `TimeFrame` enum or `MultiTimeFrameAggregator` anywhere in
+//! this crate -- one `Aggregator` is constructed per target bucket width
+//! (`bucket_ms`) directly, and a caller wanting several timeframes at once
+//! just runs one `Aggregator` per timeframe over the same source bars. The
+//! session-reset behaviour below (see [`Aggregator::with_session_offset`])
+//! is added to `Aggregator` itself for that reason: it's the only bucketing
+//! type this crate has.
+//!
+//! There's likewise no `MultiHQuant` routing candles into per-period
+//! [`crate::engine::HQuant`] engines, so there's nothing to add a
+//! `bars(period_ms)`/`periods()` listing to. The equivalent setup already
+//! works today without it: feed each `Aggregator`'s finalized bars into a
+//! dedicated `HQuant` built for that period, and
+//! [`crate::engine::HQuant::bars`] already hands back that period's
+//! aggregated `KlineBuffer` directly -- see
+//! `tests::aggregated_bars_are_readable_back_through_the_downstream_hquants_own_kline_buffer`
+//! below.
+
+use crate::indicator::{Ema, IndicatorExec, Trend};
+use crate::kline::Bar;
+
+/// An aggregated bar plus the buy-side volume merged into it, kept separate
+/// from [`Bar`] since only the aggregator (and order-flow strategies
+/// consuming its output) care about the buy/sell split.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AggregatedBar {
+    pub bar: Bar,
+    pub buy_volume: f64,
+    /// How many source bars were merged into this bucket. Data-quality
+    /// monitors compare this against the expected ratio (e.g. an H1 bucket
+    /// built from M15 source bars should read 4) to detect gaps in the
+    /// underlying feed.
+    pub sub_bar_count: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BucketState {
+    ts: i64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+    buy_volume: f64,
+    sub_bar_count: u32,
+}
+
+impl BucketState {
+    fn into_aggregated_bar(self) -> AggregatedBar {
+        AggregatedBar {
+            bar: Bar { ts: self.ts, open: self.open, high: self.high, low: self.low, close: self.close, volume: self.volume },
+            buy_volume: self.buy_volume,
+            sub_bar_count: self.sub_bar_count,
+        }
+    }
+}
+
+/// Which UTC weekday a weekly [`Aggregator`] (see [`Aggregator::weekly`])
+/// resets on. Different venues disagree here -- crypto typically follows
+/// the ISO week (Monday), but a Sunday or Friday reset shows up too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeekStart {
+    Monday,
+    Friday,
+    Sunday,
+}
+
+impl WeekStart {
+    /// [`Aggregator::with_session_offset`]'s offset that lands a 7-day
+    /// bucket's boundary on this weekday, given the Unix epoch
+    /// (1970-01-01) was a Thursday: `Monday` is 4 days after that boundary,
+    /// `Friday` 1 day, `Sunday` 3 days.
+    fn offset_ms(self) -> i64 {
+        let days_after_epoch_thursday = match self {
+            WeekStart::Monday => 4,
+            WeekStart::Friday => 1,
+            WeekStart::Sunday => 3,
+        };
+        days_after_epoch_thursday * 86_400_000
+    }
+}
+
+/// Aggregates a stream of source bars into fixed-width `bucket_ms` buckets.
+///
+/// # Live vs. backtest flush semantics
+///
+/// A bucket only finalizes naturally once a source bar belonging to the
+/// *next* bucket arrives (see `push`). At the end of a backtest there is no
+/// such bar, so the last, still-forming bucket would otherwise never be
+/// emitted; call [`Aggregator::flush_for_backtest`] once, after the source
+/// stream ends, to force it out.
+///
+/// In live mode there is no "end of stream" — the next source bar always
+/// eventually arrives — so never call `flush_for_backtest` there: doing so
+/// would emit a candle before its time window has actually elapsed,
+/// corrupting any indicator fed from it.
+#[derive(Debug)]
+pub struct Aggregator {
+    bucket_ms: i64,
+    /// Shift applied before flooring a timestamp to its bucket boundary
+    /// (and added back after), so a bucket can start somewhere other than
+    /// the UTC epoch-aligned boundary `bucket_ms` alone would imply --
+    /// e.g. a `D1` aggregator with an 08:00 `session_offset_ms` groups
+    /// bars 08:00..08:00 instead of the UTC midnight..midnight a plain
+    /// `bucket_ms`-only floor would produce. Zero for venues that reset on
+    /// the epoch-aligned boundary (crypto's usual UTC midnight daily).
+    session_offset_ms: i64,
+    /// Bucket state prior to merging the most recently pushed source bar,
+    /// kept so `update_last` can recompute the bucket from that base
+    /// instead of double-applying the latest source bar's contribution.
+    /// `None` means the most recent source bar opened the bucket.
+    before_last: Option<BucketState>,
+    /// Bucket state including the most recently pushed source bar: the
+    /// forming (not yet finalized) bucket.
+    current: Option<BucketState>,
+    /// See [`Aggregator::set_fill_gaps`].
+    fill_gaps: bool,
+    /// Total synthetic bars [`Aggregator::push`] has emitted so far because
+    /// of [`Aggregator::set_fill_gaps`], for a caller monitoring feed
+    /// quality to alert on.
+    filled_bar_count: u32,
+}
+
+impl Aggregator {
+    pub fn new(bucket_ms: i64) -> Self {
+        Self::with_session_offset(bucket_ms, 0)
+    }
+
+    /// Like `new`, but buckets reset `session_offset_ms` after each
+    /// epoch-aligned boundary rather than exactly on it -- e.g. equities'
+    /// `D1` bars resetting at exchange open rather than UTC midnight.
+    pub fn with_session_offset(bucket_ms: i64, session_offset_ms: i64) -> Self {
+        assert!(bucket_ms > 0, "Aggregator bucket_ms must be > 0");
+        Self { bucket_ms, session_offset_ms, before_last: None, current: None, fill_gaps: false, filled_bar_count: 0 }
+    }
+
+    /// A 7-day-wide `Aggregator` that resets on `week_start` rather than a
+    /// plain `Aggregator::new(7 * 86_400_000)`'s epoch-aligned boundary --
+    /// the Unix epoch (1970-01-01) was a Thursday, so that plain form
+    /// produces Thursday-to-Thursday weekly candles instead of a real
+    /// calendar week. Just [`Aggregator::with_session_offset`] with the
+    /// offset worked out from `week_start` for the caller, for venues that
+    /// reset Sunday or Friday rather than the ISO-week Monday.
+    pub fn weekly(week_start: WeekStart) -> Self {
+        Self::with_session_offset(7 * 86_400_000, week_start.offset_ms())
+    }
+
+    /// Whether a source bar that skips one or more expected buckets should
+    /// have [`Aggregator::push`] synthesize flat (`open == high == low ==
+    /// close == the previous bucket's close`, zero volume, `sub_bar_count:
+    /// 0`) bars for the missing buckets before starting the new one, e.g.
+    /// to keep an indicator fed from this aggregator's output from seeing
+    /// a silent hole in its bar-index-based lookback whenever the exchange
+    /// drops candles during an outage. `false` by default, matching this
+    /// type's original just-skip-ahead behavior.
+    pub fn set_fill_gaps(&mut self, fill_gaps: bool) {
+        self.fill_gaps = fill_gaps;
+    }
+
+    /// Total synthetic bars emitted so far because of
+    /// [`Aggregator::set_fill_gaps`].
+    pub fn filled_bar_count(&self) -> u32 {
+        self.filled_bar_count
+    }
+
+    /// Drop the in-progress and just-finalized bucket state, keeping
+    /// `bucket_ms`/`session_offset_ms`/`fill_gaps` as configured -- for
+    /// reusing this `Aggregator` on a different symbol the same way
+    /// [`crate::engine::HQuant::reset`] reuses an `HQuant`. There's no
+    /// `MultiHQuant`/multi-timeframe engine type in this crate to cascade a
+    /// reset through (see this module's doc comment) -- a caller running
+    /// several timeframes at once just calls this on each `Aggregator` in
+    /// turn, same as it constructs one per timeframe today.
+    pub fn reset(&mut self) {
+        self.before_last = None;
+        self.current = None;
+        self.filled_bar_count = 0;
+    }
+
+    fn synthetic_bar(ts: i64, prev_close: f64) -> AggregatedBar {
+        AggregatedBar {
+            bar: Bar { ts, open: prev_close, high: prev_close, low: prev_close, close: prev_close, volume: 0.0 },
+            buy_volume: 0.0,
+            sub_bar_count: 0,
+        }
+    }
+
+    fn bucket_start(&self, ts: i64) -> i64 {
+        let shifted = ts - self.session_offset_ms;
+        shifted - shifted.rem_euclid(self.bucket_ms) + self.session_offset_ms
+    }
+
+    /// Feed one source bar and its buy-side volume into the aggregator.
+    /// Returns every bar finalized as a result, oldest first: empty while
+    /// `source` still belongs to the current bucket, the just-finalized
+    /// bucket once `source` belongs to a new one, or (with
+    /// [`Aggregator::set_fill_gaps`] enabled) that bucket followed by one
+    /// synthetic bar per bucket `source`'s timestamp skipped over.
+    pub fn push(&mut self, source: &Bar, buy_volume: f64) -> Vec<AggregatedBar> {
+        let bucket_ts = self.bucket_start(source.ts);
+        let mut finalized = Vec::new();
+        if let Some(cur) = self.current {
+            if cur.ts != bucket_ts {
+                finalized.push(cur.into_aggregated_bar());
+                if self.fill_gaps {
+                    let mut gap_ts = cur.ts + self.bucket_ms;
+                    while gap_ts < bucket_ts {
+                        finalized.push(Self::synthetic_bar(gap_ts, cur.close));
+                        self.filled_bar_count += 1;
+                        gap_ts += self.bucket_ms;
+                    }
+                }
+            }
+        }
+        let base = if finalized.is_empty() { self.current } else { None };
+        self.before_last = base;
+        self.current = Some(Self::merge(base, bucket_ts, source, buy_volume));
+        finalized
+    }
+
+    /// Revise the current bucket's contribution from its most recent source
+    /// bar in place, without advancing to a new bucket. Recomputes from
+    /// `before_last` — the bucket state prior to that source bar — so the
+    /// revised OHLCV and buy volume both land correctly, the same way
+    /// `push` does.
+    pub fn update_last(&mut self, source: &Bar, buy_volume: f64) {
+        let Some(cur) = self.current else {
+            self.push(source, buy_volume);
+            return;
+        };
+        self.current = Some(Self::merge(self.before_last, cur.ts, source, buy_volume));
+    }
+
+    /// Fold `source` (with its buy volume) into `base` — the bucket state
+    /// prior to `source` — producing the bucket state including `source`.
+    /// `base = None` means `source` opens the bucket.
+    fn merge(base: Option<BucketState>, bucket_ts: i64, source: &Bar, buy_volume: f64) -> BucketState {
+        match base {
+            Some(b) => BucketState {
+                ts: bucket_ts,
+                open: b.open,
+                high: b.high.max(source.high),
+                low: b.low.min(source.low),
+                close: source.close,
+                volume: b.volume + source.volume,
+                buy_volume: b.buy_volume + buy_volume,
+                sub_bar_count: b.sub_bar_count + 1,
+            },
+            None => BucketState {
+                ts: bucket_ts,
+                open: source.open,
+                high: source.high,
+                low: source.low,
+                close: source.close,
+                volume: source.volume,
+                buy_volume,
+                sub_bar_count: 1,
+            },
+        }
+    }
+
+    /// Finalize the in-progress bucket immediately, without waiting for a
+    /// source bar from the next bucket. Only appropriate once the source
+    /// stream has ended (i.e. at backtest completion); see the type-level
+    /// docs for why this must never be called mid-stream in live mode.
+    pub fn flush_for_backtest(&mut self) -> Option<AggregatedBar> {
+        self.before_last = None;
+        self.current.take().map(BucketState::into_aggregated_bar)
+    }
+
+    /// Finalize the in-progress bucket if `now_ts` is at or past its end
+    /// (`bucket start + bucket_ms`), without waiting for a source bar from
+    /// the next bucket to trigger the usual `push`-driven finalization.
+    ///
+    /// Unlike `flush_for_backtest`, this is safe to call repeatedly
+    /// mid-stream in live mode: a live feed's next bar can be delayed by
+    /// thin trading or an upstream gap well past when the current bucket's
+    /// time window has genuinely elapsed, and a caller watching a clock
+    /// (rather than only reacting to bars) wants the bucket the moment
+    /// that happens rather than however much later the next bar shows up.
+    /// Returns `None` without touching state if the window hasn't elapsed
+    /// yet, so a source bar still belonging to this bucket can arrive and
+    /// be merged normally right up until it does.
+    pub fn close_if_elapsed(&mut self, now_ts: i64) -> Option<AggregatedBar> {
+        let cur = self.current?;
+        if now_ts < cur.ts + self.bucket_ms {
+            return None;
+        }
+        self.before_last = None;
+        self.current = None;
+        Some(cur.into_aggregated_bar())
+    }
+}
+
+/// Aggregates a stream of source bars into buckets that close once
+/// accumulated volume reaches `threshold`, rather than a fixed wall-clock
+/// width -- useful for microstructure work where a fixed nominal volume
+/// gives more comparable bars than a fixed time window during bursts of
+/// activity.
+///
+/// Source bars aren't trade-level, so unlike a true tick-by-tick volume
+/// bar a single source bar's volume can't be split across two buckets:
+/// the bar that pushes the running total to or past `threshold` closes
+/// the bucket whole (its own volume included in full, which can run the
+/// finished bucket a little over `threshold`), and the next bucket starts
+/// counting from zero rather than from the overshoot. Same "push /
+/// flush_for_backtest" shape as [`Aggregator`]; see its docs for why a
+/// flush is needed at all.
+#[derive(Debug)]
+pub struct VolumeBarAggregator {
+    threshold: f64,
+    current: Option<BucketState>,
+}
+
+impl VolumeBarAggregator {
+    pub fn new(threshold: f64) -> Self {
+        assert!(threshold > 0.0, "VolumeBarAggregator threshold must be > 0");
+        Self { threshold, current: None }
+    }
+
+    /// Feed one source bar and its buy-side volume. Returns the completed
+    /// bucket once accumulated volume reaches `threshold`; returns `None`
+    /// while it's still under.
+    pub fn push(&mut self, source: &Bar, buy_volume: f64) -> Option<AggregatedBar> {
+        let bucket_ts = self.current.map_or(source.ts, |c| c.ts);
+        let merged = Aggregator::merge(self.current, bucket_ts, source, buy_volume);
+        if merged.volume >= self.threshold {
+            self.current = None;
+            Some(merged.into_aggregated_bar())
+        } else {
+            self.current = Some(merged);
+            None
+        }
+    }
+
+    /// Finalize the in-progress bucket immediately, without waiting for
+    /// volume to reach `threshold`. Only appropriate once the source
+    /// stream has ended; see [`Aggregator::flush_for_backtest`]'s docs for
+    /// why this must never be called mid-stream in live mode.
+    pub fn flush_for_backtest(&mut self) -> Option<AggregatedBar> {
+        self.current.take().map(BucketState::into_aggregated_bar)
+    }
+}
+
+/// Aggregates a fixed count (`n`) of source bars into one bucket, rather
+/// than a fixed time width or volume threshold -- e.g. "every 100 updates"
+/// regardless of how much wall-clock time or volume they span.
+#[derive(Debug)]
+pub struct TickBarAggregator {
+    n: usize,
+    current: Option<BucketState>,
+}
+
+impl TickBarAggregator {
+    pub fn new(n: usize) -> Self {
+        assert!(n > 0, "TickBarAggregator n must be > 0");
+        Self { n, current: None }
+    }
+
+    /// Feed one source bar and its buy-side volume. Returns the completed
+    /// bucket once `n` source bars have been merged into it; returns
+    /// `None` while it's still under `n`.
+    pub fn push(&mut self, source: &Bar, buy_volume: f64) -> Option<AggregatedBar> {
+        let bucket_ts = self.current.map_or(source.ts, |c| c.ts);
+        let merged = Aggregator::merge(self.current, bucket_ts, source, buy_volume);
+        if merged.sub_bar_count as usize >= self.n {
+            self.current = None;
+            Some(merged.into_aggregated_bar())
+        } else {
+            self.current = Some(merged);
+            None
+        }
+    }
+
+    /// Finalize the in-progress bucket immediately, without waiting for
+    /// `n` source bars to arrive. Only appropriate once the source stream
+    /// has ended; see [`Aggregator::flush_for_backtest`]'s docs for why
+    /// this must never be called mid-stream in live mode.
+    pub fn flush_for_backtest(&mut self) -> Option<AggregatedBar> {
+        self.current.take().map(BucketState::into_aggregated_bar)
+    }
+}
+
+/// How wide each [`RenkoBuilder`] brick is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BrickSize {
+    /// A fixed price distance per brick.
+    Absolute(f64),
+    /// `multiplier * ATR(period)`, where ATR is Wilder's smoothed true
+    /// range (see [`RenkoBuilder`]'s docs for why it's tracked locally
+    /// rather than via [`crate::indicator::Atr`]), re-read on every pushed
+    /// bar so brick width adapts to volatility rather than staying fixed.
+    Atr { period: usize, multiplier: f64 },
+}
+
+/// Converts a price stream into Renko bricks: fixed-width steps that
+/// ignore time and any price move smaller than a full brick, only
+/// advancing once price has moved a whole brick width from the last
+/// brick's edge. A reversal only registers after a *2x* brick move against
+/// the current direction (one brick width to erase the current brick,
+/// another to open the first brick the other way) -- the standard "2x to
+/// flip" Renko rule, which is what keeps a chopping, sub-brick-width
+/// market from flipping direction on every bar.
+///
+/// [`BrickSize::Atr`] tracks its own true-range [`Ema`], seeded by its
+/// first true range rather than an average of the first `period` the way
+/// [`crate::indicator::Atr`] is -- close enough for sizing bricks, and
+/// avoiding a dependency on a type built for bar-for-bar accuracy against
+/// a reference ATR series rather than for this.
+///
+/// Only `close` prices are used to walk bricks forward (like the other
+/// synthetic-bar builders in this module, this operates on already-formed
+/// `Bar`s, not raw ticks, so there's no finer-grained path within a bar to
+/// walk); a brick's `high`/`low` are just its `open`/`close` bounds sorted.
+#[derive(Debug)]
+pub struct RenkoBuilder {
+    brick_size: BrickSize,
+    atr: Option<Ema>,
+    prev_bar: Option<Bar>,
+    /// The price edge the next brick (in either direction) would start
+    /// from. Advances by one brick width every time a brick completes.
+    origin: f64,
+    /// Direction of the most recently completed brick; `None` until the
+    /// first brick exists, since a lone starting price hasn't gone either
+    /// way yet.
+    direction: Option<Trend>,
+    bricks: Vec<Bar>,
+}
+
+impl RenkoBuilder {
+    /// `origin` is the starting reference price bricks are measured from
+    /// -- typically the first bar's close.
+    pub fn new(brick_size: BrickSize, origin: f64) -> Self {
+        let atr = match brick_size {
+            BrickSize::Absolute(size) => {
+                assert!(size > 0.0, "RenkoBuilder brick size must be > 0");
+                None
+            }
+            BrickSize::Atr { period, multiplier } => {
+                assert!(period > 0, "RenkoBuilder ATR period must be > 0");
+                assert!(multiplier > 0.0, "RenkoBuilder ATR multiplier must be > 0");
+                Some(Ema::with_alpha(1.0 / period as f64))
+            }
+        };
+        Self { brick_size, atr, prev_bar: None, origin, direction: None, bricks: Vec::new() }
+    }
+
+    fn true_range(&self, bar: &Bar) -> f64 {
+        match self.prev_bar {
+            Some(prev) => (bar.high - bar.low).max((bar.high - prev.close).abs()).max((bar.low - prev.close).abs()),
+            None => bar.high - bar.low,
+        }
+    }
+
+    /// Current brick width: the fixed size, or `multiplier * ATR` freshly
+    /// smoothed in from `bar`'s true range.
+    fn resolve_size(&mut self, bar: &Bar) -> f64 {
+        match self.brick_size {
+            BrickSize::Absolute(size) => size,
+            BrickSize::Atr { multiplier, .. } => {
+                let tr = self.true_range(bar);
+                let atr = self.atr.as_mut().expect("BrickSize::Atr always carries an Ema");
+                multiplier * atr.push(tr)
+            }
+        }
+    }
+
+    fn make_brick(ts: i64, open: f64, close: f64) -> Bar {
+        Bar { ts, open, high: open.max(close), low: open.min(close), close, volume: 0.0 }
+    }
+
+    /// Feed one bar's close price, returning every brick it completes (Renko
+    /// can close more than one brick per bar on a large enough move, unlike
+    /// the other builders in this module which never emit more than one
+    /// bucket per pushed bar).
+    pub fn push(&mut self, bar: &Bar) -> Vec<Bar> {
+        let size = self.resolve_size(bar);
+        let price = bar.close;
+        let mut new_bricks = Vec::new();
+
+        loop {
+            match self.direction {
+                None => {
+                    if price >= self.origin + size {
+                        new_bricks.push(Self::make_brick(bar.ts, self.origin, self.origin + size));
+                        self.origin += size;
+                        self.direction = Some(Trend::Up);
+                    } else if price <= self.origin - size {
+                        new_bricks.push(Self::make_brick(bar.ts, self.origin, self.origin - size));
+                        self.origin -= size;
+                        self.direction = Some(Trend::Down);
+                    } else {
+                        break;
+                    }
+                }
+                Some(Trend::Up) => {
+                    if price >= self.origin + size {
+                        new_bricks.push(Self::make_brick(bar.ts, self.origin, self.origin + size));
+                        self.origin += size;
+                    } else if price <= self.origin - 2.0 * size {
+                        new_bricks.push(Self::make_brick(bar.ts, self.origin, self.origin - size));
+                        self.origin -= size;
+                        self.direction = Some(Trend::Down);
+                    } else {
+                        break;
+                    }
+                }
+                Some(Trend::Down) => {
+                    if price <= self.origin - size {
+                        new_bricks.push(Self::make_brick(bar.ts, self.origin, self.origin - size));
+                        self.origin -= size;
+                    } else if price >= self.origin + 2.0 * size {
+                        new_bricks.push(Self::make_brick(bar.ts, self.origin, self.origin + size));
+                        self.origin += size;
+                        self.direction = Some(Trend::Up);
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.prev_bar = Some(*bar);
+        self.bricks.extend(new_bricks.iter().copied());
+        new_bricks
+    }
+
+    /// Renko bricks have no partial/forming state the way the time, volume
+    /// and tick buckets elsewhere in this module do -- a brick only exists
+    /// once price has moved a full brick width, so there's never anything
+    /// left to force out early. Provided purely so `RenkoBuilder` offers
+    /// the same `push`/`flush` shape as [`Aggregator`] and friends.
+    pub fn flush(&mut self) -> Vec<Bar> {
+        Vec::new()
+    }
+
+    /// Every brick completed so far, oldest first.
+    pub fn bricks(&self) -> &[Bar] {
+        &self.bricks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(ts: i64, close: f64) -> Bar {
+        Bar { ts, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn live_path_never_finalizes_until_the_bucket_elapses() {
+        let mut agg = Aggregator::new(3_600_000); // H1 buckets
+        // Four M15 bars, all within the same H1 bucket.
+        for ts in [0, 900_000, 1_800_000, 2_700_000] {
+            assert!(agg.push(&bar(ts, 1.0), 1.0).is_empty());
+        }
+        // Fifth bar belongs to the next H1 bucket: the first one finalizes.
+        let finalized = agg.push(&bar(3_600_000, 2.0), 1.0);
+        assert_eq!(finalized.len(), 1);
+    }
+
+    #[test]
+    fn flush_for_backtest_emits_the_final_partial_bar() {
+        let mut agg = Aggregator::new(3_600_000);
+        agg.push(&bar(0, 1.0), 1.0);
+        agg.push(&bar(900_000, 1.5), 1.0);
+        assert_eq!(agg.flush_for_backtest().map(|a| a.bar.close), Some(1.5));
+        // Nothing left to flush a second time.
+        assert_eq!(agg.flush_for_backtest(), None);
+    }
+
+    #[test]
+    fn buy_volume_accumulates_across_an_m15_to_h1_merge() {
+        let mut agg = Aggregator::new(3_600_000); // H1 buckets
+        let buy_volumes = [3.0, 5.0, 2.0, 4.0];
+        for (i, buy_volume) in buy_volumes.iter().enumerate() {
+            agg.push(&bar(i as i64 * 900_000, 1.0), *buy_volume);
+        }
+        let finalized = agg.flush_for_backtest().unwrap();
+        assert_eq!(finalized.buy_volume, buy_volumes.iter().sum::<f64>());
+    }
+
+    // There's no `MultiHQuant`/`hquant-rs/src/multi.rs`, `as_bar_open_time`,
+    // or a `Bar.buy_volume` field anywhere in this crate -- `Bar` itself
+    // deliberately has no buy/sell split (see `AggregatedBar`'s doc comment
+    // above), and a "4h engine" would just be another `Aggregator`
+    // constructed with a 4h `bucket_ms`, per this module's own doc comment
+    // disclaiming any multi-timeframe engine type. `Aggregator` already
+    // accumulates `buy_volume` per bucket regardless of bucket width, so
+    // this exercises that at H4 instead of H1 to cover the width this
+    // request asked about.
+    #[test]
+    fn buy_volume_accumulates_across_an_m15_to_h4_merge() {
+        let mut agg = Aggregator::new(14_400_000); // H4 buckets
+        let buy_volumes = [3.0, 5.0, 2.0, 4.0, 1.0, 6.0, 2.0, 3.0, 5.0, 1.0, 4.0, 2.0, 3.0, 2.0, 1.0, 4.0];
+        for (i, buy_volume) in buy_volumes.iter().enumerate() {
+            agg.push(&bar(i as i64 * 900_000, 1.0), *buy_volume);
+        }
+        let finalized = agg.flush_for_backtest().unwrap();
+        assert_eq!(finalized.buy_volume, buy_volumes.iter().sum::<f64>());
+    }
+
+    #[test]
+    fn sub_bar_count_reports_a_gap_when_fewer_bars_arrive_than_expected() {
+        let mut agg = Aggregator::new(3_600_000); // H1 buckets, expecting 4 M15 bars
+        // Only 3 of the 4 expected M15 bars arrive (a gap at 2_700_000).
+        for ts in [0, 900_000, 1_800_000] {
+            agg.push(&bar(ts, 1.0), 1.0);
+        }
+        let finalized = agg.flush_for_backtest().unwrap();
+        assert_eq!(finalized.sub_bar_count, 3);
+    }
+
+    #[test]
+    fn gap_fill_synthesizes_flat_bars_for_two_entirely_missing_h1_buckets() {
+        let mut agg = Aggregator::new(3_600_000); // H1 buckets
+        agg.set_fill_gaps(true);
+        agg.push(&bar(0, 100.0), 1.0);
+
+        // The exchange drops candles for the next two H1 buckets entirely --
+        // the next bar to arrive is three buckets later, at 3h.
+        let finalized = agg.push(&bar(3 * 3_600_000, 105.0), 1.0);
+
+        // The real bucket that closed at 0h, then two synthetic flat bars
+        // for the 1h and 2h buckets that never got a real source bar.
+        assert_eq!(finalized.len(), 3);
+        assert_eq!(finalized[0].bar.ts, 0);
+        assert_eq!(finalized[0].bar.close, 100.0);
+
+        assert_eq!(finalized[1].bar.ts, 3_600_000);
+        assert_eq!(finalized[1].sub_bar_count, 0);
+        for field in [finalized[1].bar.open, finalized[1].bar.high, finalized[1].bar.low, finalized[1].bar.close] {
+            assert_eq!(field, 100.0); // flat at the previous bucket's close
+        }
+        assert_eq!(finalized[1].bar.volume, 0.0);
+
+        assert_eq!(finalized[2].bar.ts, 2 * 3_600_000);
+        assert_eq!(finalized[2].bar.close, 100.0);
+
+        assert_eq!(agg.filled_bar_count(), 2);
+    }
+
+    #[test]
+    fn reset_drops_the_forming_bucket_and_filled_count_but_keeps_configuration() {
+        let mut agg = Aggregator::new(3_600_000); // H1 buckets
+        agg.set_fill_gaps(true);
+        agg.push(&bar(0, 100.0), 1.0);
+        agg.push(&bar(3 * 3_600_000, 105.0), 1.0); // synthesizes 2 gap-filled bars
+
+        assert_eq!(agg.filled_bar_count(), 2);
+        agg.reset();
+        assert_eq!(agg.filled_bar_count(), 0);
+        // No forming bucket left over: the next bar starts a brand new one
+        // rather than merging into whatever was mid-flight before the reset.
+        assert!(agg.push(&bar(0, 1.0), 1.0).is_empty());
+        assert!(agg.flush_for_backtest().is_some());
+    }
+
+    /// See this module's doc comment: with no `MultiHQuant` in this crate,
+    /// a caller reads back a period's aggregated OHLCV by feeding that
+    /// period's `Aggregator` output into a dedicated `HQuant` and calling
+    /// `HQuant::bars()` on it, exactly as it would for any other engine.
+    #[test]
+    fn aggregated_bars_are_readable_back_through_the_downstream_hquants_own_kline_buffer() {
+        use crate::engine::HQuant;
+
+        let mut agg = Aggregator::new(14_400_000); // H4 buckets
+        let mut h4 = HQuant::new(64);
+
+        // Each H4 bucket takes 16 M15 bars (14_400_000 / 900_000), so 64 M15
+        // bars is exactly 4 full H4 buckets.
+        for i in 0..64 {
+            for finalized in agg.push(&bar(i * 900_000, 100.0 + i as f64), 1.0) {
+                h4.push_bar(finalized.bar);
+            }
+        }
+        if let Some(finalized) = agg.flush_for_backtest() {
+            h4.push_bar(finalized.bar);
+        }
+
+        assert_eq!(h4.bars().len(), 4);
+    }
+
+    #[test]
+    fn gap_fill_is_off_by_default_and_just_skips_ahead_as_before() {
+        let mut agg = Aggregator::new(3_600_000);
+        agg.push(&bar(0, 100.0), 1.0);
+        let finalized = agg.push(&bar(3 * 3_600_000, 105.0), 1.0);
+        assert_eq!(finalized.len(), 1);
+        assert_eq!(agg.filled_bar_count(), 0);
+    }
+
+    #[test]
+    fn close_if_elapsed_emits_a_fully_formed_bucket_without_a_new_bucket_bar_arriving() {
+        let mut agg = Aggregator::new(3_600_000); // H1 buckets, expecting 4 M15 bars
+        for ts in [0, 900_000, 1_800_000, 2_700_000] {
+            assert!(agg.push(&bar(ts, 1.0), 1.0).is_empty());
+        }
+        // No bar from the next bucket has arrived, but the bucket's own
+        // time window (0..3_600_000) has fully elapsed.
+        let finalized = agg.close_if_elapsed(3_600_000);
+        assert!(finalized.is_some());
+        assert_eq!(finalized.unwrap().sub_bar_count, 4);
+        // The bucket is gone, not left around to double-emit.
+        assert_eq!(agg.close_if_elapsed(3_600_000), None);
+    }
+
+    #[test]
+    fn close_if_elapsed_does_nothing_before_the_bucket_window_ends() {
+        let mut agg = Aggregator::new(3_600_000);
+        agg.push(&bar(0, 1.0), 1.0);
+        assert_eq!(agg.close_if_elapsed(1_800_000), None);
+        // The still-forming bucket is untouched: a later bar in the same
+        // window still merges into it normally.
+        agg.push(&bar(1_800_000, 1.5), 1.0);
+        let finalized = agg.flush_for_backtest().unwrap();
+        assert_eq!(finalized.sub_bar_count, 2);
+    }
+
+    #[test]
+    fn an_eight_hour_session_offset_groups_bars_eight_to_eight_instead_of_midnight_to_midnight() {
+        let mut agg = Aggregator::with_session_offset(86_400_000, 8 * 3_600_000); // D1, 08:00 reset
+        // 07:00 and 07:59 on day 1 both belong to the bucket that opened at
+        // 08:00 the *previous* day, not a midnight-aligned bucket.
+        assert!(agg.push(&bar(7 * 3_600_000, 1.0), 1.0).is_empty());
+        assert!(agg.push(&bar(7 * 3_600_000 + 3_540_000, 1.0), 1.0).is_empty());
+        // 08:00 sharp on day 1 opens the *next* bucket, finalizing the one
+        // that ran from the previous day's 08:00 through this day's 07:59.
+        let finalized = agg.push(&bar(8 * 3_600_000, 2.0), 1.0).remove(0);
+        assert_eq!(finalized.bar.ts, 8 * 3_600_000 - 86_400_000);
+        assert_eq!(finalized.sub_bar_count, 2);
+
+        let finalized = agg.flush_for_backtest().unwrap();
+        assert_eq!(finalized.bar.ts, 8 * 3_600_000);
+    }
+
+    #[test]
+    fn a_wednesday_timestamp_aligns_to_the_preceding_monday_not_the_epochs_thursday() {
+        // 1970-01-07 was a Wednesday (the epoch, 1970-01-01, was a
+        // Thursday); the ISO week it falls in started Monday 1970-01-05.
+        let mut agg = Aggregator::weekly(WeekStart::Monday);
+        let wednesday_5am = 6 * 86_400_000 + 5 * 3_600_000;
+        assert!(agg.push(&bar(wednesday_5am, 1.0), 1.0).is_empty());
+        let finalized = agg.flush_for_backtest().unwrap();
+        assert_eq!(finalized.bar.ts, 4 * 86_400_000, "expected Monday 1970-01-05, not a Thursday-aligned boundary");
+    }
+
+    #[test]
+    fn weekly_aggregation_honors_a_sunday_or_friday_week_start_instead_of_monday() {
+        // Same Wednesday bar as above, but under venues that reset the
+        // week on Sunday or Friday instead of the ISO-week Monday.
+        let wednesday_5am = 6 * 86_400_000 + 5 * 3_600_000;
+
+        let mut sunday_agg = Aggregator::weekly(WeekStart::Sunday);
+        sunday_agg.push(&bar(wednesday_5am, 1.0), 1.0);
+        assert_eq!(sunday_agg.flush_for_backtest().unwrap().bar.ts, 3 * 86_400_000); // 1970-01-04, a Sunday
+
+        let mut friday_agg = Aggregator::weekly(WeekStart::Friday);
+        friday_agg.push(&bar(wednesday_5am, 1.0), 1.0);
+        assert_eq!(friday_agg.flush_for_backtest().unwrap().bar.ts, 86_400_000); // 1970-01-02, a Friday
+    }
+
+    #[test]
+    fn update_last_recomputes_volume_and_buy_volume_from_before_last() {
+        let mut agg = Aggregator::new(3_600_000);
+        agg.push(&bar(0, 1.0), 3.0);
+        agg.push(&bar(900_000, 1.5), 5.0);
+        // Revise the second (most recent) source bar's volume and buy
+        // volume upward.
+        let mut revised = bar(900_000, 1.5);
+        revised.volume = 10.0;
+        agg.update_last(&revised, 7.0);
+        let finalized = agg.flush_for_backtest().unwrap();
+        assert_eq!(finalized.bar.volume, 1.0 + 10.0);
+        assert_eq!(finalized.buy_volume, 3.0 + 7.0);
+    }
+
+    fn bar_with_volume(ts: i64, volume: f64) -> Bar {
+        Bar { ts, open: 1.0, high: 1.0, low: 1.0, close: 1.0, volume }
+    }
+
+    #[test]
+    fn ten_bars_of_volume_100_into_a_250_threshold_yields_4_completed_bars() {
+        let mut agg = VolumeBarAggregator::new(250.0);
+        let mut completed = 0;
+        for i in 0..10 {
+            if agg.push(&bar_with_volume(i, 100.0), 1.0).is_some() {
+                completed += 1;
+            }
+        }
+        // 9 bars (900 volume) close 3 full 250-volume buckets (at the 3rd,
+        // 6th and 9th bar); the 10th bar leaves a 100-volume bucket still
+        // forming, which only a final flush emits.
+        assert_eq!(completed, 3);
+        assert!(agg.flush_for_backtest().is_some());
+        completed += 1;
+        assert_eq!(completed, 4);
+    }
+
+    #[test]
+    fn a_volume_bucket_can_close_over_threshold_since_a_source_bar_is_never_split() {
+        let mut agg = VolumeBarAggregator::new(250.0);
+        agg.push(&bar_with_volume(0, 100.0), 0.0);
+        agg.push(&bar_with_volume(1, 100.0), 0.0);
+        // This bar's volume alone pushes the bucket to 300, past 250 --
+        // it closes whole rather than being split 150/150 across buckets.
+        let finalized = agg.push(&bar_with_volume(2, 100.0), 0.0).unwrap();
+        assert_eq!(finalized.bar.volume, 300.0);
+        assert_eq!(finalized.sub_bar_count, 3);
+    }
+
+    #[test]
+    fn tick_bar_aggregator_closes_every_n_source_bars_regardless_of_volume() {
+        let mut agg = TickBarAggregator::new(3);
+        assert!(agg.push(&bar_with_volume(0, 5.0), 0.0).is_none());
+        assert!(agg.push(&bar_with_volume(1, 500.0), 0.0).is_none());
+        let finalized = agg.push(&bar_with_volume(2, 5.0), 0.0).unwrap();
+        assert_eq!(finalized.sub_bar_count, 3);
+        assert_eq!(finalized.bar.volume, 510.0);
+
+        // A fresh bucket starts counting from zero after the previous one
+        // closed.
+        assert!(agg.push(&bar_with_volume(3, 1.0), 0.0).is_none());
+        let flushed = agg.flush_for_backtest().unwrap();
+        assert_eq!(flushed.sub_bar_count, 1);
+    }
+
+    #[test]
+    fn a_monotonic_rally_emits_one_brick_per_full_brick_width_of_advance() {
+        let mut renko = RenkoBuilder::new(BrickSize::Absolute(1.0), 100.0);
+        // Ten one-point-per-bar advances: enough for exactly 10 bricks
+        // (100 -> 110), since a plain close-only rally never skips enough
+        // in one bar to trigger the 2x reversal path.
+        let mut total = 0;
+        for i in 1..=10 {
+            total += renko.push(&bar(i, 100.0 + i as f64)).len();
+        }
+        assert_eq!(total, 10);
+        assert_eq!(renko.bricks().len(), 10);
+        assert_eq!(renko.bricks().first().unwrap().open, 100.0);
+        assert_eq!(renko.bricks().last().unwrap().close, 110.0);
+    }
+
+    #[test]
+    fn a_single_bar_big_enough_move_emits_multiple_bricks_at_once() {
+        let mut renko = RenkoBuilder::new(BrickSize::Absolute(1.0), 100.0);
+        let bricks = renko.push(&bar(0, 104.0));
+        assert_eq!(bricks.len(), 4);
+        assert_eq!(renko.bricks().last().unwrap().close, 104.0);
+    }
+
+    #[test]
+    fn a_sub_brick_move_only_flips_after_crossing_two_full_bricks() {
+        let mut renko = RenkoBuilder::new(BrickSize::Absolute(1.0), 100.0);
+        renko.push(&bar(0, 101.0)); // one up brick; origin now 101, trend Up
+        // A 1.0-point pullback (less than the 2-brick-width reversal
+        // threshold) shouldn't flip.
+        assert!(renko.push(&bar(1, 100.0)).is_empty());
+        // Crossing the full 2x threshold (99.0, i.e. two bricks below the
+        // 101 origin) flips to a down brick and, since price has moved a
+        // further full brick past the flip itself, immediately continues
+        // one more brick down.
+        let bricks = renko.push(&bar(2, 99.0));
+        assert_eq!(bricks.len(), 2);
+        assert_eq!(bricks[0].close, 100.0);
+        assert_eq!(bricks[1].close, 99.0);
+    }
+
+    #[test]
+    fn flush_never_produces_a_brick_since_renko_has_no_partial_state() {
+        let mut renko = RenkoBuilder::new(BrickSize::Absolute(1.0), 100.0);
+        renko.push(&bar(0, 100.5));
+        assert!(renko.flush().is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+//! Lightweight indicator-threshold alerts: "tell me when X crosses Y"
+//! without paying for a compiled [`crate::strategy::CompiledStrategy`].
+//! An alert never produces a trading [`crate::strategy::Signal`] -- it just
+//! reuses the same crossing-detection shape as
+//! [`crate::strategy::BoolExpr::CrossAbove`]/`CrossBelow`, but against a
+//! bare threshold rather than a second `Operand`, and is polled rather than
+//! evaluated inline against a strategy's rule list.
+//!
+//! There's no separate "component" selector here: every
+//! [`crate::indicator::IndicatorGraph`] node already outputs exactly one
+//! scalar series (that's the whole point of the [`crate::indicator::IndicatorExec`]
+//! contract), and a multi-line indicator like MACD is already three
+//! independently-named nodes (main/`_signal`/`_histogram`, see
+//! [`crate::indicator::spec`]) rather than one node with several
+//! components to pick between. Indicators that genuinely need to report
+//! several jointly-computed values -- ADX, KDJ, Parabolic SAR -- live
+//! outside the graph entirely (see their module docs) and have no `NodeId`
+//! an alert could reference in the first place.
+
+use crate::indicator::NodeId;
+
+/// Opaque handle to a registered alert; only meaningful as an argument back
+/// into [`crate::engine::HQuant`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AlertId(pub(crate) u64);
+
+/// Which way the indicator must cross `level` for the alert to fire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossDirection {
+    /// Fires the bar the indicator transitions from at-or-below `level` to
+    /// strictly above it.
+    Above,
+    /// Fires the bar the indicator transitions from at-or-above `level` to
+    /// strictly below it.
+    Below,
+}
+
+/// A registered threshold alert, plus the previous bar's value needed to
+/// tell a fresh crossing from an indicator that's simply sitting past
+/// `level`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Alert {
+    pub indicator_id: NodeId,
+    level: f64,
+    direction: CrossDirection,
+    /// The indicator's value as of the previous call to `check`. `None`
+    /// before the first bar, so the alert never fires on it (there's
+    /// nothing to have crossed from yet).
+    prev_value: Option<f64>,
+}
+
+impl Alert {
+    pub(crate) fn new(indicator_id: NodeId, level: f64, direction: CrossDirection) -> Self {
+        Self { indicator_id, level, direction, prev_value: None }
+    }
+
+    /// Forget the previous bar's value, e.g. after [`crate::engine::HQuant::reset`]
+    /// clears the indicator history this alert watches -- otherwise the next
+    /// `check` would compare a fresh post-reset value against a stale
+    /// pre-reset one and could fire a spurious crossing.
+    pub(crate) fn reset(&mut self) {
+        self.prev_value = None;
+    }
+
+    /// Feed the indicator's current value, returning whether this bar is
+    /// the crossing bar. NaN-guarded and previous-value-gated the same way
+    /// [`crate::strategy`]'s `crossed` helper is, so warm-up and missing
+    /// history never produce a spurious fire.
+    pub(crate) fn check(&mut self, current: f64) -> bool {
+        let prev = self.prev_value.replace(current);
+        let Some(prev) = prev else { return false };
+        if prev.is_nan() || current.is_nan() {
+            return false;
+        }
+        match self.direction {
+            CrossDirection::Above => prev <= self.level && current > self.level,
+            CrossDirection::Below => prev >= self.level && current < self.level,
+        }
+    }
+}
@@ -0,0 +1,761 @@
+//! Drives a single strategy's signals against the bar stream, enforcing a
+//! stop-loss/take-profit bracket against each bar's high/low range so
+//! intrabar hits are caught even when the close never trades through the
+//! level. Each open position's maximum adverse/favorable excursion and bars
+//! held are rolled up the same way, from the same high/low range (see
+//! [`super::Position::update_excursion`]) and recorded onto its
+//! [`super::Trade`] once it closes.
+
+use super::portfolio::Trade;
+use super::{ExitReason, PortfolioBacktest, Position, Side};
+use crate::indicator::Atr;
+use crate::kline::Bar;
+use crate::strategy::{Action, Signal};
+
+/// Which per-trade return series [`super::BacktestStats::calculate`] builds
+/// its Sharpe ratio from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReturnType {
+    /// `pnl / entry_price`. Standard, but understates compounding and
+    /// treats a gain and an equal-sized loss as symmetric even though a
+    /// loss needs a larger subsequent gain to recover from it.
+    #[default]
+    Simple,
+    /// `ln(1 + pnl / entry_price)`, i.e. `ln(equity_t / equity_{t-1})` for
+    /// an equity curve that compounds trade returns multiplicatively.
+    /// More appropriate once returns are compounded rather than summed.
+    Log,
+}
+
+/// Whether a fill crossed the book immediately (`Market`, paying
+/// `taker_fee_pct`) or rested until matched (`Limit`, paying the lower
+/// `maker_fee_pct`). See [`BacktestEngine::calculate_fee`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrderKind {
+    #[default]
+    Market,
+    Limit,
+}
+
+/// How far a fill is pushed away from the quoted price (bar close for
+/// entries, the stop/target level for exits) before fees are applied. All
+/// three models cost the trader, never help: a buy always fills higher,
+/// a sell always fills lower. See [`BacktestEngine::apply_slippage`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlippageModel {
+    /// A flat percentage of the fill price.
+    Fixed(f64),
+    /// `k_atr` times the engine's own Wilder-smoothed true-range estimate
+    /// (see [`BacktestEngine::observe_atr`]), as an absolute price offset
+    /// rather than a percentage -- volatility is already in price units.
+    Volatility(f64),
+    /// `k` percent, scaled by the ratio of [`BacktestConfig::order_size`]
+    /// to the current bar's volume -- a cheap market-impact proxy: the
+    /// same order moves a thin bar more than a deep one.
+    VolumeImpact(f64),
+}
+
+impl Default for SlippageModel {
+    fn default() -> Self {
+        SlippageModel::Fixed(0.0)
+    }
+}
+
+/// Backtest-wide defaults applied to any entry that doesn't already carry
+/// its own bracket via the DSL's `WITH STOP x% TARGET y%`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestConfig {
+    pub stop_loss_pct: Option<f64>,
+    pub take_profit_pct: Option<f64>,
+    /// Return series [`super::BacktestStats::calculate`] uses for its
+    /// Sharpe ratio; defaults to [`ReturnType::Simple`].
+    pub return_type: ReturnType,
+    /// Fee rate (percent of fill price) for an [`OrderKind::Market`] fill,
+    /// before any [`BacktestConfig::fee_tiers`] discount is applied.
+    pub taker_fee_pct: f64,
+    /// Fee rate (percent of fill price) for an [`OrderKind::Limit`] fill.
+    /// Not affected by `fee_tiers` -- see that field's doc comment.
+    pub maker_fee_pct: f64,
+    /// Volume tiers as `(cumulative_volume_threshold, taker_fee_pct)`,
+    /// checked against [`BacktestEngine`]'s cumulative traded volume so
+    /// far: the highest threshold met replaces `taker_fee_pct` for the
+    /// next fill. Doesn't discount `maker_fee_pct` -- real venues usually
+    /// already quote makers close to their best rate. Order doesn't
+    /// matter; empty (the default) leaves the taker fee flat.
+    pub fee_tiers: Vec<(f64, f64)>,
+    /// Slippage model applied to every fill; defaults to
+    /// [`SlippageModel::Fixed`]`(0.0)`, i.e. no slippage.
+    pub slippage_model: SlippageModel,
+    /// ATR period for [`SlippageModel::Volatility`]; ignored by the other
+    /// models. Must be `> 0` whenever `slippage_model` is `Volatility`.
+    pub atr_period: usize,
+    /// Order size fed to [`SlippageModel::VolumeImpact`]'s ratio against a
+    /// bar's volume; ignored by the other models. Like
+    /// [`BacktestEngine`]'s own `cumulative_volume`, this engine has no
+    /// real order-quantity concept, so it's a fixed per-fill notional the
+    /// caller supplies.
+    pub order_size: f64,
+    /// How the caller should size the next entry; see
+    /// [`super::PositionSizing`] for why this engine only computes the
+    /// sizing math rather than applying it to fills itself. Unlike every
+    /// other field on this struct, `BacktestEngine` never reads this one --
+    /// [`BacktestEngine::process_signal`]/`process_signal_with_kind` fill
+    /// every entry at the same one-unit size regardless of what this is set
+    /// to. It's exposed here purely so a `BacktestConfig` can carry the
+    /// sizing choice alongside the rest of a strategy's settings; call
+    /// [`super::PositionSizing::calculate_position_size`] yourself and scale
+    /// your own order quantity with the result. Defaults to full
+    /// fixed-fraction size, matching this engine's behavior before
+    /// `PositionSizing` existed.
+    pub position_sizing: super::PositionSizing,
+    /// Whether a `Buy` signal is allowed to open a new long. Doesn't affect
+    /// closing an existing long -- only entries. Defaults to `true`.
+    pub allow_long: bool,
+    /// Whether a `Sell` signal is allowed to open a new short, the mirror of
+    /// `allow_long`. For a mandate that's long-only (or short-only), set the
+    /// other side's flag to `false`; a disallowed signal is simply dropped,
+    /// same as `Hold`.
+    pub allow_short: bool,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            stop_loss_pct: None,
+            take_profit_pct: None,
+            return_type: ReturnType::default(),
+            taker_fee_pct: 0.0,
+            maker_fee_pct: 0.0,
+            fee_tiers: Vec::new(),
+            slippage_model: SlippageModel::default(),
+            atr_period: 0,
+            order_size: 0.0,
+            position_sizing: super::PositionSizing::default(),
+            allow_long: true,
+            allow_short: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestEngine {
+    strategy_id: u32,
+    config: BacktestConfig,
+    position: Option<Position>,
+    pub portfolio: PortfolioBacktest,
+    /// Sum of fill prices (see [`BacktestEngine::calculate_fee`]) traded so
+    /// far, checked against [`BacktestConfig::fee_tiers`]. This engine has
+    /// no notion of order quantity, so "volume" here is a per-unit-price
+    /// proxy consistent with `stop_loss_pct`/`take_profit_pct` already
+    /// being percentages of price rather than of cash notional.
+    cumulative_volume: f64,
+    /// Wilder-smoothed ATR for [`SlippageModel::Volatility`], via
+    /// [`crate::indicator::Atr`] -- only allocated when the config
+    /// actually asks for it.
+    atr: Option<Atr>,
+    /// Most recent value read out of `atr`, refreshed once per bar by
+    /// [`BacktestEngine::observe_atr`] so a fill mid-bar doesn't need to
+    /// push into `atr` a second time (it's only meant to consume one bar
+    /// per call).
+    current_atr: f64,
+    /// Previous bar seen, used by [`BacktestEngine::benchmark_return_pct`]
+    /// for its "most recent close" -- `atr`, when present, tracks its own
+    /// previous bar internally for the true-range recurrence.
+    prev_bar: Option<Bar>,
+    /// Close of the first bar ever passed to this engine, kept alongside
+    /// `prev_bar`'s close (the most recent one) so [`BacktestEngine::benchmark_return_pct`]
+    /// can report what a buy-and-hold over the same bars would have
+    /// returned, without this engine needing to remember the whole bar
+    /// history itself.
+    first_close: Option<f64>,
+}
+
+impl BacktestEngine {
+    pub fn new(strategy_id: u32, config: BacktestConfig) -> Self {
+        let atr = match config.slippage_model {
+            SlippageModel::Volatility(_) => {
+                assert!(config.atr_period > 0, "atr_period must be > 0 when slippage_model is Volatility");
+                Some(Atr::new(config.atr_period))
+            }
+            _ => None,
+        };
+        Self {
+            strategy_id,
+            config,
+            position: None,
+            portfolio: PortfolioBacktest::default(),
+            cumulative_volume: 0.0,
+            atr,
+            current_atr: 0.0,
+            prev_bar: None,
+            first_close: None,
+        }
+    }
+
+    /// Roll `bar` into `atr` (if [`SlippageModel::Volatility`] is
+    /// configured) and remember it as `prev_bar` for the next call. Called
+    /// once per bar, before any fill on that bar is priced, so
+    /// `current_atr` reflects the bar the fill is happening on.
+    fn observe_atr(&mut self, bar: &Bar) {
+        if let Some(atr) = self.atr.as_mut() {
+            self.current_atr = atr.push(bar);
+        }
+        self.first_close.get_or_insert(bar.close);
+        self.prev_bar = Some(*bar);
+    }
+
+    /// Buy-and-hold return, as a percent, over every bar this engine has
+    /// seen: `100 * (last_close - first_close) / first_close`. `NaN` before
+    /// any bar has been processed, the same "nothing to divide by"
+    /// convention [`super::BacktestStats::sortino`] uses.
+    pub fn benchmark_return_pct(&self) -> f64 {
+        match (self.first_close, self.prev_bar) {
+            (Some(first), Some(last)) => 100.0 * (last.close - first) / first,
+            _ => f64::NAN,
+        }
+    }
+
+    /// Adjusted fill price after [`BacktestConfig::slippage_model`]: a buy
+    /// always fills higher than quoted, a sell always fills lower.
+    fn apply_slippage(&self, price: f64, is_buy: bool, bar: &Bar) -> f64 {
+        let offset = match self.config.slippage_model {
+            SlippageModel::Fixed(pct) => price * pct / 100.0,
+            SlippageModel::Volatility(k_atr) => k_atr * self.current_atr,
+            SlippageModel::VolumeImpact(k) => {
+                if bar.volume > 0.0 {
+                    price * k * (self.config.order_size / bar.volume) / 100.0
+                } else {
+                    0.0
+                }
+            }
+        };
+        if is_buy {
+            price + offset
+        } else {
+            price - offset
+        }
+    }
+
+    /// Fee owed on a fill at `price`, in the same units as `price` (`price`
+    /// times the percent rate, divided by 100) -- [`OrderKind::Market`] pays the tiered taker rate,
+    /// [`OrderKind::Limit`] pays the flat maker rate.
+    pub fn calculate_fee(&self, price: f64, order_kind: OrderKind) -> f64 {
+        let rate_pct = match order_kind {
+            OrderKind::Market => self.tiered_taker_fee_pct(),
+            OrderKind::Limit => self.config.maker_fee_pct,
+        };
+        price * rate_pct / 100.0
+    }
+
+    /// `taker_fee_pct`, overridden by the highest [`BacktestConfig::fee_tiers`]
+    /// threshold `cumulative_volume` has already reached.
+    fn tiered_taker_fee_pct(&self) -> f64 {
+        self.config
+            .fee_tiers
+            .iter()
+            .filter(|(threshold, _)| self.cumulative_volume >= *threshold)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, rate)| *rate)
+            .unwrap_or(self.config.taker_fee_pct)
+    }
+
+    /// Like [`BacktestEngine::process_signal`], but choosing the
+    /// [`OrderKind`] a new entry fills as; `process_signal` itself always
+    /// assumes [`OrderKind::Market`]. Exits (stop/target hits) are always
+    /// charged as [`OrderKind::Market`] regardless: a resting bracket level
+    /// getting crossed by the market is a taker fill by definition.
+    pub fn process_signal_with_kind(&mut self, signal: &Signal, bar: &Bar, order_kind: OrderKind) {
+        self.observe_atr(bar);
+        if let Some(position) = self.position.as_mut() {
+            position.bars_held += 1;
+            position.update_excursion(bar);
+        }
+        if let Some(position) = self.position {
+            if let Some((reason, raw_exit_price)) = Self::intrabar_hit(&position, bar) {
+                // Closing a long sells (fills lower); closing a short buys
+                // it back (fills higher).
+                let exit_price = self.apply_slippage(raw_exit_price, position.side == Side::Short, bar);
+                let exit_fee = self.calculate_fee(exit_price, OrderKind::Market);
+                self.cumulative_volume += exit_price;
+                self.portfolio.record(Trade {
+                    strategy_id: self.strategy_id,
+                    side: position.side,
+                    entry_price: position.entry_price,
+                    exit_price,
+                    exit_reason: Some(reason),
+                    exit_ts: bar.ts,
+                    mae: position.mae,
+                    mfe: position.mfe,
+                    bars_held: position.bars_held,
+                    fee: (position.entry_fee + exit_fee) * position.remaining_fraction,
+                    size: position.remaining_fraction,
+                    partial: false,
+                });
+                self.position = None;
+                return;
+            }
+        }
+        if signal.action == Action::Close && self.position.is_some() {
+            self.close_fraction(bar, bar.close, 1.0);
+            return;
+        }
+        if self.position.is_none() {
+            let side = match signal.action {
+                Action::Buy if self.config.allow_long => Some(Side::Long),
+                Action::Sell if self.config.allow_short => Some(Side::Short),
+                Action::Buy | Action::Sell | Action::Hold | Action::Close | Action::Guard => None,
+            };
+            if let Some(side) = side {
+                let fill_price = self.apply_slippage(bar.close, side == Side::Long, bar);
+                let mut position = Position::open(side, fill_price, signal.bracket);
+                position.entry_fee = self.calculate_fee(position.entry_price, order_kind);
+                self.cumulative_volume += position.entry_price;
+                self.position = Some(self.apply_config_bracket(position));
+            }
+        }
+    }
+
+    /// Process one signal against the bar it was evaluated on: while a
+    /// position is open, check `bar`'s high/low range against its stop and
+    /// target before considering a new entry; a bar that hits both is
+    /// resolved pessimistically (stop first). Any new entry fills as
+    /// [`OrderKind::Market`] -- see [`BacktestEngine::process_signal_with_kind`]
+    /// to choose otherwise.
+    pub fn process_signal(&mut self, signal: &Signal, bar: &Bar) {
+        self.process_signal_with_kind(signal, bar, OrderKind::Market);
+    }
+
+    /// Manually close `fraction` (of the position's original size) at
+    /// `price`, e.g. a strategy taking profit in tranches instead of all at
+    /// once. `fraction` is clamped to whatever's left open, so a caller
+    /// scaling out 50% then 60% of the original size just closes the
+    /// remaining 50% on the second call rather than erroring. A no-op if
+    /// there's no open position or `fraction` clamps to `0.0`.
+    ///
+    /// PnL and fees are realized proportionally on the closed size (see
+    /// [`super::portfolio::Trade::size`]), and the remainder stays open at
+    /// the same entry price and bracket -- closing everything left behaves
+    /// exactly like a bracket hit, just recorded with `exit_reason: None`
+    /// the same way an explicit `CLOSE` signal would be, since neither is a
+    /// stop or target level getting crossed. Mapping a signal's own
+    /// strength to `fraction` (rather than always fully closing on `CLOSE`)
+    /// is left to the caller -- `Signal` carries no such strength field.
+    pub fn close_fraction(&mut self, bar: &Bar, price: f64, fraction: f64) {
+        let Some(position) = self.position else { return };
+        let fraction = fraction.clamp(0.0, position.remaining_fraction);
+        if fraction <= 0.0 {
+            return;
+        }
+
+        // Closing a long sells (fills lower); closing a short buys it back
+        // (fills higher), the same convention `intrabar_hit`'s caller uses.
+        let exit_price = self.apply_slippage(price, position.side == Side::Short, bar);
+        let exit_fee = self.calculate_fee(exit_price, OrderKind::Market) * fraction;
+        let entry_fee = position.entry_fee * fraction;
+        self.cumulative_volume += exit_price * fraction;
+
+        let remaining = position.remaining_fraction - fraction;
+        let closes_everything_left = remaining <= 1e-9;
+
+        self.portfolio.record(Trade {
+            strategy_id: self.strategy_id,
+            side: position.side,
+            entry_price: position.entry_price,
+            exit_price,
+            exit_reason: None,
+            exit_ts: bar.ts,
+            mae: position.mae,
+            mfe: position.mfe,
+            bars_held: position.bars_held,
+            fee: entry_fee + exit_fee,
+            size: fraction,
+            partial: !closes_everything_left,
+        });
+
+        if closes_everything_left {
+            self.position = None;
+        } else {
+            self.position.as_mut().unwrap().remaining_fraction = remaining;
+        }
+    }
+
+    /// Fill in a stop/target from `config` for whichever side of the
+    /// bracket the signal's own (DSL) bracket left unset.
+    fn apply_config_bracket(&self, mut position: Position) -> Position {
+        if position.stop_price.is_none() {
+            position.stop_price = self.config.stop_loss_pct.map(|pct| match position.side {
+                Side::Long => position.entry_price * (1.0 - pct / 100.0),
+                Side::Short => position.entry_price * (1.0 + pct / 100.0),
+            });
+        }
+        if position.target_price.is_none() {
+            position.target_price = self.config.take_profit_pct.map(|pct| match position.side {
+                Side::Long => position.entry_price * (1.0 + pct / 100.0),
+                Side::Short => position.entry_price * (1.0 - pct / 100.0),
+            });
+        }
+        position
+    }
+
+    /// Whether `bar`'s high/low range crosses `position`'s stop or target,
+    /// checking the stop first: a bar that gaps through both in one move
+    /// should count as a loss, not a win.
+    fn intrabar_hit(position: &Position, bar: &Bar) -> Option<(ExitReason, f64)> {
+        match position.side {
+            Side::Long => {
+                if let Some(stop) = position.stop_price {
+                    if bar.low <= stop {
+                        return Some((ExitReason::Stop, stop));
+                    }
+                }
+                if let Some(target) = position.target_price {
+                    if bar.high >= target {
+                        return Some((ExitReason::Target, target));
+                    }
+                }
+            }
+            Side::Short => {
+                if let Some(stop) = position.stop_price {
+                    if bar.high >= stop {
+                        return Some((ExitReason::Stop, stop));
+                    }
+                }
+                if let Some(target) = position.target_price {
+                    if bar.low <= target {
+                        return Some((ExitReason::Target, target));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Capture this engine's state -- its open position (with bracket) and
+    /// closed-trade history -- so it can be persisted and later resumed
+    /// with [`BacktestEngine::restore`] exactly where it left off.
+    ///
+    /// This engine only ever holds one bracketed position at a time (see
+    /// the module docs): an entry's stop/target are resolved to absolute
+    /// prices atomically at fill time rather than resting as separate
+    /// limit orders, so there's no separate resting-order book to persist
+    /// beyond `position` itself.
+    pub fn snapshot(&self) -> BacktestEngineSnapshot {
+        BacktestEngineSnapshot {
+            strategy_id: self.strategy_id,
+            config: self.config.clone(),
+            position: self.position,
+            portfolio: self.portfolio.clone(),
+            cumulative_volume: self.cumulative_volume,
+            atr: self.atr.clone(),
+            current_atr: self.current_atr,
+            prev_bar: self.prev_bar,
+            first_close: self.first_close,
+        }
+    }
+
+    /// Rebuild an engine from a [`BacktestEngine::snapshot`], resuming with
+    /// the same open position, bracket and closed-trade history it was
+    /// snapshotted with.
+    pub fn restore(snapshot: BacktestEngineSnapshot) -> Self {
+        Self {
+            strategy_id: snapshot.strategy_id,
+            config: snapshot.config,
+            position: snapshot.position,
+            portfolio: snapshot.portfolio,
+            cumulative_volume: snapshot.cumulative_volume,
+            atr: snapshot.atr,
+            current_atr: snapshot.current_atr,
+            prev_bar: snapshot.prev_bar,
+            first_close: snapshot.first_close,
+        }
+    }
+}
+
+/// Point-in-time state produced by [`BacktestEngine::snapshot`] and
+/// consumed by [`BacktestEngine::restore`].
+#[derive(Debug, Clone)]
+pub struct BacktestEngineSnapshot {
+    strategy_id: u32,
+    config: BacktestConfig,
+    position: Option<Position>,
+    portfolio: PortfolioBacktest,
+    cumulative_volume: f64,
+    atr: Option<Atr>,
+    current_atr: f64,
+    prev_bar: Option<Bar>,
+    first_close: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(open: f64, high: f64, low: f64, close: f64) -> Bar {
+        Bar { ts: 0, open, high, low, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn a_low_piercing_the_stop_closes_the_position_at_the_stop_price() {
+        let config = BacktestConfig { stop_loss_pct: Some(2.0), take_profit_pct: Some(5.0), ..Default::default() };
+        let mut engine = BacktestEngine::new(1, config);
+
+        engine.process_signal(&Signal { action: Action::Buy, bracket: None }, &bar(100.0, 100.0, 100.0, 100.0));
+        assert_eq!(engine.portfolio.trades.len(), 0);
+
+        // Low pierces the 2% stop (98.0) even though the close doesn't.
+        engine.process_signal(&Signal { action: Action::Hold, bracket: None }, &bar(99.0, 99.5, 97.5, 99.0));
+
+        assert_eq!(engine.portfolio.trades.len(), 1);
+        let trade = engine.portfolio.trades[0];
+        assert_eq!(trade.exit_reason, Some(ExitReason::Stop));
+        assert!((trade.exit_price - 98.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_restored_engine_exits_a_snapshotted_position_exactly_like_a_non_restored_one() {
+        let config = BacktestConfig { stop_loss_pct: Some(2.0), take_profit_pct: Some(5.0), ..Default::default() };
+        let mut engine = BacktestEngine::new(1, config);
+        engine.process_signal(&Signal { action: Action::Buy, bracket: None }, &bar(100.0, 100.0, 100.0, 100.0));
+
+        let mut restored = BacktestEngine::restore(engine.snapshot());
+
+        // Low pierces the 2% stop (98.0) even though the close doesn't.
+        let piercing_bar = bar(99.0, 99.5, 97.5, 99.0);
+        engine.process_signal(&Signal { action: Action::Hold, bracket: None }, &piercing_bar);
+        restored.process_signal(&Signal { action: Action::Hold, bracket: None }, &piercing_bar);
+
+        assert_eq!(restored.portfolio.trades, engine.portfolio.trades);
+        assert_eq!(restored.portfolio.trades.len(), 1);
+        assert_eq!(restored.portfolio.trades[0].exit_reason, Some(ExitReason::Stop));
+    }
+
+    #[test]
+    fn a_dip_then_recovery_before_a_profitable_exit_records_negative_mae_and_positive_mfe() {
+        let config = BacktestConfig { stop_loss_pct: Some(10.0), take_profit_pct: Some(5.0), ..Default::default() };
+        let mut engine = BacktestEngine::new(1, config);
+
+        engine.process_signal(&Signal { action: Action::Buy, bracket: None }, &bar(100.0, 100.0, 100.0, 100.0));
+        // Dips to 97 (a -3% adverse excursion) intrabar, closes flat.
+        engine.process_signal(&Signal { action: Action::Hold, bracket: None }, &bar(100.0, 100.0, 97.0, 99.0));
+        // Recovers and hits the 5% target (105.0) intrabar.
+        engine.process_signal(&Signal { action: Action::Hold, bracket: None }, &bar(99.0, 106.0, 99.0, 104.0));
+
+        assert_eq!(engine.portfolio.trades.len(), 1);
+        let trade = engine.portfolio.trades[0];
+        assert_eq!(trade.exit_reason, Some(ExitReason::Target));
+        assert!(trade.mae < 0.0, "mae={}", trade.mae);
+        assert!(trade.mfe > 0.0, "mfe={}", trade.mfe);
+        assert_eq!(trade.bars_held, 2);
+    }
+
+    #[test]
+    fn closing_half_then_the_rest_realizes_the_same_total_pnl_and_fee_as_one_full_close() {
+        let config = BacktestConfig { taker_fee_pct: 0.1, ..Default::default() };
+
+        let mut scaled_out = BacktestEngine::new(1, config.clone());
+        scaled_out.process_signal(&Signal { action: Action::Buy, bracket: None }, &bar(100.0, 100.0, 100.0, 100.0));
+        scaled_out.close_fraction(&bar(100.0, 100.0, 100.0, 100.0), 110.0, 0.5);
+        scaled_out.close_fraction(&bar(100.0, 100.0, 100.0, 100.0), 120.0, 0.5);
+
+        assert_eq!(scaled_out.portfolio.trades.len(), 2);
+        assert!(scaled_out.portfolio.trades[0].partial);
+        assert!(!scaled_out.portfolio.trades[1].partial);
+        assert!(scaled_out.position.is_none());
+
+        // A single full close at the blended price a 50/50 split of 110
+        // and 120 implies (115.0) realizes the same total PnL and fee.
+        let mut full_close = BacktestEngine::new(1, config);
+        full_close.process_signal(&Signal { action: Action::Buy, bracket: None }, &bar(100.0, 100.0, 100.0, 100.0));
+        full_close.close_fraction(&bar(100.0, 100.0, 100.0, 100.0), 115.0, 1.0);
+
+        let scaled_pnl: f64 = scaled_out.portfolio.trades.iter().map(Trade::pnl).sum();
+        let scaled_fee: f64 = scaled_out.portfolio.trades.iter().map(|t| t.fee).sum();
+        let full_pnl = full_close.portfolio.trades[0].pnl();
+        let full_fee = full_close.portfolio.trades[0].fee;
+
+        assert!((scaled_pnl - full_pnl).abs() < 1e-9, "scaled_pnl={scaled_pnl} full_pnl={full_pnl}");
+        assert!((scaled_fee - full_fee).abs() < 1e-9, "scaled_fee={scaled_fee} full_fee={full_fee}");
+    }
+
+    #[test]
+    fn close_fraction_clamps_to_whatever_is_left_open() {
+        let mut engine = BacktestEngine::new(1, BacktestConfig::default());
+        engine.process_signal(&Signal { action: Action::Buy, bracket: None }, &bar(100.0, 100.0, 100.0, 100.0));
+
+        engine.close_fraction(&bar(100.0, 100.0, 100.0, 100.0), 110.0, 0.5);
+        // Asking for 60% more than the 50% already closed clamps to the
+        // 50% actually left, rather than erroring.
+        engine.close_fraction(&bar(100.0, 100.0, 100.0, 100.0), 120.0, 0.6);
+
+        assert_eq!(engine.portfolio.trades.len(), 2);
+        assert!((engine.portfolio.trades[1].size - 0.5).abs() < 1e-9);
+        assert!(engine.position.is_none());
+    }
+
+    #[test]
+    fn close_fraction_is_a_no_op_with_no_open_position() {
+        let mut engine = BacktestEngine::new(1, BacktestConfig::default());
+        engine.close_fraction(&bar(100.0, 100.0, 100.0, 100.0), 110.0, 0.5);
+        assert_eq!(engine.portfolio.trades.len(), 0);
+    }
+
+    #[test]
+    fn a_limit_entry_is_charged_the_maker_rate_not_the_taker_rate() {
+        let config = BacktestConfig { taker_fee_pct: 0.1, maker_fee_pct: 0.02, ..Default::default() };
+        let mut engine = BacktestEngine::new(1, config);
+
+        engine.process_signal_with_kind(
+            &Signal { action: Action::Buy, bracket: None },
+            &bar(100.0, 100.0, 100.0, 100.0),
+            OrderKind::Limit,
+        );
+
+        let position = engine.position.unwrap();
+        assert!((position.entry_fee - 0.02).abs() < 1e-9, "entry_fee={}", position.entry_fee);
+    }
+
+    #[test]
+    fn a_market_entry_is_charged_the_taker_rate() {
+        let config = BacktestConfig { taker_fee_pct: 0.1, maker_fee_pct: 0.02, ..Default::default() };
+        let mut engine = BacktestEngine::new(1, config);
+
+        engine.process_signal_with_kind(
+            &Signal { action: Action::Buy, bracket: None },
+            &bar(100.0, 100.0, 100.0, 100.0),
+            OrderKind::Market,
+        );
+
+        let position = engine.position.unwrap();
+        assert!((position.entry_fee - 0.1).abs() < 1e-9, "entry_fee={}", position.entry_fee);
+    }
+
+    #[test]
+    fn crossing_a_volume_tier_lowers_the_taker_rate_on_the_next_fill() {
+        let config = BacktestConfig {
+            taker_fee_pct: 0.1,
+            fee_tiers: vec![(150.0, 0.05)], // once cumulative volume >= 150, taker fee drops to 0.05%
+            ..Default::default()
+        };
+        let mut engine = BacktestEngine::new(1, config);
+
+        // First entry: cumulative volume is still 0, so the base 0.1% rate applies.
+        engine.process_signal_with_kind(
+            &Signal { action: Action::Buy, bracket: None },
+            &bar(100.0, 100.0, 100.0, 100.0),
+            OrderKind::Market,
+        );
+        assert!((engine.position.unwrap().entry_fee - 0.1).abs() < 1e-9);
+        // No stop/target configured: an explicit close would be needed to
+        // exit for real, but for this test we just drop the open position
+        // and cumulative volume (100.0 from the entry above) carries over.
+        engine.position = None;
+
+        // Second entry: cumulative volume from the first fill alone (100.0)
+        // hasn't crossed the 150 tier yet, so this still pays the base rate.
+        engine.process_signal_with_kind(
+            &Signal { action: Action::Buy, bracket: None },
+            &bar(60.0, 60.0, 60.0, 60.0),
+            OrderKind::Market,
+        );
+        assert!((engine.position.unwrap().entry_fee - 0.06).abs() < 1e-9, "cumulative volume is 100, tier needs 150");
+        engine.position = None;
+
+        // Cumulative volume is now 160 (100 + 60), past the 150 tier: the
+        // next fill should use the discounted 0.05% rate.
+        engine.process_signal_with_kind(
+            &Signal { action: Action::Buy, bracket: None },
+            &bar(100.0, 100.0, 100.0, 100.0),
+            OrderKind::Market,
+        );
+        assert!((engine.position.unwrap().entry_fee - 0.05).abs() < 1e-9, "entry_fee={}", engine.position.unwrap().entry_fee);
+    }
+
+    #[test]
+    fn fixed_slippage_fills_a_long_entry_above_the_bar_close() {
+        let config = BacktestConfig { slippage_model: SlippageModel::Fixed(1.0), ..Default::default() };
+        let mut engine = BacktestEngine::new(1, config);
+
+        engine.process_signal(&Signal { action: Action::Buy, bracket: None }, &bar(100.0, 100.0, 100.0, 100.0));
+
+        // 1% of 100.0 fills 1.0 above the quoted close.
+        assert!((engine.position.unwrap().entry_price - 101.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn volatility_slippage_scales_with_the_engines_own_atr_estimate() {
+        let config =
+            BacktestConfig { slippage_model: SlippageModel::Volatility(2.0), atr_period: 3, ..Default::default() };
+        let mut engine = BacktestEngine::new(1, config);
+
+        // First bar: no prior close, so true range is just this bar's own
+        // high-low range (10.0), and `Atr` with no committed state yet
+        // returns that true range directly.
+        engine.process_signal(&Signal { action: Action::Hold, bracket: None }, &bar(100.0, 105.0, 95.0, 100.0));
+        engine.process_signal(&Signal { action: Action::Buy, bracket: None }, &bar(100.0, 105.0, 95.0, 100.0));
+
+        let expected_offset = 2.0 * engine.current_atr;
+        assert!(expected_offset > 0.0);
+        assert!((engine.position.unwrap().entry_price - (100.0 + expected_offset)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn volume_impact_slippage_moves_a_thin_bar_more_than_a_deep_one() {
+        let deep_config = BacktestConfig {
+            slippage_model: SlippageModel::VolumeImpact(10.0),
+            order_size: 50.0,
+            ..Default::default()
+        };
+        let mut deep_engine = BacktestEngine::new(1, deep_config);
+        deep_engine.process_signal(
+            &Signal { action: Action::Buy, bracket: None },
+            &Bar { ts: 0, open: 100.0, high: 100.0, low: 100.0, close: 100.0, volume: 1000.0 },
+        );
+
+        let thin_config = BacktestConfig {
+            slippage_model: SlippageModel::VolumeImpact(10.0),
+            order_size: 50.0,
+            ..Default::default()
+        };
+        let mut thin_engine = BacktestEngine::new(1, thin_config);
+        thin_engine.process_signal(
+            &Signal { action: Action::Buy, bracket: None },
+            &Bar { ts: 0, open: 100.0, high: 100.0, low: 100.0, close: 100.0, volume: 10.0 },
+        );
+
+        let deep_fill = deep_engine.position.unwrap().entry_price;
+        let thin_fill = thin_engine.position.unwrap().entry_price;
+        assert!(thin_fill > deep_fill, "thin_fill={thin_fill} deep_fill={deep_fill}");
+    }
+
+    #[test]
+    fn a_sell_signal_opens_nothing_with_shorting_disallowed() {
+        let config = BacktestConfig { allow_short: false, ..Default::default() };
+        let mut engine = BacktestEngine::new(1, config);
+        engine.process_signal(&Signal { action: Action::Sell, bracket: None }, &bar(100.0, 101.0, 99.0, 100.0));
+        assert!(engine.position.is_none());
+    }
+
+    #[test]
+    fn a_buy_signal_opens_nothing_with_longing_disallowed() {
+        let config = BacktestConfig { allow_long: false, ..Default::default() };
+        let mut engine = BacktestEngine::new(1, config);
+        engine.process_signal(&Signal { action: Action::Buy, bracket: None }, &bar(100.0, 101.0, 99.0, 100.0));
+        assert!(engine.position.is_none());
+    }
+
+    #[test]
+    fn a_disallowed_side_doesnt_block_closing_an_existing_position_via_its_bracket() {
+        let config = BacktestConfig {
+            stop_loss_pct: Some(2.0),
+            allow_short: false,
+            ..Default::default()
+        };
+        let mut engine = BacktestEngine::new(1, config);
+        engine.process_signal(&Signal { action: Action::Buy, bracket: None }, &bar(100.0, 100.0, 100.0, 100.0));
+        assert!(engine.position.is_some());
+        // `allow_short` only gates opening a new short via a `Sell` signal --
+        // it has no bearing on an already-open long hitting its own stop.
+        engine.process_signal(&Signal { action: Action::Hold, bracket: None }, &bar(97.0, 97.0, 97.0, 97.0));
+        assert!(engine.position.is_none(), "the stop hit should still close the open long");
+    }
+}
@@ -0,0 +1,513 @@
+//! Funding accrual (and leveraged liquidation) for perpetual futures, kept
+//! separate from [`super::engine::BacktestEngine`]'s stop/target-triggered
+//! trade log since funding settles on its own wall-clock schedule
+//! (typically every 8h) rather than in response to a price move.
+//!
+//! Unlike [`super::engine::BacktestEngine`], `FuturesBacktest` doesn't model
+//! fees; it only tracks `cash`, the funding accrued against an open position, and (for a
+//! leveraged position) whether a bar's range has liquidated it.
+//!
+//! There's no Python binding of this type, but [`crate::ffi::c`] (behind
+//! the `c_abi` feature) wraps it alongside its wrap of the live
+//! [`crate::engine::HQuant`] indicator/strategy engine, so a dashboard can
+//! read [`FuturesBacktest::equity_curve`]/[`FuturesBacktest::current_position`]/
+//! [`FuturesBacktest::trades`] without linking this crate directly.
+
+use super::Side;
+use crate::common::CircularColumn;
+
+/// One closed leg of a [`FuturesBacktest`] position, recorded by
+/// [`FuturesBacktest::close_at`]. [`FuturesBacktest`] doesn't model fees
+/// (see the module doc comment), so unlike
+/// [`super::portfolio::Trade::fee`] there's no fee to report here.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FuturesTrade {
+    pub side: Side,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub quantity: f64,
+}
+
+impl FuturesTrade {
+    pub fn pnl(&self) -> f64 {
+        match self.side {
+            Side::Long => (self.exit_price - self.entry_price) * self.quantity,
+            Side::Short => (self.entry_price - self.exit_price) * self.quantity,
+        }
+    }
+}
+
+/// A snapshot of a [`FuturesBacktest`]'s currently open position, for a
+/// caller (e.g. a dashboard) that wants the position's shape without
+/// reaching into the engine's own private fields. `margin` is the
+/// notional divided by leverage, the isolated margin
+/// [`FuturesBacktest::liquidation_price_long`]/`liquidation_price_short`
+/// are computed against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionSnapshot {
+    pub side: Side,
+    pub entry_price: f64,
+    pub quantity: f64,
+    pub margin: f64,
+}
+
+/// Backtest-wide parameters for a [`FuturesBacktest`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BacktestParams {
+    /// Default funding rate per interval, e.g. `0.0001` for 1bp every 8h.
+    /// [`FuturesBacktest::accrue_funding`] takes its own `rate` argument so
+    /// a caller can still apply a different observed rate per interval.
+    pub funding_rate: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct FuturesBacktest {
+    params: BacktestParams,
+    cash: f64,
+    side: Option<Side>,
+    quantity: f64,
+    entry_price: f64,
+    leverage: f64,
+    /// Number of times [`FuturesBacktest::check_liquidation`] has
+    /// force-closed a position.
+    pub liquidations: u32,
+    /// Mark-to-market equity (`cash` plus unrealized PnL) sampled once per
+    /// [`FuturesBacktest::on_price`] call, oldest first once wrapped -- the
+    /// same fixed-capacity ring buffer [`crate::engine::HQuant`] uses for
+    /// its own bar/indicator history, so a long-running backtest's memory
+    /// stays bounded by `equity_capacity` rather than growing with every
+    /// price tick.
+    equity_curve: CircularColumn<f64>,
+    /// Every leg closed by [`FuturesBacktest::close_at`] (including one
+    /// force-closed by [`FuturesBacktest::check_liquidation`]), oldest
+    /// first -- unbounded, the same convention
+    /// [`super::portfolio::PortfolioBacktest::trades`] uses for its own
+    /// closed-trade log.
+    trades: Vec<FuturesTrade>,
+}
+
+impl FuturesBacktest {
+    pub fn new(params: BacktestParams, starting_cash: f64, equity_capacity: usize) -> Self {
+        Self {
+            params,
+            cash: starting_cash,
+            side: None,
+            quantity: 0.0,
+            entry_price: 0.0,
+            leverage: 1.0,
+            liquidations: 0,
+            equity_curve: CircularColumn::new(equity_capacity),
+            trades: Vec::new(),
+        }
+    }
+
+    /// Mark-to-market at `price` and roll the result into the equity curve:
+    /// `cash + unrealized_pnl(price)`. Returns the sampled equity so a
+    /// caller driving its own loop doesn't need a separate read-back call
+    /// for the value it just recorded. Doesn't itself check liquidation or
+    /// accrue funding -- call [`FuturesBacktest::check_liquidation`]/
+    /// [`FuturesBacktest::accrue_funding`] alongside it, same as before this
+    /// existed.
+    pub fn on_price(&mut self, price: f64) -> f64 {
+        let equity = self.cash + self.unrealized_pnl(price);
+        self.equity_curve.push(equity);
+        equity
+    }
+
+    /// Zero-copy `(raw_slice, capacity, len, head)` view over the recorded
+    /// equity curve, the same convention [`crate::engine::HQuant::indicator_array`]
+    /// exposes for indicator history.
+    pub fn equity_curve_array(&self) -> (&[f64], usize, usize, usize) {
+        self.equity_curve.raw_view()
+    }
+
+    /// The recorded equity curve, oldest first -- a convenience for callers
+    /// (e.g. charting) that just want a `Vec`; prefer
+    /// [`FuturesBacktest::equity_curve_array`] for zero-copy access.
+    pub fn equity_curve(&self) -> Vec<f64> {
+        self.equity_curve.to_vec()
+    }
+
+    pub fn params(&self) -> BacktestParams {
+        self.params
+    }
+
+    pub fn cash(&self) -> f64 {
+        self.cash
+    }
+
+    /// Open (or replace) the tracked position at 1x (unleveraged) margin.
+    /// `quantity` is always positive; direction comes from `side`. See
+    /// [`FuturesBacktest::open_leveraged`] for a position that can be
+    /// liquidated.
+    pub fn open(&mut self, side: Side, quantity: f64) {
+        self.open_leveraged(side, quantity, 0.0, 1.0);
+    }
+
+    /// Open (or replace) the tracked position at `leverage`x margin against
+    /// `entry_price`, so [`FuturesBacktest::check_liquidation`] has a
+    /// liquidation price to compare bars against.
+    pub fn open_leveraged(&mut self, side: Side, quantity: f64, entry_price: f64, leverage: f64) {
+        assert!(leverage > 0.0, "leverage must be > 0");
+        self.side = Some(side);
+        self.quantity = quantity;
+        self.entry_price = entry_price;
+        self.leverage = leverage;
+    }
+
+    pub fn close(&mut self) {
+        self.side = None;
+        self.quantity = 0.0;
+    }
+
+    /// Like [`FuturesBacktest::close`], but at a known `price`: records a
+    /// [`FuturesTrade`] with that price as its exit before flattening, so
+    /// the position doesn't just disappear from [`FuturesBacktest::trades`]
+    /// without a record of how it closed. A no-op (records nothing) while
+    /// already flat.
+    pub fn close_at(&mut self, price: f64) {
+        let Some(side) = self.side else {
+            return;
+        };
+        self.trades.push(FuturesTrade {
+            side,
+            entry_price: self.entry_price,
+            exit_price: price,
+            quantity: self.quantity,
+        });
+        self.close();
+    }
+
+    /// Every leg [`FuturesBacktest::close_at`] has closed so far, oldest
+    /// first -- a dashboard reading this instead of recomputing PnL from
+    /// price history alone.
+    pub fn trades(&self) -> &[FuturesTrade] {
+        &self.trades
+    }
+
+    /// A snapshot of the currently open position, or `None` while flat.
+    pub fn current_position(&self) -> Option<PositionSnapshot> {
+        let side = self.side?;
+        Some(PositionSnapshot {
+            side,
+            entry_price: self.entry_price,
+            quantity: self.quantity,
+            margin: self.entry_price * self.quantity / self.leverage,
+        })
+    }
+
+    /// Isolated-margin liquidation price: the price at which this
+    /// position's leveraged loss consumes its entire margin. Ignores
+    /// maintenance margin, so this is the point equity hits exactly zero
+    /// rather than the (slightly less extreme) price a real exchange would
+    /// force-close at. `None` while flat.
+    fn liquidation_price(&self) -> Option<f64> {
+        let side = self.side?;
+        Some(match side {
+            Side::Long => self.entry_price * (1.0 - 1.0 / self.leverage),
+            Side::Short => self.entry_price * (1.0 + 1.0 / self.leverage),
+        })
+    }
+
+    /// The open position's liquidation price, if it's currently long.
+    /// `None` both while flat and while short -- a position is only ever
+    /// one side at a time, so there's no separate "what if this were
+    /// short" price to report alongside it.
+    pub fn liquidation_price_long(&self) -> Option<f64> {
+        match self.side {
+            Some(Side::Long) => self.liquidation_price(),
+            _ => None,
+        }
+    }
+
+    /// The open position's liquidation price, if it's currently short. See
+    /// [`FuturesBacktest::liquidation_price_long`].
+    pub fn liquidation_price_short(&self) -> Option<f64> {
+        match self.side {
+            Some(Side::Short) => self.liquidation_price(),
+            _ => None,
+        }
+    }
+
+    /// Mark-to-market PnL of the open position at `price`, ignoring funding
+    /// already accrued into `cash`. `0.0` while flat.
+    pub fn unrealized_pnl(&self, price: f64) -> f64 {
+        let Some(side) = self.side else {
+            return 0.0;
+        };
+        match side {
+            Side::Long => (price - self.entry_price) * self.quantity,
+            Side::Short => (self.entry_price - price) * self.quantity,
+        }
+    }
+
+    /// Whether `extreme_price` has crossed this position's liquidation
+    /// price -- the caller picks which of a bar's high/low is the
+    /// side-appropriate extreme to check (a long is threatened by
+    /// `bar.low`, a short by `bar.high`); see
+    /// [`FuturesBacktest::check_liquidation`] for that side selection done
+    /// for you.
+    fn is_liquidated(&self, extreme_price: f64) -> bool {
+        match (self.side, self.liquidation_price()) {
+            (Some(Side::Long), Some(liq)) => extreme_price <= liq,
+            (Some(Side::Short), Some(liq)) => extreme_price >= liq,
+            (None, _) => false,
+            (_, None) => unreachable!("liquidation_price is None only while side is None"),
+        }
+    }
+
+    /// Check a bar's full high/low range for a liquidation, using
+    /// `bar_low` against a long and `bar_high` against a short (rather than
+    /// always checking the same one regardless of side). Force-closes and
+    /// counts the liquidation if triggered.
+    pub fn check_liquidation(&mut self, bar_high: f64, bar_low: f64) -> bool {
+        let Some(side) = self.side else {
+            return false;
+        };
+        let extreme = match side {
+            Side::Long => bar_low,
+            Side::Short => bar_high,
+        };
+        if self.is_liquidated(extreme) {
+            self.liquidations += 1;
+            // Recorded at the liquidation price itself, not the bar's wick
+            // extreme -- the same "exit at the level, not the bar's raw
+            // range" convention `BacktestEngine::intrabar_hit`'s caller
+            // uses for a stop/target hit.
+            let liq = self.liquidation_price().expect("side is Some, checked above");
+            self.close_at(liq);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Debit/credit `cash` by `position_notional * rate`: a long pays
+    /// positive funding (its `cash` goes down), a short receives it. A
+    /// no-op while flat.
+    pub fn accrue_funding(&mut self, rate: f64, price: f64) {
+        let Some(side) = self.side else {
+            return;
+        };
+        let notional = self.quantity * price;
+        self.cash += match side {
+            Side::Long => -notional * rate,
+            Side::Short => notional * rate,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_long_held_through_positive_funding_intervals_pays_the_expected_total() {
+        let params = BacktestParams { funding_rate: 0.0001 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        backtest.open(Side::Long, 2.0);
+
+        let price = 50_000.0;
+        for _ in 0..3 {
+            backtest.accrue_funding(params.funding_rate, price);
+        }
+
+        let expected_paid = 3.0 * 2.0 * price * params.funding_rate;
+        assert!((backtest.cash() - (10_000.0 - expected_paid)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn a_short_receives_funding_a_long_would_have_paid() {
+        let params = BacktestParams { funding_rate: 0.0001 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        backtest.open(Side::Short, 2.0);
+
+        backtest.accrue_funding(params.funding_rate, 50_000.0);
+
+        assert!(backtest.cash() > 10_000.0);
+    }
+
+    #[test]
+    fn accruing_funding_while_flat_is_a_no_op() {
+        let params = BacktestParams { funding_rate: 0.0001 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        backtest.accrue_funding(params.funding_rate, 50_000.0);
+        assert_eq!(backtest.cash(), 10_000.0);
+    }
+
+    // Regression test: a short's liquidation price sits *above* entry, so
+    // it must be checked against a bar's high, not its low -- checking
+    // only bar_low regardless of side (as if a long) would never catch a
+    // short's liquidation at all.
+    #[test]
+    fn a_short_liquidates_on_a_high_wick_piercing_its_liquidation_price() {
+        let params = BacktestParams { funding_rate: 0.0 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        // 10x leverage: short liquidates at 50_000 * 1.1 = 55_000.
+        backtest.open_leveraged(Side::Short, 1.0, 50_000.0, 10.0);
+
+        // A bar whose low stays well short of liquidation but whose high
+        // wicks through 55_000.
+        let liquidated = backtest.check_liquidation(55_500.0, 50_500.0);
+
+        assert!(liquidated);
+        assert_eq!(backtest.liquidations, 1);
+    }
+
+    #[test]
+    fn a_short_survives_a_high_wick_that_falls_short_of_its_liquidation_price() {
+        let params = BacktestParams { funding_rate: 0.0 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        backtest.open_leveraged(Side::Short, 1.0, 50_000.0, 10.0);
+
+        let liquidated = backtest.check_liquidation(54_000.0, 50_500.0);
+
+        assert!(!liquidated);
+        assert_eq!(backtest.liquidations, 0);
+    }
+
+    #[test]
+    fn a_long_liquidates_on_a_low_wick_piercing_its_liquidation_price() {
+        let params = BacktestParams { funding_rate: 0.0 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        // 10x leverage: long liquidates at 50_000 * 0.9 = 45_000.
+        backtest.open_leveraged(Side::Long, 1.0, 50_000.0, 10.0);
+
+        let liquidated = backtest.check_liquidation(50_500.0, 44_500.0);
+
+        assert!(liquidated);
+        assert_eq!(backtest.liquidations, 1);
+    }
+
+    #[test]
+    fn unrealized_pnl_is_zero_while_flat_and_signed_by_side_once_open() {
+        let params = BacktestParams { funding_rate: 0.0 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        assert_eq!(backtest.unrealized_pnl(50_000.0), 0.0);
+
+        backtest.open_leveraged(Side::Long, 2.0, 50_000.0, 10.0);
+        assert!((backtest.unrealized_pnl(51_000.0) - 2_000.0).abs() < 1e-6);
+        assert!((backtest.unrealized_pnl(49_000.0) - -2_000.0).abs() < 1e-6);
+
+        backtest.open_leveraged(Side::Short, 2.0, 50_000.0, 10.0);
+        assert!((backtest.unrealized_pnl(49_000.0) - 2_000.0).abs() < 1e-6);
+        assert!((backtest.unrealized_pnl(51_000.0) - -2_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn liquidation_price_long_and_short_only_report_for_the_matching_open_side() {
+        let params = BacktestParams { funding_rate: 0.0 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        assert_eq!(backtest.liquidation_price_long(), None);
+        assert_eq!(backtest.liquidation_price_short(), None);
+
+        // 10x leverage: long liquidates at 50_000 * 0.9 = 45_000.
+        backtest.open_leveraged(Side::Long, 1.0, 50_000.0, 10.0);
+        assert!((backtest.liquidation_price_long().unwrap() - 45_000.0).abs() < 1e-6);
+        assert_eq!(backtest.liquidation_price_short(), None);
+
+        // 10x leverage: short liquidates at 50_000 * 1.1 = 55_000.
+        backtest.open_leveraged(Side::Short, 1.0, 50_000.0, 10.0);
+        assert!((backtest.liquidation_price_short().unwrap() - 55_000.0).abs() < 1e-6);
+        assert_eq!(backtest.liquidation_price_long(), None);
+    }
+
+    #[test]
+    fn on_price_records_an_equity_sample_matching_cash_plus_unrealized_pnl() {
+        let params = BacktestParams { funding_rate: 0.0 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        backtest.open_leveraged(Side::Long, 2.0, 50_000.0, 10.0);
+
+        let path = [50_000.0, 50_500.0, 51_000.0, 50_200.0, 49_800.0];
+        for &price in &path {
+            let sampled = backtest.on_price(price);
+            assert!((sampled - (backtest.cash() + backtest.unrealized_pnl(price))).abs() < 1e-9);
+        }
+
+        let curve = backtest.equity_curve();
+        assert_eq!(curve.len(), path.len());
+        for (i, &price) in path.iter().enumerate() {
+            let expected = 10_000.0 + backtest.unrealized_pnl(price);
+            assert!((curve[i] - expected).abs() < 1e-9, "sample {i}: {} vs {}", curve[i], expected);
+        }
+    }
+
+    #[test]
+    fn the_equity_curve_stays_bounded_at_its_configured_capacity() {
+        let params = BacktestParams { funding_rate: 0.0 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 3);
+        backtest.open(Side::Long, 1.0);
+
+        for price in [100.0, 101.0, 102.0, 103.0, 104.0] {
+            backtest.on_price(price);
+        }
+
+        let (_, capacity, len, _) = backtest.equity_curve_array();
+        assert_eq!(capacity, 3);
+        assert_eq!(len, 3);
+        // Only the most recent 3 samples survive the ring buffer wrap.
+        let curve = backtest.equity_curve();
+        assert_eq!(curve, vec![10_102.0, 10_103.0, 10_104.0]);
+    }
+
+    #[test]
+    fn current_position_reports_the_open_positions_shape_and_none_while_flat() {
+        let params = BacktestParams { funding_rate: 0.0 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        assert_eq!(backtest.current_position(), None);
+
+        backtest.open_leveraged(Side::Long, 2.0, 50_000.0, 10.0);
+        let position = backtest.current_position().unwrap();
+        assert_eq!(position.side, Side::Long);
+        assert_eq!(position.entry_price, 50_000.0);
+        assert_eq!(position.quantity, 2.0);
+        // Notional 100_000 at 10x leverage: margin 10_000.
+        assert!((position.margin - 10_000.0).abs() < 1e-6);
+
+        backtest.close();
+        assert_eq!(backtest.current_position(), None);
+    }
+
+    #[test]
+    fn a_buy_then_sell_cycle_records_one_trade_with_the_expected_pnl() {
+        let params = BacktestParams { funding_rate: 0.0 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        assert!(backtest.trades().is_empty());
+
+        backtest.open(Side::Long, 1.0);
+        backtest.close_at(110.0);
+
+        let trades = backtest.trades();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].side, Side::Long);
+        assert!((trades[0].pnl() - 110.0).abs() < 1e-9); // entry_price defaults to 0.0 for `open`
+
+        backtest.open(Side::Short, 1.0);
+        backtest.close_at(90.0);
+
+        assert_eq!(backtest.trades().len(), 2);
+        assert_eq!(backtest.trades()[1].side, Side::Short);
+    }
+
+    #[test]
+    fn close_at_records_the_liquidation_price_not_the_bars_wick() {
+        let params = BacktestParams { funding_rate: 0.0 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        // 10x leverage: long liquidates at 50_000 * 0.9 = 45_000.
+        backtest.open_leveraged(Side::Long, 1.0, 50_000.0, 10.0);
+
+        backtest.check_liquidation(50_500.0, 44_500.0);
+
+        let trades = backtest.trades();
+        assert_eq!(trades.len(), 1);
+        assert!((trades[0].exit_price - 45_000.0).abs() < 1e-6, "exit_price={}", trades[0].exit_price);
+    }
+
+    #[test]
+    fn close_at_is_a_no_op_while_flat() {
+        let params = BacktestParams { funding_rate: 0.0 };
+        let mut backtest = FuturesBacktest::new(params, 10_000.0, 500);
+        backtest.close_at(100.0);
+        assert!(backtest.trades().is_empty());
+    }
+}
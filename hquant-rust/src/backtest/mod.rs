@@ -0,0 +1,285 @@
+//! Interprets compiled-strategy [`Signal`]s as positions against a price
+//! stream: opens with an optional bracket (stop + target) armed atomically,
+//! and reports when a price crosses one of those levels.
+
+pub mod engine;
+pub mod futures;
+pub mod portfolio;
+
+pub use engine::{BacktestConfig, BacktestEngine, BacktestEngineSnapshot, OrderKind, ReturnType, SlippageModel};
+pub use futures::{BacktestParams, FuturesBacktest, FuturesTrade, PositionSnapshot};
+pub use portfolio::{BacktestStats, PortfolioBacktest, Trade};
+
+use crate::kline::Bar;
+use crate::strategy::{Action, Bracket, Signal};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Long,
+    Short,
+}
+
+/// How much to risk on the next entry. This engine has no equity or order-
+/// quantity concept of its own (see [`engine::BacktestConfig::order_size`]'s
+/// doc comment for the same limitation) -- fills always trade the full
+/// price move, exactly as if every entry were the same one unit. Sizing a
+/// real order off [`PositionSizing::calculate_position_size`] is therefore
+/// left to the caller, the same way it already scales `order_size` itself:
+/// this type just does the sizing math, in whichever unit each mode implies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PositionSizing {
+    /// A fixed percent of account equity/notional per trade, whatever the
+    /// caller takes that to mean -- the mode every `BacktestConfig` used
+    /// implicitly before this enum existed.
+    FixedFraction(f64),
+    /// A fixed notional amount per trade, in the caller's own currency
+    /// units rather than a percent.
+    FixedNotional(f64),
+    /// The Kelly criterion's stake fraction (as a percent), from a
+    /// caller-supplied win rate (`0.0..=1.0`) and payoff ratio (average win
+    /// / average loss). See [`PositionSizing::calculate_position_size`] for
+    /// the formula.
+    Kelly { win_rate: f64, payoff: f64 },
+    /// Sizes inversely to `atr` so every trade risks roughly the same
+    /// `target_vol` regardless of how volatile the market currently is --
+    /// `atr` is a caller-supplied volatility reading (e.g. from
+    /// [`engine::BacktestEngine`]'s own ATR estimate under
+    /// [`engine::SlippageModel::Volatility`], or any other source), not
+    /// tracked by this type itself.
+    VolatilityTargeted { target_vol: f64, atr: f64 },
+}
+
+impl Default for PositionSizing {
+    /// [`PositionSizing::FixedFraction`]`(100.0)`: full size, matching the
+    /// engine's behavior before this enum existed.
+    fn default() -> Self {
+        PositionSizing::FixedFraction(100.0)
+    }
+}
+
+impl PositionSizing {
+    /// Size for the next trade, in whichever unit this mode implies: a
+    /// percent for `FixedFraction`/`Kelly`/`VolatilityTargeted`, an absolute
+    /// notional for `FixedNotional`.
+    pub fn calculate_position_size(&self) -> f64 {
+        match *self {
+            PositionSizing::FixedFraction(pct) => pct,
+            PositionSizing::FixedNotional(amount) => amount,
+            // f* = W - (1 - W) / R, the fraction of the bankroll to stake;
+            // clamped at 0 so a negative edge never suggests shorting the
+            // strategy's own sizing rather than just sitting out.
+            PositionSizing::Kelly { win_rate, payoff } => {
+                (win_rate - (1.0 - win_rate) / payoff).max(0.0) * 100.0
+            }
+            // Same-risk sizing: half the ATR should get roughly twice the
+            // size for the same target_vol budget.
+            PositionSizing::VolatilityTargeted { target_vol, atr } => {
+                if atr > 0.0 {
+                    target_vol / atr
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    Stop,
+    Target,
+}
+
+/// An open position, with its bracket (if any) already resolved to
+/// absolute prices at entry time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub side: Side,
+    pub entry_price: f64,
+    pub stop_price: Option<f64>,
+    pub target_price: Option<f64>,
+    /// Worst unrealized PnL percent seen while this position was open
+    /// (negative, or `0.0` if it never went underwater), tracked against
+    /// each bar's full high/low range so an intrabar wick counts even if
+    /// the close doesn't reach it. See [`Position::update_excursion`].
+    pub mae: f64,
+    /// Best unrealized PnL percent seen while this position was open, the
+    /// mirror of `mae`.
+    pub mfe: f64,
+    /// Number of bars this position has been open for, counted from the
+    /// first bar *after* entry (the entry bar itself is `0`).
+    pub bars_held: u32,
+    /// Fee paid to open this position, from
+    /// [`super::engine::BacktestEngine::calculate_fee`], carried until the
+    /// exit fee joins it on the closed [`super::Trade`]. Always the fee for
+    /// the position's original full size, even after a
+    /// [`super::engine::BacktestEngine::close_fraction`] scale-out --
+    /// [`super::engine::BacktestEngine::close_fraction`] charges the share of
+    /// it matching the fraction each call closes (and a bracket hit the
+    /// share of it still open) rather than shrinking this field.
+    pub entry_fee: f64,
+    /// Fraction (`0.0..=1.0`) of this position's original size still open,
+    /// reduced by each [`super::engine::BacktestEngine::close_fraction`]
+    /// call; `1.0` until the first scale-out. A full close (a bracket hit,
+    /// or a `close_fraction` that empties this) needs no separate flag --
+    /// it's just the case where this would reach `0.0`, at which point
+    /// [`super::engine::BacktestEngine`] drops the position entirely
+    /// instead of leaving a zero-size one around.
+    pub remaining_fraction: f64,
+}
+
+impl Position {
+    /// Open a position at `entry_price`, resolving `bracket`'s percent
+    /// distances against `side` so the stop sits on the losing side and the
+    /// target on the winning side regardless of direction. Both levels come
+    /// from the same `bracket`, so `open` sets both or neither;
+    /// [`super::engine::BacktestEngine`] may fill in either one alone from
+    /// its config afterwards.
+    pub fn open(side: Side, entry_price: f64, bracket: Option<Bracket>) -> Self {
+        let (stop_price, target_price) = match bracket {
+            Some(b) => match side {
+                Side::Long => (
+                    Some(entry_price * (1.0 - b.stop_pct / 100.0)),
+                    Some(entry_price * (1.0 + b.target_pct / 100.0)),
+                ),
+                Side::Short => (
+                    Some(entry_price * (1.0 + b.stop_pct / 100.0)),
+                    Some(entry_price * (1.0 - b.target_pct / 100.0)),
+                ),
+            },
+            None => (None, None),
+        };
+        Self {
+            side,
+            entry_price,
+            stop_price,
+            target_price,
+            mae: 0.0,
+            mfe: 0.0,
+            bars_held: 0,
+            entry_fee: 0.0,
+            remaining_fraction: 1.0,
+        }
+    }
+
+    /// Open a position from a strategy [`Signal`], or `None` if the signal
+    /// doesn't represent an entry (`Hold`/`Close` open nothing). A `Guard`
+    /// signal never reaches here in practice -- [`crate::strategy::CompiledStrategy::evaluate_with`]
+    /// resolves it to no `Signal` at all -- but is treated the same as
+    /// `Hold`/`Close` for exhaustiveness.
+    pub fn from_signal(signal: &Signal, entry_price: f64) -> Option<Self> {
+        let side = match signal.action {
+            Action::Buy => Side::Long,
+            Action::Sell => Side::Short,
+            Action::Hold | Action::Close | Action::Guard => return None,
+        };
+        Some(Self::open(side, entry_price, signal.bracket))
+    }
+
+    /// Whether `price` has crossed this position's stop or target, checking
+    /// the stop first since a bar that gaps through both should count as a
+    /// loss, not a win.
+    pub fn check_exit(&self, price: f64) -> Option<ExitReason> {
+        match self.side {
+            Side::Long => {
+                if self.stop_price.is_some_and(|s| price <= s) {
+                    return Some(ExitReason::Stop);
+                }
+                if self.target_price.is_some_and(|t| price >= t) {
+                    return Some(ExitReason::Target);
+                }
+            }
+            Side::Short => {
+                if self.stop_price.is_some_and(|s| price >= s) {
+                    return Some(ExitReason::Stop);
+                }
+                if self.target_price.is_some_and(|t| price <= t) {
+                    return Some(ExitReason::Target);
+                }
+            }
+        }
+        None
+    }
+
+    fn pnl_pct(&self, price: f64) -> f64 {
+        match self.side {
+            Side::Long => (price - self.entry_price) / self.entry_price * 100.0,
+            Side::Short => (self.entry_price - price) / self.entry_price * 100.0,
+        }
+    }
+
+    /// Roll `bar`'s full high/low range into this position's running
+    /// maximum-adverse/favorable-excursion bounds. Called once per bar the
+    /// position is open, before checking for a stop/target exit.
+    pub fn update_excursion(&mut self, bar: &Bar) {
+        let (favorable_price, adverse_price) = match self.side {
+            Side::Long => (bar.high, bar.low),
+            Side::Short => (bar.low, bar.high),
+        };
+        self.mfe = self.mfe.max(self.pnl_pct(favorable_price));
+        self.mae = self.mae.min(self.pnl_pct(adverse_price));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bracketed_long_entry_has_the_specified_stop_and_target() {
+        let signal = Signal { action: Action::Buy, bracket: Some(Bracket { stop_pct: 2.0, target_pct: 5.0 }) };
+        let position = Position::from_signal(&signal, 100.0).unwrap();
+        assert!((position.stop_price.unwrap() - 98.0).abs() < 1e-9);
+        assert!((position.target_price.unwrap() - 105.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stop_and_target_trigger_correctly_on_a_long_position() {
+        let signal = Signal { action: Action::Buy, bracket: Some(Bracket { stop_pct: 2.0, target_pct: 5.0 }) };
+        let position = Position::from_signal(&signal, 100.0).unwrap();
+        assert_eq!(position.check_exit(99.0), None);
+        assert_eq!(position.check_exit(98.0), Some(ExitReason::Stop));
+        assert_eq!(position.check_exit(105.0), Some(ExitReason::Target));
+    }
+
+    #[test]
+    fn close_signal_opens_no_position() {
+        let signal = Signal { action: Action::Close, bracket: None };
+        assert_eq!(Position::from_signal(&signal, 100.0), None);
+    }
+
+    #[test]
+    fn fixed_fraction_and_notional_sizing_return_their_configured_value_unchanged() {
+        assert_eq!(PositionSizing::FixedFraction(25.0).calculate_position_size(), 25.0);
+        assert_eq!(PositionSizing::FixedNotional(500.0).calculate_position_size(), 500.0);
+    }
+
+    #[test]
+    fn kelly_sizing_matches_the_hand_computed_stake_fraction() {
+        // f* = W - (1 - W) / R = 0.6 - 0.4 / 2.0 = 0.4, i.e. 40%.
+        let sizing = PositionSizing::Kelly { win_rate: 0.6, payoff: 2.0 };
+        assert!((sizing.calculate_position_size() - 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn kelly_sizing_clamps_a_negative_edge_to_zero() {
+        // f* = 0.3 - 0.7 / 1.0 = -0.4: a losing edge, clamped to 0 rather
+        // than suggesting the strategy short its own sizing.
+        let sizing = PositionSizing::Kelly { win_rate: 0.3, payoff: 1.0 };
+        assert_eq!(sizing.calculate_position_size(), 0.0);
+    }
+
+    #[test]
+    fn volatility_targeted_sizing_scales_inversely_with_atr() {
+        let calm = PositionSizing::VolatilityTargeted { target_vol: 2.0, atr: 1.0 };
+        let choppy = PositionSizing::VolatilityTargeted { target_vol: 2.0, atr: 4.0 };
+        assert!((calm.calculate_position_size() - 2.0).abs() < 1e-9);
+        assert!((choppy.calculate_position_size() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn volatility_targeted_sizing_is_zero_with_no_atr_reading() {
+        let sizing = PositionSizing::VolatilityTargeted { target_vol: 2.0, atr: 0.0 };
+        assert_eq!(sizing.calculate_position_size(), 0.0);
+    }
+}
@@ -0,0 +1,588 @@
+//! Tracks closed trades across multiple strategies sharing one backtest so
+//! they can be compared against each other, e.g. via
+//! [`PortfolioBacktest::information_ratio`].
+
+use super::{ExitReason, ReturnType, Side};
+
+/// A closed trade, tagged with the strategy that opened it so a shared
+/// [`PortfolioBacktest`] can separate one strategy's contribution from
+/// another's.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trade {
+    pub strategy_id: u32,
+    pub side: Side,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    /// Why the trade closed, e.g. a stop or target hit; `None` for trades
+    /// closed by an explicit `CLOSE` signal rather than a bracket level.
+    pub exit_reason: Option<ExitReason>,
+    /// Millisecond unix timestamp of the bar the trade closed on, the same
+    /// convention as [`crate::kline::Bar::ts`]. Used by [`BacktestStats`]
+    /// to bucket PnL by hour-of-day and day-of-week.
+    pub exit_ts: i64,
+    /// Worst and best unrealized PnL percent seen while this trade's
+    /// position was open, from [`super::Position::mae`]/`mfe`.
+    pub mae: f64,
+    pub mfe: f64,
+    /// Number of bars the position was open for, from
+    /// [`super::Position::bars_held`].
+    pub bars_held: u32,
+    /// Entry fee plus exit fee, from [`super::BacktestEngine::calculate_fee`].
+    /// Not netted out of [`Trade::pnl`] -- that stays gross, matching how
+    /// `pnl` never accounted for anything beyond price movement even
+    /// before fees existed.
+    pub fee: f64,
+    /// Fraction (`0.0..=1.0`) of the position's original size this trade
+    /// closed -- `1.0` for a full close of a position that was never scaled
+    /// out of, less than `1.0` for a scale-out tranche or for the remainder
+    /// that a bracket hit or the last [`super::BacktestEngine::close_fraction`]
+    /// call closes after an earlier scale-out. [`Trade::pnl`]
+    /// and `fee` are both already scaled to this fraction, so a
+    /// [`PortfolioBacktest`] summing several tranches' `pnl`/`fee` gets the
+    /// same total a single full close at the same prices would have.
+    pub size: f64,
+    /// Whether this trade left part of the position still open --
+    /// `true` for every [`super::BacktestEngine::close_fraction`] tranche
+    /// except the one that empties it, `false` for an ordinary full close.
+    pub partial: bool,
+}
+
+impl Trade {
+    /// Raw price move, scaled by `size` so a scale-out's tranches sum to
+    /// the same total a single full close would have realized.
+    pub fn pnl(&self) -> f64 {
+        let diff = match self.side {
+            Side::Long => self.exit_price - self.entry_price,
+            Side::Short => self.entry_price - self.exit_price,
+        };
+        diff * self.size
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PortfolioBacktest {
+    pub trades: Vec<Trade>,
+}
+
+impl PortfolioBacktest {
+    pub fn record(&mut self, trade: Trade) {
+        self.trades.push(trade);
+    }
+
+    fn pnl_series(&self, strategy_id: u32) -> Vec<f64> {
+        self.trades.iter().filter(|t| t.strategy_id == strategy_id).map(Trade::pnl).collect()
+    }
+
+    /// Information ratio of `strategy_a` against `strategy_b`: the mean of
+    /// their per-trade active return (`a`'s trade pnl minus `b`'s, paired
+    /// by trade order) divided by its standard deviation. `NaN` if either
+    /// strategy has no trades recorded.
+    pub fn information_ratio(&self, strategy_a: u32, strategy_b: u32) -> f64 {
+        let a = self.pnl_series(strategy_a);
+        let b = self.pnl_series(strategy_b);
+        let n = a.len().min(b.len());
+        if n == 0 {
+            return f64::NAN;
+        }
+        let active: Vec<f64> = (0..n).map(|i| a[i] - b[i]).collect();
+        let mean = active.iter().sum::<f64>() / n as f64;
+        let variance = active.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n as f64;
+        mean / variance.sqrt()
+    }
+}
+
+/// A seasonal breakdown of realized PnL by the hour-of-day and day-of-week
+/// (UTC) each trade closed on, computed from [`Trade::exit_ts`]. Useful for
+/// spotting session effects, e.g. a strategy that only makes money during
+/// Asia hours.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BacktestStats {
+    pub total_pnl: f64,
+    pub pnl_by_hour: [f64; 24],
+    /// Monday = 0 .. Sunday = 6.
+    pub pnl_by_weekday: [f64; 7],
+    /// Mean per-trade return over its standard deviation (population,
+    /// unannualized, the same convention [`PortfolioBacktest::information_ratio`]
+    /// uses), computed from the return series `return_type` selects.
+    /// `NaN` with no trades.
+    pub sharpe: f64,
+    /// Longest run of consecutive winning trades (positive PnL), in trade
+    /// log order. A zero-PnL trade ends the streak without extending it
+    /// (see [`longest_streaks`]).
+    pub max_consecutive_wins: u32,
+    /// Longest run of consecutive losing trades (negative PnL), the mirror
+    /// of `max_consecutive_wins`.
+    pub max_consecutive_losses: u32,
+    /// Like `sharpe`, but the denominator only counts downside deviation
+    /// (return shortfalls below zero), so a strategy with big wins and
+    /// small losses scores higher here than on Sharpe. `NaN` with no
+    /// trades or with no losing returns to measure downside from.
+    pub sortino: f64,
+    /// Total compounded return over the trade log divided by the largest
+    /// peak-to-trough drawdown of the compounding equity curve
+    /// [`sharpe`]/`sortino`'s returns imply. This crate has no notion of
+    /// trade frequency or elapsed wall-clock time, so unlike the classic
+    /// annualized Calmar ratio this is left unannualized, the same
+    /// convention `sharpe` already uses for the same reason. `NaN` with no
+    /// trades or with zero drawdown to divide by.
+    pub calmar: f64,
+    /// Longest stretch, in bars, that the compounding equity curve spent
+    /// below a prior peak before recovering to it -- "time underwater" for
+    /// the same drawdown [`calmar`]'s denominator measures by depth rather
+    /// than duration. Counted from [`Trade::bars_held`] (plus the entry bar
+    /// itself, which `bars_held` doesn't count), summed over every trade
+    /// closing while equity sits below its running peak. `0` with no trades
+    /// or if equity never dips below a prior peak.
+    pub max_drawdown_duration: u32,
+    /// Percent of `total_bars` (the `calculate` argument) spent with a
+    /// position open, summing every trade's `bars_held + 1`. `NaN` if
+    /// `total_bars` is `0`, the same "nothing to divide by" convention
+    /// `sortino`/`calmar` use above.
+    pub time_in_market_pct: f64,
+    /// Total compounded return over the trade log, as a percent -- the same
+    /// equity curve [`calmar`]'s numerator uses, just reported directly
+    /// rather than divided by drawdown. `NaN` with no trades.
+    pub return_pct: f64,
+    /// Buy-and-hold return over the same bars, as a percent -- the
+    /// `benchmark_return_pct` argument to `calculate`, passed straight
+    /// through from [`super::BacktestEngine::benchmark_return_pct`] so this
+    /// struct doesn't need to depend on the engine type that computed it.
+    pub benchmark_return_pct: f64,
+    /// `return_pct - benchmark_return_pct`: how much this strategy beat (or
+    /// lagged) simply holding the underlying over the same window. `NaN` if
+    /// either side is `NaN`.
+    pub alpha: f64,
+}
+
+impl BacktestStats {
+    /// `total_bars` is the number of bars the backtest ran over -- needed
+    /// only for `time_in_market_pct`, since (unlike every other stat here)
+    /// it's a ratio against the whole backtest window rather than something
+    /// derivable purely from the closed trades themselves. `benchmark_return_pct`
+    /// is likewise not derivable from `trades` alone -- see
+    /// [`super::BacktestEngine::benchmark_return_pct`].
+    pub fn calculate(trades: &[Trade], return_type: ReturnType, total_bars: u32, benchmark_return_pct: f64) -> Self {
+        let mut stats = Self {
+            total_pnl: 0.0,
+            pnl_by_hour: [0.0; 24],
+            pnl_by_weekday: [0.0; 7],
+            sharpe: f64::NAN,
+            max_consecutive_wins: 0,
+            max_consecutive_losses: 0,
+            sortino: f64::NAN,
+            calmar: f64::NAN,
+            max_drawdown_duration: 0,
+            time_in_market_pct: f64::NAN,
+            return_pct: f64::NAN,
+            benchmark_return_pct,
+            alpha: f64::NAN,
+        };
+        for trade in trades {
+            let pnl = trade.pnl();
+            stats.total_pnl += pnl;
+            stats.pnl_by_hour[hour_of_day(trade.exit_ts)] += pnl;
+            stats.pnl_by_weekday[weekday_index(trade.exit_ts)] += pnl;
+        }
+        stats.sharpe = sharpe_ratio(trades, return_type);
+        stats.sortino = sortino_ratio(trades, return_type);
+        stats.calmar = calmar_ratio(trades, return_type);
+        (stats.max_consecutive_wins, stats.max_consecutive_losses) = longest_streaks(trades);
+        stats.max_drawdown_duration = max_drawdown_duration_bars(trades, return_type);
+        stats.time_in_market_pct = time_in_market_pct(trades, total_bars);
+        stats.return_pct = total_return_pct(trades, return_type);
+        stats.alpha = stats.return_pct - stats.benchmark_return_pct;
+        stats
+    }
+}
+
+/// `(max_consecutive_wins, max_consecutive_losses)` walking `trades` in
+/// order. A zero-PnL trade is neither a win nor a loss: it ends whichever
+/// streak was running (a breakeven trade breaks a streak's momentum either
+/// way) without starting a new one of its own.
+fn longest_streaks(trades: &[Trade]) -> (u32, u32) {
+    let (mut max_wins, mut max_losses) = (0u32, 0u32);
+    let (mut wins, mut losses) = (0u32, 0u32);
+    for trade in trades {
+        let pnl = trade.pnl();
+        if pnl > 0.0 {
+            wins += 1;
+            losses = 0;
+        } else if pnl < 0.0 {
+            losses += 1;
+            wins = 0;
+        } else {
+            wins = 0;
+            losses = 0;
+        }
+        max_wins = max_wins.max(wins);
+        max_losses = max_losses.max(losses);
+    }
+    (max_wins, max_losses)
+}
+
+/// Per-trade returns as `pnl / entry_price`, the simple-return step of an
+/// equity curve that compounds one trade at a time (`equity_t = equity_{t-1}
+/// * (1 + return_t)`).
+fn simple_returns(trades: &[Trade]) -> Vec<f64> {
+    trades.iter().map(|t| t.pnl() / t.entry_price).collect()
+}
+
+/// Sharpe ratio (mean / population std-dev, unannualized) of the per-trade
+/// return series `return_type` selects. [`ReturnType::Log`] uses
+/// `ln(1 + return)`, i.e. `ln(equity_t / equity_{t-1})` for the same
+/// compounding equity curve `return_type`'s docs describe.
+fn sharpe_ratio(trades: &[Trade], return_type: ReturnType) -> f64 {
+    let returns = returns_for(trades, return_type);
+    if returns.is_empty() {
+        return f64::NAN;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+    mean / variance.sqrt()
+}
+
+fn returns_for(trades: &[Trade], return_type: ReturnType) -> Vec<f64> {
+    match return_type {
+        ReturnType::Simple => simple_returns(trades),
+        ReturnType::Log => simple_returns(trades).into_iter().map(|r| (1.0 + r).ln()).collect(),
+    }
+}
+
+/// Population standard deviation of the shortfall below a zero target
+/// return (i.e. only losing returns pull this away from zero; winning
+/// returns contribute nothing), the denominator [`sortino_ratio`] uses in
+/// place of `sharpe_ratio`'s full-sample standard deviation.
+fn downside_deviation(returns: &[f64]) -> f64 {
+    let squared_shortfalls: Vec<f64> = returns.iter().map(|r| r.min(0.0).powi(2)).collect();
+    let mean_squared_shortfall = squared_shortfalls.iter().sum::<f64>() / squared_shortfalls.len() as f64;
+    mean_squared_shortfall.sqrt()
+}
+
+/// Like [`sharpe_ratio`], but dividing by [`downside_deviation`] instead of
+/// full-sample standard deviation, so upside volatility doesn't get
+/// penalized the way Sharpe penalizes it.
+fn sortino_ratio(trades: &[Trade], return_type: ReturnType) -> f64 {
+    let returns = returns_for(trades, return_type);
+    if returns.is_empty() {
+        return f64::NAN;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let downside = downside_deviation(&returns);
+    if downside == 0.0 {
+        return f64::NAN;
+    }
+    mean / downside
+}
+
+/// Compounding equity curve implied by `returns`, starting at `1.0` (see
+/// [`sharpe_ratio`]'s doc comment for the same `equity_t = equity_{t-1} *
+/// (1 + return_t)` convention).
+fn equity_curve(returns: &[f64]) -> Vec<f64> {
+    let mut equity = 1.0;
+    returns
+        .iter()
+        .map(|r| {
+            equity *= 1.0 + r;
+            equity
+        })
+        .collect()
+}
+
+/// Largest peak-to-trough decline of `equity`, as a fraction (`0.25` for a
+/// 25% drawdown). `0.0` if `equity` never dips below a prior peak.
+fn max_drawdown_pct(equity: &[f64]) -> f64 {
+    let mut peak = 1.0_f64;
+    let mut worst = 0.0_f64;
+    for &value in equity {
+        peak = peak.max(value);
+        worst = worst.max((peak - value) / peak);
+    }
+    worst
+}
+
+/// Total compounded return over `trades` divided by the largest drawdown
+/// of the equity curve that return series implies -- see
+/// [`BacktestStats::calmar`] for why this is left unannualized.
+fn calmar_ratio(trades: &[Trade], return_type: ReturnType) -> f64 {
+    let returns = returns_for(trades, return_type);
+    if returns.is_empty() {
+        return f64::NAN;
+    }
+    let equity = equity_curve(&returns);
+    let total_return = equity.last().unwrap() - 1.0;
+    let drawdown = max_drawdown_pct(&equity);
+    if drawdown == 0.0 {
+        return f64::NAN;
+    }
+    total_return / drawdown
+}
+
+/// Total compounded return over `trades`, as a percent -- the same equity
+/// curve [`calmar_ratio`]'s numerator uses. `NaN` with no trades.
+fn total_return_pct(trades: &[Trade], return_type: ReturnType) -> f64 {
+    let returns = returns_for(trades, return_type);
+    if returns.is_empty() {
+        return f64::NAN;
+    }
+    let equity = equity_curve(&returns);
+    100.0 * (equity.last().unwrap() - 1.0)
+}
+
+/// Longest stretch, in bars, that the compounding equity curve implied by
+/// `trades` spends below a prior peak before recovering to it -- see
+/// [`BacktestStats::max_drawdown_duration`]. Each trade contributes
+/// `bars_held + 1` bars (the `+ 1` for the entry bar `bars_held` doesn't
+/// count, per [`super::Position::bars_held`]) to the running underwater
+/// stretch while its closing equity sits below the peak seen so far; the
+/// stretch resets to `0` the bar equity reaches a new peak.
+fn max_drawdown_duration_bars(trades: &[Trade], return_type: ReturnType) -> u32 {
+    let returns = returns_for(trades, return_type);
+    let equity = equity_curve(&returns);
+    let mut peak = 1.0_f64;
+    let mut underwater = 0u32;
+    let mut worst_underwater = 0u32;
+    for (trade, &value) in trades.iter().zip(equity.iter()) {
+        if value >= peak {
+            peak = value;
+            underwater = 0;
+        } else {
+            underwater += trade.bars_held + 1;
+            worst_underwater = worst_underwater.max(underwater);
+        }
+    }
+    worst_underwater
+}
+
+/// Percent of `total_bars` spent with a position open, summing every
+/// trade's `bars_held + 1` (see [`max_drawdown_duration_bars`] for the same
+/// `+ 1`). `NaN` if `total_bars` is `0`, nothing to divide by.
+fn time_in_market_pct(trades: &[Trade], total_bars: u32) -> f64 {
+    if total_bars == 0 {
+        return f64::NAN;
+    }
+    let bars_in_market: u32 = trades.iter().map(|t| t.bars_held + 1).sum();
+    100.0 * bars_in_market as f64 / total_bars as f64
+}
+
+/// UTC hour-of-day, `0..24`, for a millisecond unix timestamp.
+fn hour_of_day(ts_ms: i64) -> usize {
+    ts_ms.div_euclid(3_600_000).rem_euclid(24) as usize
+}
+
+/// UTC day-of-week, Monday = 0 .. Sunday = 6, for a millisecond unix
+/// timestamp. The Unix epoch (1970-01-01) was a Thursday, i.e. index 3.
+fn weekday_index(ts_ms: i64) -> usize {
+    let days_since_epoch = ts_ms.div_euclid(86_400_000);
+    (days_since_epoch + 3).rem_euclid(7) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(strategy_id: u32, entry: f64, exit: f64) -> Trade {
+        Trade { strategy_id, side: Side::Long, entry_price: entry, exit_price: exit, exit_reason: None, exit_ts: 0, mae: 0.0, mfe: 0.0, bars_held: 0, fee: 0.0, size: 1.0, partial: false }
+    }
+
+    #[test]
+    fn information_ratio_is_positive_when_a_consistently_outperforms_b() {
+        let mut portfolio = PortfolioBacktest::default();
+        for (entry, exit_a, exit_b) in [(100.0, 110.0, 102.0), (100.0, 108.0, 101.0), (100.0, 112.0, 99.0)] {
+            portfolio.record(trade(1, entry, exit_a));
+            portfolio.record(trade(2, entry, exit_b));
+        }
+        assert!(portfolio.information_ratio(1, 2) > 0.0);
+    }
+
+    #[test]
+    fn information_ratio_is_nan_with_no_trades() {
+        let portfolio = PortfolioBacktest::default();
+        assert!(portfolio.information_ratio(1, 2).is_nan());
+    }
+
+    #[test]
+    fn pnl_buckets_by_hour_and_weekday_sum_to_the_total() {
+        // 1970-01-01 00:00 UTC was a Thursday (weekday index 3).
+        let thursday_midnight = 0;
+        let thursday_5am = 5 * 3_600_000;
+        // Three days later, 1970-01-04, was a Sunday (weekday index 6).
+        let sunday_midnight = 3 * 86_400_000;
+
+        let trades = vec![
+            Trade { strategy_id: 1, side: Side::Long, entry_price: 100.0, exit_price: 110.0, exit_reason: None, exit_ts: thursday_midnight, mae: 0.0, mfe: 0.0, bars_held: 0, fee: 0.0, size: 1.0, partial: false },
+            Trade { strategy_id: 1, side: Side::Long, entry_price: 100.0, exit_price: 90.0, exit_reason: None, exit_ts: thursday_5am, mae: 0.0, mfe: 0.0, bars_held: 0, fee: 0.0, size: 1.0, partial: false },
+            Trade { strategy_id: 1, side: Side::Long, entry_price: 100.0, exit_price: 105.0, exit_reason: None, exit_ts: sunday_midnight, mae: 0.0, mfe: 0.0, bars_held: 0, fee: 0.0, size: 1.0, partial: false },
+        ];
+
+        let stats = BacktestStats::calculate(&trades, ReturnType::Simple, 0, f64::NAN);
+
+        assert!((stats.pnl_by_hour[0] - 15.0).abs() < 1e-9); // +10 (Thu midnight) + 5 (Sun midnight)
+        assert!((stats.pnl_by_hour[5] - (-10.0)).abs() < 1e-9);
+        assert!((stats.pnl_by_weekday[3] - 0.0).abs() < 1e-9); // Thursday: +10 - 10
+        assert!((stats.pnl_by_weekday[6] - 5.0).abs() < 1e-9); // Sunday: +5
+
+        let bucketed_by_hour: f64 = stats.pnl_by_hour.iter().sum();
+        let bucketed_by_weekday: f64 = stats.pnl_by_weekday.iter().sum();
+        assert!((bucketed_by_hour - stats.total_pnl).abs() < 1e-9);
+        assert!((bucketed_by_weekday - stats.total_pnl).abs() < 1e-9);
+    }
+
+    #[test]
+    fn max_consecutive_wins_and_losses_reset_across_a_zero_pnl_trade() {
+        // Streaks: win, win, win (3), loss, loss (2), zero (breaks both),
+        // win, win, win, win (4), loss (1).
+        let trades = vec![
+            trade(1, 100.0, 110.0),
+            trade(1, 100.0, 105.0),
+            trade(1, 100.0, 101.0),
+            trade(1, 100.0, 95.0),
+            trade(1, 100.0, 90.0),
+            trade(1, 100.0, 100.0),
+            trade(1, 100.0, 102.0),
+            trade(1, 100.0, 103.0),
+            trade(1, 100.0, 104.0),
+            trade(1, 100.0, 105.0),
+            trade(1, 100.0, 99.0),
+        ];
+
+        let stats = BacktestStats::calculate(&trades, ReturnType::Simple, 0, f64::NAN);
+        assert_eq!(stats.max_consecutive_wins, 4);
+        assert_eq!(stats.max_consecutive_losses, 2);
+    }
+
+    #[test]
+    fn log_return_sharpe_matches_a_hand_computed_value_and_differs_from_simple() {
+        let trades = vec![
+            trade(1, 100.0, 110.0),
+            trade(1, 100.0, 95.0),
+            trade(1, 100.0, 120.0),
+            trade(1, 100.0, 90.0),
+        ];
+
+        let simple = BacktestStats::calculate(&trades, ReturnType::Simple, 0, f64::NAN).sharpe;
+        let log = BacktestStats::calculate(&trades, ReturnType::Log, 0, f64::NAN).sharpe;
+
+        let log_returns: Vec<f64> = [0.10, -0.05, 0.20, -0.10].iter().map(|r: &f64| (1.0 + r).ln()).collect();
+        let mean = log_returns.iter().sum::<f64>() / log_returns.len() as f64;
+        let variance = log_returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / log_returns.len() as f64;
+        let expected_log_sharpe = mean / variance.sqrt();
+
+        assert!((log - expected_log_sharpe).abs() < 1e-9);
+        assert!((simple - log).abs() > 1e-6, "simple={simple} log={log} should differ");
+    }
+
+    #[test]
+    fn asymmetric_wins_and_losses_score_higher_on_sortino_than_sharpe() {
+        // Big wins, small losses: upside volatility should hurt Sharpe
+        // (which penalizes all deviation) but not Sortino (which only
+        // penalizes downside deviation).
+        let trades = vec![
+            trade(1, 100.0, 130.0),
+            trade(1, 100.0, 95.0),
+            trade(1, 100.0, 140.0),
+            trade(1, 100.0, 97.0),
+            trade(1, 100.0, 125.0),
+        ];
+
+        let stats = BacktestStats::calculate(&trades, ReturnType::Simple, 0, f64::NAN);
+
+        assert!(stats.sortino > stats.sharpe, "sortino={} sharpe={}", stats.sortino, stats.sharpe);
+    }
+
+    #[test]
+    fn sortino_and_calmar_are_nan_with_no_trades() {
+        let stats = BacktestStats::calculate(&[], ReturnType::Simple, 0, f64::NAN);
+        assert!(stats.sortino.is_nan());
+        assert!(stats.calmar.is_nan());
+    }
+
+    #[test]
+    fn calmar_relates_total_return_to_the_worst_drawdown() {
+        // Equity curve: 1.0 -> 1.20 (peak) -> 0.90 (trough, -25% drawdown)
+        // -> 1.08 (total return +8%).
+        let trades = vec![trade(1, 100.0, 120.0), trade(1, 100.0, 75.0), trade(1, 100.0, 120.0)];
+
+        let stats = BacktestStats::calculate(&trades, ReturnType::Simple, 0, f64::NAN);
+
+        assert!((stats.calmar - (0.08 / 0.25)).abs() < 1e-9, "calmar={}", stats.calmar);
+    }
+
+    #[test]
+    fn calmar_is_nan_when_the_equity_curve_never_draws_down() {
+        let trades = vec![trade(1, 100.0, 110.0), trade(1, 100.0, 105.0)];
+        let stats = BacktestStats::calculate(&trades, ReturnType::Simple, 0, f64::NAN);
+        assert!(stats.calmar.is_nan());
+    }
+
+    fn trade_held(entry: f64, exit: f64, bars_held: u32) -> Trade {
+        Trade { bars_held, ..trade(1, entry, exit) }
+    }
+
+    #[test]
+    fn max_drawdown_duration_spans_every_bar_underwater_until_a_new_peak() {
+        // Equity curve: 1.0 -> 1.20 (new peak, resets the stretch) -> 1.02
+        // (underwater) -> 0.90 (still underwater) -> 1.25 (new peak, ends
+        // the stretch). Underwater for trades 2 and 3: (3 + 1) + (2 + 1) = 7
+        // bars.
+        let trades = vec![
+            trade_held(100.0, 120.0, 0), // equity 1.20, new peak
+            trade_held(100.0, 85.0, 3),  // equity 1.02, underwater
+            trade_held(100.0, 88.24, 2), // equity 0.90, underwater
+            trade_held(100.0, 138.9, 1), // equity 1.25, new peak
+        ];
+
+        let stats = BacktestStats::calculate(&trades, ReturnType::Simple, 0, f64::NAN);
+        assert_eq!(stats.max_drawdown_duration, 7);
+    }
+
+    #[test]
+    fn max_drawdown_duration_is_zero_with_no_trades_or_no_drawdown() {
+        let stats = BacktestStats::calculate(&[], ReturnType::Simple, 0, f64::NAN);
+        assert_eq!(stats.max_drawdown_duration, 0);
+
+        let trades = vec![trade(1, 100.0, 110.0), trade(1, 100.0, 105.0)];
+        let stats = BacktestStats::calculate(&trades, ReturnType::Simple, 0, f64::NAN);
+        assert_eq!(stats.max_drawdown_duration, 0);
+    }
+
+    #[test]
+    fn time_in_market_pct_sums_entry_plus_held_bars_over_the_backtest_window() {
+        // 2 trades, bars_held 4 and 9 -> (4 + 1) + (9 + 1) = 15 bars in
+        // market out of 50 total.
+        let trades = vec![trade_held(100.0, 110.0, 4), trade_held(100.0, 95.0, 9)];
+        let stats = BacktestStats::calculate(&trades, ReturnType::Simple, 50, f64::NAN);
+        assert!((stats.time_in_market_pct - 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn time_in_market_pct_is_nan_with_zero_total_bars() {
+        let trades = vec![trade(1, 100.0, 110.0)];
+        let stats = BacktestStats::calculate(&trades, ReturnType::Simple, 0, f64::NAN);
+        assert!(stats.time_in_market_pct.is_nan());
+    }
+
+    #[test]
+    fn alpha_is_negative_when_the_strategy_underperforms_a_rising_market() {
+        use crate::backtest::BacktestEngine;
+        use crate::kline::Bar;
+        use crate::strategy::{Action, Bracket, Signal};
+
+        let mut engine = BacktestEngine::new(1, Default::default());
+        let bar = |high: f64, close: f64| Bar { ts: 0, open: close, high, low: close, close, volume: 1.0 };
+
+        // Market rises from 100 to 150 (+50%) over the window, but the
+        // strategy only captures a small piece of it: one long opened at
+        // 100 with a 10% target, closed at 110 (+10%) once that target hits.
+        let bracket = Some(Bracket { stop_pct: 50.0, target_pct: 10.0 });
+        engine.process_signal(&Signal { action: Action::Buy, bracket }, &bar(100.0, 100.0));
+        engine.process_signal(&Signal { action: Action::Hold, bracket: None }, &bar(110.0, 110.0));
+        engine.process_signal(&Signal { action: Action::Hold, bracket: None }, &bar(150.0, 150.0));
+
+        let benchmark = engine.benchmark_return_pct();
+        assert!((benchmark - 50.0).abs() < 1e-9, "benchmark={benchmark}");
+
+        let stats = BacktestStats::calculate(&engine.portfolio.trades, ReturnType::Simple, 3, benchmark);
+        assert!((stats.return_pct - 10.0).abs() < 1e-9, "return_pct={}", stats.return_pct);
+        assert!(stats.alpha < 0.0, "alpha={}", stats.alpha);
+    }
+}
@@ -0,0 +1,228 @@
+//! Fixed-capacity circular buffer used to store indicator/price history.
+
+/// A ring buffer over `T` that always reports values in "distance from the
+/// most recent push" order via [`CircularColumn::get_from_end`].
+#[derive(Debug, Clone)]
+pub struct CircularColumn<T> {
+    buf: Vec<T>,
+    capacity: usize,
+    len: usize,
+    /// Index one past the most recently written slot.
+    head: usize,
+}
+
+impl<T: Copy + Default> CircularColumn<T> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "CircularColumn capacity must be > 0");
+        Self {
+            buf: vec![T::default(); capacity],
+            capacity,
+            len: 0,
+            head: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The raw backing storage, in physical (not chronological) order.
+    /// Combined with [`CircularColumn::head`] and [`CircularColumn::len`],
+    /// this is enough for a zero-copy consumer (e.g. a numpy view) to
+    /// reconstruct chronological order without going through
+    /// [`CircularColumn::get_from_end`] one value at a time.
+    pub fn raw_slice(&self) -> &[T] {
+        &self.buf
+    }
+
+    /// Index one past the most recently written slot; the oldest value in
+    /// the buffer once it has wrapped.
+    pub fn head(&self) -> usize {
+        self.head
+    }
+
+    /// `(raw_slice, capacity, len, head)`, the convention zero-copy FFI
+    /// exports (numpy, N-API typed arrays) build their views from.
+    pub fn raw_view(&self) -> (&[T], usize, usize, usize) {
+        (&self.buf, self.capacity, self.len, self.head)
+    }
+
+    /// The buffer's contents split into at most two chronologically-ordered
+    /// slices (oldest first), the same "at most one wrap point" shape
+    /// `VecDeque::as_slices` exposes. While the buffer hasn't wrapped yet
+    /// (`len < capacity`), everything lives in one contiguous slice at the
+    /// front and the second is empty; once it's wrapped, the oldest values
+    /// sit from `head` to the end of `buf` and the rest wrap back to the
+    /// start.
+    pub fn chronological_slices(&self) -> (&[T], &[T]) {
+        if self.len < self.capacity {
+            (&self.buf[..self.len], &[])
+        } else {
+            (&self.buf[self.head..], &self.buf[..self.head])
+        }
+    }
+
+    /// Iterate the buffer's contents oldest to newest, without allocating,
+    /// regardless of where `head` currently sits.
+    pub fn chronological_iter(&self) -> impl Iterator<Item = T> + '_ {
+        let (front, back) = self.chronological_slices();
+        front.iter().chain(back.iter()).copied()
+    }
+
+    /// The buffer's contents in chronological order (oldest first), for
+    /// callers that want a plain `Vec` rather than [`CircularColumn::raw_view`]'s
+    /// zero-copy parts.
+    pub fn to_vec(&self) -> Vec<T> {
+        self.chronological_iter().collect()
+    }
+
+    /// Append a new value, evicting the oldest one once at capacity.
+    pub fn push(&mut self, value: T) {
+        self.buf[self.head] = value;
+        self.head = (self.head + 1) % self.capacity;
+        if self.len < self.capacity {
+            self.len += 1;
+        }
+    }
+
+    /// Overwrite the most recently pushed value in place (used when a live
+    /// bar is still forming and gets revised in place rather than closed).
+    pub fn update_last(&mut self, value: T) {
+        if self.len == 0 {
+            self.push(value);
+            return;
+        }
+        let last = (self.head + self.capacity - 1) % self.capacity;
+        self.buf[last] = value;
+    }
+
+    /// Read a value counting back from the most recent push: `0` is the
+    /// last pushed value, `1` is the one before that, and so on. Returns
+    /// `None` once `n` reaches beyond how much history is stored.
+    pub fn get_from_end(&self, n: usize) -> Option<T> {
+        if n >= self.len {
+            return None;
+        }
+        let idx = (self.head + self.capacity - 1 - n) % self.capacity;
+        Some(self.buf[idx])
+    }
+
+    /// Read a value by chronological index (`0` = oldest currently
+    /// retained value), the logical-index complement to
+    /// [`CircularColumn::get_from_end`]'s distance-from-most-recent
+    /// addressing -- the same ordering [`CircularColumn::to_vec`] returns.
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        self.get_from_end(self.len - 1 - index)
+    }
+
+    /// Overwrite the value at chronological `index` in place. Returns
+    /// `false` without writing if `index` is out of range.
+    pub fn set(&mut self, index: usize, value: T) -> bool {
+        if index >= self.len {
+            return false;
+        }
+        let n = self.len - 1 - index;
+        let idx = (self.head + self.capacity - 1 - n) % self.capacity;
+        self.buf[idx] = value;
+        true
+    }
+
+    /// Drop every stored value, keeping `capacity` unchanged -- the same
+    /// "definitions survive, accumulated state doesn't" reset
+    /// [`crate::engine::HQuant::reset`] applies at every layer it touches.
+    pub fn clear(&mut self) {
+        self.buf.fill(T::default());
+        self.len = 0;
+        self.head = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_from_end_tracks_recency() {
+        let mut col: CircularColumn<f64> = CircularColumn::new(3);
+        col.push(1.0);
+        col.push(2.0);
+        col.push(3.0);
+        assert_eq!(col.get_from_end(0), Some(3.0));
+        assert_eq!(col.get_from_end(1), Some(2.0));
+        assert_eq!(col.get_from_end(2), Some(1.0));
+        assert_eq!(col.get_from_end(3), None);
+
+        col.push(4.0); // evicts 1.0
+        assert_eq!(col.get_from_end(0), Some(4.0));
+        assert_eq!(col.get_from_end(2), Some(2.0));
+    }
+
+    #[test]
+    fn get_addresses_chronologically_across_a_wrap() {
+        let mut col: CircularColumn<f64> = CircularColumn::new(3);
+        col.push(1.0);
+        col.push(2.0);
+        col.push(3.0);
+        col.push(4.0); // evicts 1.0: chronological order is now 2.0, 3.0, 4.0
+
+        assert_eq!(col.get(0), Some(2.0));
+        assert_eq!(col.get(1), Some(3.0));
+        assert_eq!(col.get(2), Some(4.0));
+        assert_eq!(col.get(3), None);
+    }
+
+    #[test]
+    fn set_overwrites_a_chronological_index_across_a_wrap() {
+        let mut col: CircularColumn<f64> = CircularColumn::new(3);
+        col.push(1.0);
+        col.push(2.0);
+        col.push(3.0);
+        col.push(4.0); // evicts 1.0
+
+        assert!(col.set(0, 20.0));
+        assert_eq!(col.to_vec(), vec![20.0, 3.0, 4.0]);
+        assert!(!col.set(3, 99.0));
+    }
+
+    #[test]
+    fn chronological_iter_yields_oldest_to_newest_past_capacity() {
+        let mut col: CircularColumn<f64> = CircularColumn::new(3);
+        for x in [1.0, 2.0, 3.0, 4.0, 5.0] {
+            col.push(x); // wraps twice: buf ends up holding 3.0, 4.0, 5.0
+        }
+        assert_eq!(col.chronological_iter().collect::<Vec<_>>(), vec![3.0, 4.0, 5.0]);
+        assert_eq!(col.to_vec(), vec![3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn chronological_iter_matches_a_single_contiguous_slice_before_wrapping() {
+        let mut col: CircularColumn<f64> = CircularColumn::new(5);
+        col.push(1.0);
+        col.push(2.0);
+        let (front, back) = col.chronological_slices();
+        assert_eq!(front, &[1.0, 2.0]);
+        assert!(back.is_empty());
+    }
+
+    #[test]
+    fn update_last_overwrites_without_advancing() {
+        let mut col: CircularColumn<f64> = CircularColumn::new(3);
+        col.push(1.0);
+        col.push(2.0);
+        col.update_last(20.0);
+        assert_eq!(col.get_from_end(0), Some(20.0));
+        assert_eq!(col.get_from_end(1), Some(1.0));
+        assert_eq!(col.len(), 2);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod column;
+pub mod ring_buffer;
+pub mod rolling_correlation;
+pub mod rolling_extrema;
+
+pub use column::CircularColumn;
+pub use ring_buffer::F64RingBuffer;
+pub use rolling_correlation::RollingCorrelation;
+pub use rolling_extrema::RollingExtrema;
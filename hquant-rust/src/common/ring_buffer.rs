@@ -0,0 +1,351 @@
+//! Fixed-capacity rolling window over `f64` with O(1) mean/variance,
+//! tracked incrementally via Welford's algorithm rather than the naive
+//! `sum_sq/n - mean*mean` formula, which suffers catastrophic cancellation
+//! once prices get large (e.g. BTC near 70000) relative to the window's
+//! spread.
+//!
+//! There's no generic `RingBuffer<T>` here (and so no manual
+//! `head`/`to_actual_index` bookkeeping to preserve) -- [`F64RingBuffer`]
+//! is `f64`-specific and backed directly by a `VecDeque`, which already
+//! handles index bookkeeping for us. `pop_front`/`peek_front` below give
+//! the same "consume from the front" capability a generic ring buffer
+//! would, decrementing the Welford accumulator on pop the same way `push`
+//! does on eviction.
+
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub struct F64RingBuffer {
+    capacity: usize,
+    window: VecDeque<f64>,
+    /// Sample count backing `mean`/`m2`, tracked explicitly rather than
+    /// derived from `window.len()` so `add`/`remove` stay correct
+    /// regardless of the order callers mutate `window` in.
+    n: usize,
+    mean: f64,
+    /// Sum of squared deviations from the running mean.
+    m2: f64,
+}
+
+impl F64RingBuffer {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "F64RingBuffer capacity must be > 0");
+        Self {
+            capacity,
+            window: VecDeque::with_capacity(capacity),
+            n: 0,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    /// The window's contents, oldest first -- e.g. for
+    /// [`crate::indicator::exec::StdDevBand::serialize_state`] to persist,
+    /// then replay through a fresh buffer on restore rather than
+    /// serializing the Welford accumulator directly.
+    pub fn to_vec(&self) -> Vec<f64> {
+        self.window.iter().copied().collect()
+    }
+
+    /// Fold `x` into the running Welford accumulator as a new sample.
+    fn add(&mut self, x: f64) {
+        self.n += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.n as f64;
+        let delta2 = x - self.mean;
+        self.m2 += delta * delta2;
+    }
+
+    /// Undo `y`'s contribution to the running Welford accumulator.
+    fn remove(&mut self, y: f64) {
+        if self.n <= 1 {
+            self.n = 0;
+            self.mean = 0.0;
+            self.m2 = 0.0;
+            return;
+        }
+        self.n -= 1;
+        let delta = y - self.mean;
+        self.mean -= delta / self.n as f64;
+        let delta2 = y - self.mean;
+        self.m2 -= delta * delta2;
+    }
+
+    /// Push a new sample, evicting the oldest one once at capacity.
+    pub fn push(&mut self, x: f64) {
+        if self.window.len() == self.capacity {
+            let evicted = self.window.pop_front().unwrap();
+            self.remove(evicted);
+        }
+        self.add(x);
+        self.window.push_back(x);
+    }
+
+    /// Remove and return the oldest sample, decrementing the running
+    /// Welford accumulator to match. For windowed replay callers that need
+    /// to drain a fixed-lookahead window from the front rather than let
+    /// `push` silently evict it.
+    pub fn pop_front(&mut self) -> Option<f64> {
+        let x = self.window.pop_front()?;
+        self.remove(x);
+        Some(x)
+    }
+
+    /// The oldest sample without removing it.
+    pub fn peek_front(&self) -> Option<&f64> {
+        self.window.front()
+    }
+
+    /// Revise the most recently pushed sample in place.
+    pub fn update_last(&mut self, x: f64) {
+        match self.window.pop_back() {
+            Some(old) => {
+                self.remove(old);
+                self.add(x);
+                self.window.push_back(x);
+            }
+            None => self.push(x),
+        }
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.window.is_empty() {
+            f64::NAN
+        } else {
+            self.mean
+        }
+    }
+
+    /// Population variance. Clamped at zero as a final guard against
+    /// floating-point rounding pushing it a hair below zero.
+    pub fn variance(&self) -> f64 {
+        if self.window.is_empty() {
+            return f64::NAN;
+        }
+        (self.m2 / self.n as f64).max(0.0)
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Change the window's capacity in place, preserving logical order
+    /// (oldest first). Shrinking truncates the oldest samples down to the
+    /// new capacity, evicting them from the Welford accumulator via
+    /// [`Self::pop_front`] exactly as if they'd aged out naturally. Growing
+    /// just raises the ceiling -- it does *not* zero-extend the window with
+    /// synthetic samples, since padding a price series with fake `0.0`s
+    /// would silently corrupt `mean`/`variance` for every caller relying on
+    /// them, the same class of bug [`crate::spread::SpreadBuilder`]'s
+    /// timestamp-alignment check exists to prevent. A freshly grown buffer
+    /// just has room to grow into as real samples arrive.
+    pub fn resize(&mut self, new_capacity: usize) {
+        assert!(new_capacity > 0, "F64RingBuffer capacity must be > 0");
+        while self.window.len() > new_capacity {
+            self.pop_front();
+        }
+        self.capacity = new_capacity;
+    }
+
+    /// Mean over just the most recent `window` samples (`1 <= window <=
+    /// len()`). `window == len()` reuses the O(1) cached [`Self::mean`];
+    /// any smaller window has no cached partial sum to reuse (this buffer
+    /// only tracks Welford state for the *whole* window), so it falls back
+    /// to an O(window) sum over the most recently pushed samples.
+    pub fn rolling_mean(&self, window: usize) -> f64 {
+        assert!(window > 0, "rolling_mean window must be > 0");
+        assert!(
+            window <= self.window.len(),
+            "rolling_mean window ({window}) exceeds buffer length ({})",
+            self.window.len()
+        );
+        if window == self.window.len() {
+            return self.mean;
+        }
+        self.window.iter().rev().take(window).sum::<f64>() / window as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variance_of_constant_series_is_zero() {
+        let mut buf = F64RingBuffer::new(5);
+        for _ in 0..5 {
+            buf.push(3.0);
+        }
+        assert_eq!(buf.variance(), 0.0);
+        assert_eq!(buf.std_dev(), 0.0);
+    }
+
+    #[test]
+    fn variance_matches_textbook_formula_on_a_small_window() {
+        let mut buf = F64RingBuffer::new(4);
+        for x in [2.0, 4.0, 4.0, 4.0] {
+            buf.push(x);
+        }
+        // mean = 3.5, population variance = 0.75
+        assert!((buf.variance() - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn near_constant_large_prices_stay_non_negative_and_finite() {
+        // Mirrors BTC-scale prices with sub-cent jitter, the case that
+        // breaks `sum_sq/n - mean*mean` via cancellation.
+        let mut buf = F64RingBuffer::new(20);
+        let base = 70_000.0;
+        for i in 0..40 {
+            buf.push(base + (i % 3) as f64 * 1e-6);
+        }
+        assert!(buf.variance() >= 0.0);
+        assert!(buf.std_dev().is_finite());
+    }
+
+    #[test]
+    fn pop_front_drains_oldest_first_and_keeps_the_mean_correct() {
+        let mut buf = F64RingBuffer::new(10);
+        for x in [1.0, 2.0, 3.0, 4.0] {
+            buf.push(x);
+        }
+        assert_eq!(buf.peek_front(), Some(&1.0));
+        assert_eq!(buf.pop_front(), Some(1.0));
+        assert_eq!(buf.peek_front(), Some(&2.0));
+        assert_eq!(buf.len(), 3);
+        assert!((buf.mean() - (2.0 + 3.0 + 4.0) / 3.0).abs() < 1e-9);
+
+        assert_eq!(buf.pop_front(), Some(2.0));
+        assert_eq!(buf.pop_front(), Some(3.0));
+        assert_eq!(buf.pop_front(), Some(4.0));
+        assert_eq!(buf.pop_front(), None);
+        assert_eq!(buf.peek_front(), None);
+        assert!(buf.is_empty());
+        assert!(buf.mean().is_nan());
+    }
+
+    #[test]
+    fn mixing_push_and_pop_matches_a_plain_deque_window() {
+        let mut buf = F64RingBuffer::new(3);
+        buf.push(1.0);
+        buf.push(2.0);
+        assert_eq!(buf.pop_front(), Some(1.0));
+        buf.push(3.0);
+        buf.push(4.0);
+        // window is now [2.0, 3.0, 4.0] after evicting nothing (pop_front
+        // already made room, so push never had to evict on top of it)
+        assert_eq!(buf.len(), 3);
+        assert!((buf.mean() - (2.0 + 3.0 + 4.0) / 3.0).abs() < 1e-9);
+        assert_eq!(buf.pop_front(), Some(2.0));
+        assert_eq!(buf.pop_front(), Some(3.0));
+        assert_eq!(buf.pop_front(), Some(4.0));
+        assert_eq!(buf.pop_front(), None);
+    }
+
+    #[test]
+    fn rolling_mean_over_the_full_window_matches_the_cached_mean() {
+        let mut buf = F64RingBuffer::new(5);
+        for x in [2.0, 4.0, 6.0, 8.0, 10.0] {
+            buf.push(x);
+        }
+        assert_eq!(buf.rolling_mean(5), buf.mean());
+    }
+
+    #[test]
+    fn rolling_mean_over_a_partial_window_matches_a_brute_force_mean_on_random_data() {
+        let mut buf = F64RingBuffer::new(50);
+
+        // Deterministic pseudo-random walk, same generator as
+        // `RollingExtrema`'s brute-force comparison test.
+        let mut state: u64 = 88172645463325252;
+        let mut price = 100.0;
+        for _ in 0..2_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let step = ((state % 2001) as f64 - 1000.0) / 100.0;
+            price += step;
+            buf.push(price);
+
+            let contents = buf.to_vec();
+            for window in [1, 3, 17, buf.len()] {
+                if window > contents.len() {
+                    continue;
+                }
+                let brute_force: f64 = contents[contents.len() - window..].iter().sum::<f64>() / window as f64;
+                assert!((buf.rolling_mean(window) - brute_force).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds buffer length")]
+    fn rolling_mean_rejects_a_window_larger_than_the_buffer() {
+        let mut buf = F64RingBuffer::new(5);
+        buf.push(1.0);
+        buf.rolling_mean(2);
+    }
+
+    #[test]
+    fn shrinking_a_wrapped_buffer_truncates_the_oldest_samples() {
+        let mut buf = F64RingBuffer::new(5);
+        // Wrap the buffer so the oldest logical sample isn't at deque index 0.
+        for x in [1.0, 2.0, 3.0, 4.0, 5.0, 6.0] {
+            buf.push(x);
+        }
+        assert_eq!(buf.to_vec(), vec![2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        buf.resize(3);
+        assert_eq!(buf.capacity(), 3);
+        assert_eq!(buf.len(), 3);
+        assert_eq!(buf.to_vec(), vec![4.0, 5.0, 6.0]);
+        assert!((buf.mean() - (4.0 + 5.0 + 6.0) / 3.0).abs() < 1e-9);
+
+        // Capacity is now actually enforced, not just the accumulator.
+        buf.push(7.0);
+        assert_eq!(buf.to_vec(), vec![5.0, 6.0, 7.0]);
+    }
+
+    #[test]
+    fn growing_a_wrapped_buffer_preserves_order_without_padding() {
+        let mut buf = F64RingBuffer::new(3);
+        for x in [1.0, 2.0, 3.0, 4.0] {
+            buf.push(x);
+        }
+        assert_eq!(buf.to_vec(), vec![2.0, 3.0, 4.0]);
+
+        buf.resize(5);
+        assert_eq!(buf.capacity(), 5);
+        assert_eq!(buf.len(), 3);
+        assert_eq!(buf.to_vec(), vec![2.0, 3.0, 4.0]);
+        assert!((buf.mean() - (2.0 + 3.0 + 4.0) / 3.0).abs() < 1e-9);
+
+        // Room to grow into as new samples arrive, without evicting yet.
+        buf.push(5.0);
+        buf.push(6.0);
+        assert_eq!(buf.to_vec(), vec![2.0, 3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn update_last_revises_without_shifting_window() {
+        let mut buf = F64RingBuffer::new(3);
+        buf.push(1.0);
+        buf.push(2.0);
+        buf.push(3.0);
+        buf.update_last(30.0);
+        assert_eq!(buf.len(), 3);
+        assert!((buf.mean() - (1.0 + 2.0 + 30.0) / 3.0).abs() < 1e-9);
+    }
+}
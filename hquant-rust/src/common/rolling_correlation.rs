@@ -0,0 +1,260 @@
+//! Fixed-capacity rolling Pearson correlation (and beta) between two paired
+//! `f64` series, for pairs trading: how tightly an asset's close tracks a
+//! reference series over a moving window, and how much of the reference's
+//! move it captures.
+//!
+//! Tracked via the paired generalization of [`F64RingBuffer`]'s Welford
+//! accumulator (running means, sums of squared deviations `m2x`/`m2y`, and
+//! their cross term `c`, the running covariance's numerator) rather than
+//! the naive `sum_xy - n*mean_x*mean_y` formula, for the same
+//! catastrophic-cancellation reason documented on [`F64RingBuffer`]. `c`,
+//! `m2x` and `m2y` share the same divisor (`n` for population, `n-1` for
+//! sample), so it cancels out of both `correlation` (`c /
+//! sqrt(m2x*m2y)`) and `beta` (`c / m2x`) without ever being computed.
+//!
+//! [`F64RingBuffer`]: super::F64RingBuffer
+
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub struct RollingCorrelation {
+    capacity: usize,
+    window: VecDeque<(f64, f64)>,
+    /// Sample count backing the accumulators below, tracked explicitly the
+    /// same way [`super::F64RingBuffer`] does.
+    n: usize,
+    mean_x: f64,
+    mean_y: f64,
+    /// Sum of squared deviations of `x`/`y` from their running means.
+    m2x: f64,
+    m2y: f64,
+    /// Sum of cross-deviations of `x` and `y` from their running means --
+    /// the running covariance's numerator.
+    c: f64,
+}
+
+impl RollingCorrelation {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "RollingCorrelation capacity must be > 0");
+        Self {
+            capacity,
+            window: VecDeque::with_capacity(capacity),
+            n: 0,
+            mean_x: 0.0,
+            mean_y: 0.0,
+            m2x: 0.0,
+            m2y: 0.0,
+            c: 0.0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    /// Fold `(x, y)` into the running accumulators as a new sample.
+    fn add(&mut self, x: f64, y: f64) {
+        self.n += 1;
+        let dx = x - self.mean_x;
+        self.mean_x += dx / self.n as f64;
+        let dy = y - self.mean_y;
+        self.mean_y += dy / self.n as f64;
+        let dx2 = x - self.mean_x;
+        self.m2x += dx * dx2;
+        let dy2 = y - self.mean_y;
+        self.m2y += dy * dy2;
+        self.c += dx * dy2;
+    }
+
+    /// Undo `(x, y)`'s contribution to the running accumulators.
+    fn remove(&mut self, x: f64, y: f64) {
+        if self.n <= 1 {
+            self.n = 0;
+            self.mean_x = 0.0;
+            self.mean_y = 0.0;
+            self.m2x = 0.0;
+            self.m2y = 0.0;
+            self.c = 0.0;
+            return;
+        }
+        self.n -= 1;
+        let dx = x - self.mean_x;
+        self.mean_x -= dx / self.n as f64;
+        let dy = y - self.mean_y;
+        self.mean_y -= dy / self.n as f64;
+        let dx2 = x - self.mean_x;
+        self.m2x -= dx * dx2;
+        let dy2 = y - self.mean_y;
+        self.m2y -= dy * dy2;
+        self.c -= dx * dy2;
+    }
+
+    /// Push a new `(x, y)` pair, evicting the oldest one once at capacity.
+    pub fn push(&mut self, x: f64, y: f64) {
+        if self.window.len() == self.capacity {
+            let (ex, ey) = self.window.pop_front().unwrap();
+            self.remove(ex, ey);
+        }
+        self.add(x, y);
+        self.window.push_back((x, y));
+    }
+
+    /// Revise the most recently pushed pair in place.
+    pub fn update_last(&mut self, x: f64, y: f64) {
+        match self.window.pop_back() {
+            Some((ex, ey)) => {
+                self.remove(ex, ey);
+                self.add(x, y);
+                self.window.push_back((x, y));
+            }
+            None => self.push(x, y),
+        }
+    }
+
+    /// Pearson correlation over the window, `0.0` if either series has no
+    /// variance to correlate against (a flat series can't move with or
+    /// against anything).
+    pub fn correlation(&self) -> f64 {
+        if self.window.is_empty() {
+            return f64::NAN;
+        }
+        let denom = (self.m2x * self.m2y).sqrt();
+        if denom == 0.0 {
+            0.0
+        } else {
+            self.c / denom
+        }
+    }
+
+    /// Beta of `y` against `x`: `cov(x, y) / var(x)`, `0.0` if `x` has no
+    /// variance over the window.
+    pub fn beta(&self) -> f64 {
+        if self.window.is_empty() {
+            return f64::NAN;
+        }
+        if self.m2x == 0.0 {
+            0.0
+        } else {
+            self.c / self.m2x
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfectly_correlated_series_reports_correlation_of_one() {
+        let mut rc = RollingCorrelation::new(10);
+        for i in 0..10 {
+            let x = 100.0 + i as f64;
+            rc.push(x, 2.0 * x + 5.0);
+        }
+        assert!((rc.correlation() - 1.0).abs() < 1e-9);
+        assert!((rc.beta() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn anti_correlated_series_reports_correlation_of_negative_one() {
+        let mut rc = RollingCorrelation::new(10);
+        for i in 0..10 {
+            let x = 100.0 + i as f64;
+            rc.push(x, 50.0 - x);
+        }
+        assert!((rc.correlation() - (-1.0)).abs() < 1e-9);
+        assert!((rc.beta() - (-1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn uncorrelated_flat_series_reports_zero_rather_than_dividing_by_zero() {
+        let mut rc = RollingCorrelation::new(5);
+        for i in 0..5 {
+            rc.push(100.0 + i as f64, 42.0);
+        }
+        assert_eq!(rc.correlation(), 0.0);
+        assert_eq!(rc.beta(), 0.0);
+    }
+
+    #[test]
+    fn update_last_revises_the_most_recent_pair_without_shifting_the_window() {
+        let mut a = RollingCorrelation::new(5);
+        let mut b = RollingCorrelation::new(5);
+        for i in 0..4 {
+            let x = 100.0 + i as f64;
+            a.push(x, 2.0 * x);
+            b.push(x, 2.0 * x);
+        }
+        a.push(999.0, -999.0);
+        a.update_last(104.0, 208.0);
+        b.push(104.0, 208.0);
+
+        assert!((a.correlation() - b.correlation()).abs() < 1e-9);
+        assert!((a.beta() - b.beta()).abs() < 1e-9);
+        assert_eq!(a.len(), b.len());
+    }
+
+    #[test]
+    fn empty_window_reports_nan_rather_than_zero() {
+        let rc = RollingCorrelation::new(5);
+        assert!(rc.correlation().is_nan());
+        assert!(rc.beta().is_nan());
+    }
+
+    /// Naive `sum_xy - n*mean_x*mean_y` correlation/beta over `pairs`,
+    /// deliberately not sharing any code with [`RollingCorrelation`]'s
+    /// Welford accumulators, so this is a real check against the eviction
+    /// path rather than the same algebra checking itself.
+    fn brute_force_correlation_and_beta(pairs: &[(f64, f64)]) -> (f64, f64) {
+        let n = pairs.len() as f64;
+        let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let cov = pairs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>();
+        let var_x = pairs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+        let var_y = pairs.iter().map(|(_, y)| (y - mean_y).powi(2)).sum::<f64>();
+        // Same "no variance to correlate against" -> 0.0 convention as
+        // `RollingCorrelation::correlation`/`beta`, rather than the NaN a
+        // literal division by zero would give here.
+        let correlation = if var_x * var_y == 0.0 { 0.0 } else { cov / (var_x * var_y).sqrt() };
+        let beta = if var_x == 0.0 { 0.0 } else { cov / var_x };
+        (correlation, beta)
+    }
+
+    #[test]
+    fn pushing_past_capacity_evicts_the_oldest_pair_and_matches_a_brute_force_window() {
+        // Every pair after the first `capacity` triggers `push`'s
+        // `pop_front`/`remove` eviction path -- the "rolling" behavior this
+        // type exists for, as opposed to `update_last`'s revise-in-place.
+        let capacity = 5;
+        let mut rc = RollingCorrelation::new(capacity);
+        let all_pairs: Vec<(f64, f64)> =
+            (0..20).map(|i| { let x = 100.0 + i as f64 * 1.7; (x, 3.0 * x - (i as f64 % 4.0).powi(2)) }).collect();
+
+        for (i, &(x, y)) in all_pairs.iter().enumerate() {
+            rc.push(x, y);
+            let window_start = (i + 1).saturating_sub(capacity);
+            let window = &all_pairs[window_start..=i];
+            assert_eq!(rc.len(), window.len());
+
+            let (expected_correlation, expected_beta) = brute_force_correlation_and_beta(window);
+            assert!(
+                (rc.correlation() - expected_correlation).abs() < 1e-9,
+                "sample {i}: correlation {} != brute-force {expected_correlation}",
+                rc.correlation()
+            );
+            assert!(
+                (rc.beta() - expected_beta).abs() < 1e-9,
+                "sample {i}: beta {} != brute-force {expected_beta}",
+                rc.beta()
+            );
+        }
+    }
+}
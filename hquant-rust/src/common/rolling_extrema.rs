@@ -0,0 +1,205 @@
+//! Rolling max/min over a fixed-capacity window, backed by monotonic
+//! deques (the classic "sliding window maximum" technique), for indicators
+//! like Donchian channels, Williams %R and Stochastic that would otherwise
+//! re-scan the whole window on every bar. Each value enters and leaves a
+//! deque at most once over its lifetime in the window, so `push` is
+//! amortized O(1) against [`F64RingBuffer`]'s O(n) fold.
+//!
+//! [`RollingExtrema::update_last`] can't patch a deque in place for an
+//! arbitrary revision: `push` discards values as it pops them, so there's
+//! nothing to "put back" if the revised value turns out to be smaller than
+//! something already evicted. Rather than track enough extra state to
+//! reverse that, `update_last` rebuilds both deques from the window's
+//! contents, O(capacity) -- the same naive-fold cost this type otherwise
+//! avoids, paid only on revision rather than on every bar.
+//!
+//! This crate has no bench harness (no `benches/` directory or `criterion`
+//! dependency), so the O(1)-vs-O(n) improvement is argued from the
+//! algorithm rather than measured; `matches_a_naive_scan_over_a_500_period_window`
+//! below checks the two agree at the window size the calling indicators
+//! actually use.
+//!
+//! [`F64RingBuffer`]: super::F64RingBuffer
+
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub struct RollingExtrema {
+    capacity: usize,
+    /// Values currently in the window, oldest first -- the source of truth
+    /// `update_last` rebuilds the deques from.
+    window: VecDeque<f64>,
+    /// Global insertion index of the next pushed sample, used to expire
+    /// deque entries that have fallen out of the window.
+    next_index: usize,
+    /// `(value, insertion index)`, decreasing value front-to-back; the
+    /// front is always the window's maximum.
+    max_deque: VecDeque<(f64, usize)>,
+    /// `(value, insertion index)`, increasing value front-to-back; the
+    /// front is always the window's minimum.
+    min_deque: VecDeque<(f64, usize)>,
+}
+
+impl RollingExtrema {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "RollingExtrema capacity must be > 0");
+        Self {
+            capacity,
+            window: VecDeque::with_capacity(capacity),
+            next_index: 0,
+            max_deque: VecDeque::new(),
+            min_deque: VecDeque::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The window's contents, oldest first -- e.g. for
+    /// [`crate::indicator::exec::RollingMax::serialize_state`] to persist,
+    /// then replay through a fresh `RollingExtrema` on restore rather than
+    /// serializing the deques directly.
+    pub fn to_vec(&self) -> Vec<f64> {
+        self.window.iter().copied().collect()
+    }
+
+    /// Push a new sample, evicting the oldest one once at capacity.
+    pub fn push(&mut self, x: f64) {
+        if self.window.len() == self.capacity {
+            self.window.pop_front();
+        }
+        self.window.push_back(x);
+
+        let index = self.next_index;
+        self.next_index += 1;
+        let oldest_valid_index = index + 1 - self.window.len();
+
+        while self.max_deque.front().is_some_and(|&(_, i)| i < oldest_valid_index) {
+            self.max_deque.pop_front();
+        }
+        while self.max_deque.back().is_some_and(|&(v, _)| v <= x) {
+            self.max_deque.pop_back();
+        }
+        self.max_deque.push_back((x, index));
+
+        while self.min_deque.front().is_some_and(|&(_, i)| i < oldest_valid_index) {
+            self.min_deque.pop_front();
+        }
+        while self.min_deque.back().is_some_and(|&(v, _)| v >= x) {
+            self.min_deque.pop_back();
+        }
+        self.min_deque.push_back((x, index));
+    }
+
+    /// Revise the most recently pushed sample in place. O(capacity): see
+    /// the module doc for why a deque can't be patched in place here.
+    pub fn update_last(&mut self, x: f64) {
+        let mut values: Vec<f64> = self.window.iter().copied().collect();
+        match values.last_mut() {
+            Some(last) => *last = x,
+            None => values.push(x),
+        }
+
+        self.window.clear();
+        self.max_deque.clear();
+        self.min_deque.clear();
+        self.next_index = 0;
+        for v in values {
+            self.push(v);
+        }
+    }
+
+    pub fn max(&self) -> f64 {
+        self.max_deque.front().map(|&(v, _)| v).unwrap_or(f64::NAN)
+    }
+
+    pub fn min(&self) -> f64 {
+        self.min_deque.front().map(|&(v, _)| v).unwrap_or(f64::NAN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_max_min(window: &VecDeque<f64>) -> (f64, f64) {
+        let max = window.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let min = window.iter().copied().fold(f64::INFINITY, f64::min);
+        (max, min)
+    }
+
+    #[test]
+    fn tracks_the_max_and_min_of_a_small_window() {
+        let mut extrema = RollingExtrema::new(3);
+        for x in [5.0, 1.0, 9.0, 2.0] {
+            extrema.push(x);
+        }
+        // Window is now [1.0, 9.0, 2.0]; the 5.0 fell out of range.
+        assert_eq!(extrema.max(), 9.0);
+        assert_eq!(extrema.min(), 1.0);
+    }
+
+    #[test]
+    fn update_last_revises_without_shifting_the_window() {
+        let mut extrema = RollingExtrema::new(3);
+        for x in [5.0, 1.0, 9.0] {
+            extrema.push(x);
+        }
+        extrema.update_last(-3.0);
+        assert_eq!(extrema.len(), 3);
+        assert_eq!(extrema.min(), -3.0);
+        assert_eq!(extrema.max(), 5.0);
+    }
+
+    #[test]
+    fn empty_extrema_reports_nan() {
+        let extrema = RollingExtrema::new(3);
+        assert!(extrema.max().is_nan());
+        assert!(extrema.min().is_nan());
+    }
+
+    #[test]
+    fn matches_a_naive_scan_over_a_500_period_window() {
+        let period = 500;
+        let mut extrema = RollingExtrema::new(period);
+        let mut naive_window: VecDeque<f64> = VecDeque::with_capacity(period);
+
+        // Deterministic pseudo-random walk so the window sees both rising
+        // and falling stretches, exercising deque eviction on both sides.
+        let mut state: u64 = 88172645463325252;
+        let mut price = 100.0;
+        for i in 0..5_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let step = ((state % 2001) as f64 - 1000.0) / 100.0;
+            price += step;
+
+            extrema.push(price);
+            if naive_window.len() == period {
+                naive_window.pop_front();
+            }
+            naive_window.push_back(price);
+
+            if i % 37 == 0 {
+                // Occasionally exercise the update_last / rebuild path too.
+                let revised = price + 0.5;
+                extrema.update_last(revised);
+                *naive_window.back_mut().unwrap() = revised;
+            }
+
+            let (naive_max, naive_min) = naive_max_min(&naive_window);
+            assert_eq!(extrema.max(), naive_max);
+            assert_eq!(extrema.min(), naive_min);
+        }
+    }
+}
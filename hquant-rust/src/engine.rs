@@ -0,0 +1,1070 @@
+//! `HQuant`: the single-instrument facade tying together bar ingestion and
+//! the indicator graph. Language bindings (`ffi/`) wrap this rather than
+//! the graph directly.
+
+use crate::alert::{Alert, AlertId, CrossDirection};
+use crate::backtest::{BacktestConfig, BacktestEngine, PortfolioBacktest};
+use crate::indicator::{IndicatorGraph, NodeId};
+use crate::kline::{Bar, Field, KlineBuffer};
+use crate::strategy::{dsl_parser, CompiledStrategy, FieldHistory, Signal};
+use crate::Result;
+use std::collections::{BTreeSet, HashMap};
+
+/// Handle to a backtest group attached via [`HQuant::attach_backtest`].
+/// Opaque; only meaningful as an argument back into `HQuant`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BacktestHandle(usize);
+
+/// One [`BacktestEngine`] per strategy id attached together, so each
+/// strategy's signals are tracked independently even though they're driven
+/// off the same bar stream and the same `config`.
+struct AttachedBacktest {
+    engines: HashMap<u32, BacktestEngine>,
+}
+
+/// See [`HQuant::set_signal_callback`].
+type SignalCallback = Box<dyn FnMut(&[Signal]) + Send>;
+
+pub struct HQuant {
+    graph: IndicatorGraph,
+    bars: KlineBuffer,
+    indicators: HashMap<String, NodeId>,
+    strategies: HashMap<u32, CompiledStrategy>,
+    /// Priority each strategy in `strategies` was registered with, keyed the
+    /// same way; consulted by `evaluate_signals` to order signals from
+    /// strategies that fire on the same bar. Absent entries (there shouldn't
+    /// be any -- `add_strategy` always inserts one) sort as `0`.
+    strategy_priorities: HashMap<u32, i32>,
+    backtests: Vec<AttachedBacktest>,
+    alerts: HashMap<AlertId, Alert>,
+    next_alert_id: u64,
+    /// Alerts that fired on the most recently pushed bar, drained by
+    /// `poll_alerts` rather than cleared automatically on the next
+    /// `push_bar`, so a caller that polls less often than it pushes bars
+    /// never misses one.
+    pending_alerts: Vec<AlertId>,
+    /// Invoked synchronously, in place of the alert-style poll/drain
+    /// mechanism above, at the end of every `push_bar`/`update_last` that
+    /// produces at least one signal. `+ Send` so `HQuant` stays `Send`
+    /// (needed for `ffi::python`'s `py.allow_threads`); see
+    /// [`HQuant::set_signal_callback`].
+    signal_callback: Option<SignalCallback>,
+    /// Set for the duration of `signal_callback` running, so a callback
+    /// that re-enters `push_bar`/`update_last` on this same `HQuant`
+    /// can't recursively invoke itself.
+    dispatching_signals: bool,
+    /// Bar index (`self.bars.len()` at the time) each strategy last emitted
+    /// a signal through `evaluate_signals`, for strategies with a nonzero
+    /// [`CompiledStrategy::cooldown_bars`]. Only consulted/updated on the
+    /// live `signal_callback` path -- `drive_backtests` evaluates every
+    /// strategy's raw condition on every bar regardless, since a backtest
+    /// wants to see the true signal history rather than a debounced one.
+    last_signal_bar: HashMap<u32, usize>,
+    /// Whether each strategy's condition matched on the bar just evaluated,
+    /// for strategies with [`CompiledStrategy::edge_triggered`] set --
+    /// `evaluate_signals` only lets a match through when this was `false`
+    /// (or absent) on the previous bar. Updated every bar regardless of
+    /// whether `edge_triggered` is set, so flipping the flag on later (via
+    /// `add_strategy`) doesn't see a stale gap in history. Same
+    /// live-callback-only scoping as `last_signal_bar`.
+    last_match_state: HashMap<u32, bool>,
+    /// See [`HQuant::set_strict_ordering`].
+    strict_ordering: bool,
+}
+
+impl HQuant {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            graph: IndicatorGraph::with_history(capacity),
+            bars: KlineBuffer::new(capacity),
+            indicators: HashMap::new(),
+            strategies: HashMap::new(),
+            strategy_priorities: HashMap::new(),
+            backtests: Vec::new(),
+            alerts: HashMap::new(),
+            next_alert_id: 0,
+            pending_alerts: Vec::new(),
+            signal_callback: None,
+            dispatching_signals: false,
+            last_signal_bar: HashMap::new(),
+            last_match_state: HashMap::new(),
+            strict_ordering: false,
+        }
+    }
+
+    /// Toggle rejection of out-of-order bars through [`HQuant::try_push_bar`].
+    /// Off by default, matching `push_bar`'s long-standing "accept whatever
+    /// timestamp arrives" behavior -- turn this on for feeds where a late or
+    /// duplicate bar would otherwise silently corrupt every indicator's
+    /// incremental state.
+    pub fn set_strict_ordering(&mut self, strict: bool) {
+        self.strict_ordering = strict;
+    }
+
+    /// Clear all accumulated bar/indicator/signal state so this `HQuant`
+    /// can be reused for a different symbol, without losing the wiring a
+    /// caller would otherwise have to rebuild: registered indicators,
+    /// strategies, alerts, and the signal callback all survive untouched.
+    ///
+    /// Clears the bar history, resets every indicator node back to its
+    /// just-built state (see [`crate::indicator::IndicatorGraph::reset`]),
+    /// drains any alerts that fired but haven't been polled yet, and forgets
+    /// per-strategy cooldown/edge-trigger bookkeeping -- that bookkeeping is
+    /// keyed off bar indices that are meaningless once the bar history
+    /// underneath it is gone. Attached backtests are left alone: their
+    /// recorded trades belong to the run that produced them, not to the live
+    /// state this resets.
+    pub fn reset(&mut self) {
+        self.bars.clear();
+        self.graph.reset();
+        self.pending_alerts.clear();
+        for alert in self.alerts.values_mut() {
+            alert.reset();
+        }
+        self.last_signal_bar.clear();
+        self.last_match_state.clear();
+    }
+
+    /// Add an indicator by spec string (e.g. `"EMA_12"`, `"TSI(25,13)"`).
+    pub fn add_indicator(&mut self, name: &str) -> Result<NodeId> {
+        let id = self.graph.add_from_spec(name)?;
+        self.indicators.insert(name.to_string(), id);
+        Ok(id)
+    }
+
+    /// Remove an indicator by id, refusing (returning `false`) if another
+    /// live indicator still depends on it. On success, its spec name can be
+    /// `add_indicator`-ed again.
+    pub fn remove_indicator(&mut self, id: NodeId) -> bool {
+        if !self.graph.remove_indicator(id) {
+            return false;
+        }
+        self.indicators.retain(|_, &mut existing| existing != id);
+        true
+    }
+
+    /// Swap an existing indicator's parameters (e.g. `SMA_20` for
+    /// `SMA_50`) in place, backfilling the replacement over the bar history
+    /// already retained in [`HQuant::bars`]. See
+    /// [`crate::indicator::IndicatorGraph::replace`] for the id-stability
+    /// caveats around multi-node specs.
+    pub fn replace_indicator(&mut self, id: NodeId, new_spec: &str) -> Result<NodeId> {
+        let history = self.bars.to_bars();
+        let new_id = self.graph.replace(id, new_spec, &history)?;
+        self.indicators.retain(|_, existing| *existing != id);
+        self.indicators.insert(new_spec.to_string(), new_id);
+        Ok(new_id)
+    }
+
+    /// Register a compiled strategy under `id` with `priority`, replacing
+    /// any strategy previously registered under the same id. Returns the
+    /// distinct indicator node ids the strategy resolved to (see
+    /// [`CompiledStrategy::indicator_ids`]), so a caller -- e.g. a UI
+    /// listing strategies -- can tell when two strategies end up sharing
+    /// computations rather than each owning independent indicators.
+    ///
+    /// `priority` breaks ties when multiple strategies fire on the same bar:
+    /// [`HQuant::evaluate_signals`] emits lower-priority-number signals
+    /// first (then by `id` if two strategies share a priority), so a risk
+    /// strategy that must be processed ahead of an entry strategy can be
+    /// given a lower number. Strategies with no explicit ordering need can
+    /// all use the same priority (e.g. `0`) and will simply sort by `id`.
+    pub fn add_strategy(&mut self, id: u32, strategy: CompiledStrategy, priority: i32) -> BTreeSet<NodeId> {
+        let indicator_ids = strategy.indicator_ids();
+        self.strategies.insert(id, strategy);
+        self.strategy_priorities.insert(id, priority);
+        // A freshly (re-)registered strategy starts with a clean cooldown,
+        // not the previous occupant's -- otherwise replacing strategy `id`
+        // with an unrelated one could inherit a stale "just fired" window.
+        self.last_signal_bar.remove(&id);
+        self.last_match_state.remove(&id);
+        indicator_ids
+    }
+
+    pub fn strategy(&self, id: u32) -> Option<&CompiledStrategy> {
+        self.strategies.get(&id)
+    }
+
+    /// Remove a strategy by id. Returns `false` if no strategy was
+    /// registered under `id`.
+    pub fn remove_strategy(&mut self, id: u32) -> bool {
+        self.last_signal_bar.remove(&id);
+        self.last_match_state.remove(&id);
+        self.strategy_priorities.remove(&id);
+        self.strategies.remove(&id).is_some()
+    }
+
+    pub fn push_bar(&mut self, bar: Bar) {
+        self.bars.push(&bar);
+        self.graph.push_bar(&bar);
+        self.drive_backtests(&bar);
+        self.check_alerts();
+        self.dispatch_signals();
+    }
+
+    /// Like [`HQuant::push_bar`], but when [`HQuant::set_strict_ordering`]
+    /// is enabled, refuses a bar that would go backwards in time instead of
+    /// silently corrupting every indicator's incremental state with an
+    /// out-of-order sample. A bar exactly at the last pushed timestamp is
+    /// treated as a revision of it and routed to [`HQuant::update_last`]
+    /// rather than rejected. With `strict_ordering` off (the default), this
+    /// is equivalent to `push_bar`.
+    pub fn try_push_bar(&mut self, bar: Bar) -> Result<()> {
+        if self.strict_ordering {
+            let last = self.bars.len().checked_sub(1).and_then(|i| self.bars.get(i));
+            if let Some(last) = last {
+                if bar.ts < last.ts {
+                    return Err(crate::HQuantError::OutOfOrderBar {
+                        ts: bar.ts,
+                        last_ts: last.ts,
+                    });
+                }
+                if bar.ts == last.ts {
+                    self.update_last(bar);
+                    return Ok(());
+                }
+            }
+        }
+        self.push_bar(bar);
+        Ok(())
+    }
+
+    /// Append-or-revise: a bar at the same timestamp as the last pushed one
+    /// is treated as that same candle ticking forward and routed to
+    /// [`HQuant::update_last`]; any other timestamp (including one going
+    /// backwards) is appended via [`HQuant::push_bar`]. Unlike
+    /// [`HQuant::try_push_bar`], this never rejects a bar -- it's for
+    /// callers that only care about "same candle or a new one", not about
+    /// enforcing monotonic time, and it works the same regardless of
+    /// [`HQuant::set_strict_ordering`].
+    pub fn upsert_bar(&mut self, bar: Bar) {
+        let last = self.bars.len().checked_sub(1).and_then(|i| self.bars.get(i));
+        match last {
+            Some(last) if last.ts == bar.ts => self.update_last(bar),
+            _ => self.push_bar(bar),
+        }
+    }
+
+    /// Register `cb` to run synchronously at the end of every `push_bar`/
+    /// `update_last` call that produces at least one signal from a
+    /// registered top-level strategy, replacing any previously registered
+    /// callback. Unlike `poll_alerts`, nothing is queued for later
+    /// draining -- `cb` sees each batch of signals exactly once, on the
+    /// call stack of the `push_bar`/`update_last` that produced it. See
+    /// [`HQuant::clear_signal_callback`] to remove it, and
+    /// [`crate::ffi::c::hquant_set_signal_callback`] for the raw C entry
+    /// point built on top of this.
+    pub fn set_signal_callback(&mut self, cb: impl FnMut(&[Signal]) + Send + 'static) {
+        self.signal_callback = Some(Box::new(cb));
+    }
+
+    /// Deregister the callback set by [`HQuant::set_signal_callback`], if
+    /// any.
+    pub fn clear_signal_callback(&mut self) {
+        self.signal_callback = None;
+    }
+
+    /// Every registered top-level strategy's signal for the bar just
+    /// pushed/revised, ordered by `(priority, id)` ascending -- lowest
+    /// priority number first, then ascending strategy id to break a tie --
+    /// for determinism (`self.strategies` is a `HashMap`) and so a caller
+    /// can register e.g. a risk strategy at a lower priority than the entry
+    /// strategies it needs to run ahead of. Skips any strategy that:
+    /// - didn't match a rule,
+    /// - is [`CompiledStrategy::edge_triggered`] and also matched the
+    ///   previous bar (not a rising edge, tracked in
+    ///   `self.last_match_state`), or
+    /// - has a nonzero [`CompiledStrategy::cooldown_bars`] and last emitted
+    ///   within that many bars (tracked in `self.last_signal_bar`).
+    ///
+    /// `&mut self` so a strategy can record the match/emission state above
+    /// as it goes.
+    fn evaluate_signals(&mut self) -> Vec<Signal> {
+        let history = self.field_history();
+        let bar_index = self.bars.len();
+        let mut ids: Vec<u32> = self.strategies.keys().copied().collect();
+        ids.sort_by_key(|id| (self.strategy_priorities.get(id).copied().unwrap_or(0), *id));
+        let mut signals = Vec::new();
+        for id in ids {
+            let strategy = &self.strategies[&id];
+            let Some(signal) = strategy.evaluate(&self.graph, &history) else {
+                self.last_match_state.insert(id, false);
+                continue;
+            };
+            let previously_matched = self.last_match_state.insert(id, true).unwrap_or(false);
+            if strategy.edge_triggered && previously_matched {
+                continue;
+            }
+            if strategy.cooldown_bars > 0 {
+                if let Some(&last) = self.last_signal_bar.get(&id) {
+                    if bar_index.saturating_sub(last) < strategy.cooldown_bars {
+                        continue;
+                    }
+                }
+            }
+            self.last_signal_bar.insert(id, bar_index);
+            signals.push(signal);
+        }
+        signals
+    }
+
+    /// Run `signal_callback`, if one is registered, against the signals
+    /// produced by the bar just pushed/revised -- a no-op if there's no
+    /// callback, no strategy produced a signal, or a callback re-entering
+    /// `push_bar`/`update_last` is already running.
+    fn dispatch_signals(&mut self) {
+        if self.dispatching_signals || self.signal_callback.is_none() {
+            return;
+        }
+        let signals = self.evaluate_signals();
+        if signals.is_empty() {
+            return;
+        }
+        self.dispatching_signals = true;
+        if let Some(cb) = self.signal_callback.as_mut() {
+            cb(&signals);
+        }
+        self.dispatching_signals = false;
+    }
+
+    /// Register a lightweight alert that fires once, the bar `indicator_id`
+    /// crosses `level` in `direction` -- cheaper than compiling a
+    /// [`CompiledStrategy`] just to watch one threshold. See
+    /// [`crate::alert`] for why there's no separate "component" selector.
+    pub fn add_alert(&mut self, indicator_id: NodeId, level: f64, direction: CrossDirection) -> AlertId {
+        let id = AlertId(self.next_alert_id);
+        self.next_alert_id += 1;
+        self.alerts.insert(id, Alert::new(indicator_id, level, direction));
+        id
+    }
+
+    /// Deregister an alert. Returns `false` if no alert was registered
+    /// under `id`.
+    pub fn remove_alert(&mut self, id: AlertId) -> bool {
+        self.alerts.remove(&id).is_some()
+    }
+
+    /// Drain and return the ids of every alert that has fired since the
+    /// last call to `poll_alerts`.
+    pub fn poll_alerts(&mut self) -> Vec<AlertId> {
+        std::mem::take(&mut self.pending_alerts)
+    }
+
+    /// Check every registered alert against the indicator value as of the
+    /// bar just pushed, queuing the ones that just crossed their level.
+    fn check_alerts(&mut self) {
+        let graph = &self.graph;
+        for (&id, alert) in self.alerts.iter_mut() {
+            let Some(value) = graph.get_from_end(alert.indicator_id, 0) else {
+                continue;
+            };
+            if alert.check(value) {
+                self.pending_alerts.push(id);
+            }
+        }
+    }
+
+    /// Attach `strategy_ids` as a new group of independently-tracked
+    /// backtests, all sharing `config` and fed from every bar pushed from
+    /// this point on. Returns a handle for retrieving their results later
+    /// via [`HQuant::backtest_portfolio`].
+    pub fn attach_backtest(&mut self, config: BacktestConfig, strategy_ids: &[u32]) -> BacktestHandle {
+        let engines = strategy_ids.iter().map(|&id| (id, BacktestEngine::new(id, config.clone()))).collect();
+        self.backtests.push(AttachedBacktest { engines });
+        BacktestHandle(self.backtests.len() - 1)
+    }
+
+    /// The recorded trades for one strategy within an attached backtest
+    /// group, or `None` if the handle or strategy id is unknown.
+    pub fn backtest_portfolio(&self, handle: BacktestHandle, strategy_id: u32) -> Option<&PortfolioBacktest> {
+        self.backtests.get(handle.0)?.engines.get(&strategy_id).map(|e| &e.portfolio)
+    }
+
+    /// Trailing per-field history covering just the current and previous
+    /// bar, the most any DSL rule needs for cross detection (see
+    /// [`FieldHistory`]).
+    fn field_history(&self) -> FieldHistory {
+        Self::field_history_from_buffer(&self.bars)
+    }
+
+    /// Like `field_history`, but against an arbitrary [`KlineBuffer`]
+    /// rather than this `HQuant`'s own -- shared with
+    /// [`HQuant::dry_evaluate_strategy`], which replays bars through a
+    /// scratch buffer instead of this one.
+    fn field_history_from_buffer(bars: &KlineBuffer) -> FieldHistory {
+        let take_last_two = |field: Field| {
+            let series = bars.field_column(field).to_vec();
+            series[series.len().saturating_sub(2)..].to_vec()
+        };
+        FieldHistory {
+            open: take_last_two(Field::Open),
+            high: take_last_two(Field::High),
+            low: take_last_two(Field::Low),
+            close: take_last_two(Field::Close),
+            volume: take_last_two(Field::Volume),
+        }
+    }
+
+    /// Compile `dsl` against a scratch graph seeded with this `HQuant`'s
+    /// currently registered indicator specs, replay `bars` through it from
+    /// scratch, and return every signal the strategy would emit -- without
+    /// registering the strategy or touching this `HQuant`'s own graph or
+    /// bar history. Ideal for a strategy editor's "preview" pane: the
+    /// exact signals a real `add_strategy` + `push_bar` run over `bars`
+    /// would produce, without committing to either.
+    pub fn dry_evaluate_strategy(&self, dsl: &str, bars: &[Bar]) -> Result<Vec<Signal>> {
+        let capacity = bars.len().max(1);
+        let mut graph = IndicatorGraph::with_history(capacity);
+        for name in self.indicators.keys() {
+            graph.add_from_spec(name)?;
+        }
+        let strategy = dsl_parser::compile(dsl, &graph)?;
+
+        let mut buffer = KlineBuffer::new(capacity);
+        let mut signals = Vec::new();
+        for &bar in bars {
+            buffer.push(&bar);
+            graph.push_bar(&bar);
+            let history = Self::field_history_from_buffer(&buffer);
+            if let Some(signal) = strategy.evaluate(&graph, &history) {
+                signals.push(signal);
+            }
+        }
+        Ok(signals)
+    }
+
+    /// Snapshot enough state to resume an equivalent `HQuant` elsewhere:
+    /// the bar history, every registered indicator's spec and internal
+    /// accumulators, and pending alert ids. Strategies and alert
+    /// definitions aren't included -- like `add_indicator`, they're
+    /// wiring the caller already owns and can re-add via `add_strategy`/
+    /// `add_alert` after [`HQuant::load_state`], the same as it would
+    /// building a fresh `HQuant` from scratch.
+    ///
+    /// This crate has no `serde` dependency, so (like [`crate::kline::KlineFrame`])
+    /// this uses a hand-rolled little-endian binary format rather than
+    /// `#[derive(Serialize, Deserialize)]`.
+    pub fn save_state(&self) -> Vec<u8> {
+        let bar_frame = crate::kline::KlineFrame::new(self.bars.to_bars()).to_binary();
+        let node_snapshots = self.graph.snapshot_nodes();
+        let top_level_names: Vec<&str> = node_snapshots
+            .iter()
+            .map(|(name, _, _)| name.as_str())
+            .filter(|name| self.indicators.contains_key(*name))
+            .collect();
+
+        let mut buf = Vec::new();
+        buf.push(STATE_VERSION);
+        write_bytes(&mut buf, &bar_frame);
+
+        write_u32(&mut buf, top_level_names.len() as u32);
+        for name in &top_level_names {
+            write_string(&mut buf, name);
+        }
+
+        write_u32(&mut buf, node_snapshots.len() as u32);
+        for (name, exec_state, output) in &node_snapshots {
+            write_string(&mut buf, name);
+            write_bytes(&mut buf, exec_state);
+            write_u32(&mut buf, output.len() as u32);
+            for &v in output {
+                buf.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+
+        write_u32(&mut buf, self.pending_alerts.len() as u32);
+        for alert_id in &self.pending_alerts {
+            buf.extend_from_slice(&alert_id.0.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Restore state written by [`HQuant::save_state`], replacing this
+    /// `HQuant`'s bar history and indicator graph in place. Rebuilds the
+    /// graph by replaying each indicator's spec through `add_from_spec`
+    /// (so it comes back with the same node shape) and then restores each
+    /// node's accumulators from the saved blob, rather than replaying the
+    /// bar history back through the graph -- the same "rebuild from
+    /// recorded spec names" approach [`HQuant::dry_evaluate_strategy`]
+    /// uses for its scratch graph.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<()> {
+        let mut cursor = Cursor { data, pos: 0 };
+        let version = cursor.read_u8()?;
+        if version != STATE_VERSION {
+            return Err(crate::HQuantError::InvalidSpec(format!("unsupported HQuant state version {version}")));
+        }
+        let bar_frame_bytes = cursor.read_bytes()?;
+        let bars = crate::kline::KlineFrame::from_binary(bar_frame_bytes)?.bars;
+
+        let top_level_count = cursor.read_u32()? as usize;
+        let mut top_level_names = Vec::with_capacity(top_level_count);
+        for _ in 0..top_level_count {
+            top_level_names.push(cursor.read_string()?);
+        }
+
+        let node_snapshot_count = cursor.read_u32()? as usize;
+        let mut node_snapshots = Vec::with_capacity(node_snapshot_count);
+        for _ in 0..node_snapshot_count {
+            let name = cursor.read_string()?;
+            let exec_state = cursor.read_bytes()?.to_vec();
+            let output_len = cursor.read_u32()? as usize;
+            let mut output = Vec::with_capacity(output_len);
+            for _ in 0..output_len {
+                output.push(cursor.read_f64()?);
+            }
+            node_snapshots.push((name, exec_state, output));
+        }
+
+        let pending_count = cursor.read_u32()? as usize;
+        let mut pending_alerts = Vec::with_capacity(pending_count);
+        for _ in 0..pending_count {
+            pending_alerts.push(AlertId(cursor.read_u64()?));
+        }
+
+        let capacity = bars.len().max(1);
+        let mut graph = IndicatorGraph::with_history(capacity);
+        let mut indicators = HashMap::new();
+        for name in &top_level_names {
+            let id = graph.add_from_spec(name)?;
+            indicators.insert(name.clone(), id);
+        }
+        for (name, exec_state, output) in &node_snapshots {
+            graph.restore_node(name, exec_state, output)?;
+        }
+
+        let mut buffer = KlineBuffer::new(capacity);
+        for bar in &bars {
+            buffer.push(bar);
+        }
+
+        self.graph = graph;
+        self.bars = buffer;
+        self.indicators = indicators;
+        self.pending_alerts = pending_alerts;
+        Ok(())
+    }
+
+    /// Feed every attached backtest group the signal each of its strategies
+    /// produces for `bar`. A strategy with no registered [`CompiledStrategy`]
+    /// (or one that emits no signal) is treated as holding.
+    fn drive_backtests(&mut self, bar: &Bar) {
+        if self.backtests.is_empty() {
+            return;
+        }
+        let history = self.field_history();
+        let graph = &self.graph;
+        let strategies = &self.strategies;
+        for attached in &mut self.backtests {
+            for (strategy_id, engine) in attached.engines.iter_mut() {
+                let signal = strategies
+                    .get(strategy_id)
+                    .and_then(|s| s.evaluate(graph, &history))
+                    .unwrap_or(crate::strategy::Signal { action: crate::strategy::Action::Hold, bracket: None });
+                engine.process_signal(&signal, bar);
+            }
+        }
+    }
+
+    pub fn update_last(&mut self, bar: Bar) {
+        self.bars.update_last(&bar);
+        self.graph.update_last(&bar);
+        self.dispatch_signals();
+    }
+
+    pub fn indicator_last(&self, id: NodeId) -> Option<f64> {
+        self.graph.get_from_end(id, 0)
+    }
+
+    /// The indicator's most recent non-`NaN` value, scanning back through
+    /// history for consumers (e.g. a chart) that want display continuity
+    /// through warm-up or a bad bar rather than a flickering `NaN`.
+    pub fn indicator_last_valid(&self, id: NodeId) -> Option<f64> {
+        self.graph.get_last_valid(id)
+    }
+
+    pub fn indicator_id(&self, name: &str) -> Option<NodeId> {
+        self.indicators.get(name).copied()
+    }
+
+    /// Whether `id` has seen enough bars for [`HQuant::indicator_last`] to
+    /// mean anything, rather than a caller checking the value for `NaN`
+    /// itself -- see [`IndicatorGraph::is_ready`].
+    pub fn indicator_ready(&self, id: NodeId) -> bool {
+        self.graph.is_ready(id)
+    }
+
+    /// Zero-copy `(raw_slice, capacity, len, head)` view over an
+    /// indicator's full output history.
+    pub fn indicator_array(&self, id: NodeId) -> Option<(&[f64], usize, usize, usize)> {
+        self.graph.raw_view(id)
+    }
+
+    /// An indicator's full output history, oldest first. Prefer
+    /// [`HQuant::indicator_array`] for zero-copy access; this is a
+    /// convenience for callers (e.g. charting) that just want a `Vec`.
+    pub fn indicator_series(&self, id: NodeId) -> Option<Vec<f64>> {
+        self.graph.series(id)
+    }
+
+    /// Zero-copy view over the close price history, in the same
+    /// `(raw_slice, capacity, len, head)` convention as
+    /// [`HQuant::indicator_array`].
+    pub fn close_column(&self) -> (&[f64], usize, usize, usize) {
+        self.bars.close_column().raw_view()
+    }
+
+    pub fn bars(&self) -> &KlineBuffer {
+        &self.bars
+    }
+
+    /// The underlying indicator graph, e.g. for compiling a strategy against
+    /// it via [`crate::strategy::dsl_parser::compile`].
+    pub fn graph(&self) -> &IndicatorGraph {
+        &self.graph
+    }
+}
+
+const STATE_VERSION: u8 = 1;
+
+fn write_u32(buf: &mut Vec<u8>, x: u32) {
+    buf.extend_from_slice(&x.to_le_bytes());
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    write_u32(buf, bytes.len() as u32);
+    buf.extend_from_slice(bytes);
+}
+
+fn write_string(buf: &mut Vec<u8>, s: &str) {
+    write_bytes(buf, s.as_bytes());
+}
+
+/// Minimal little-endian byte reader with bounds checking, local to
+/// [`HQuant::save_state`]'s binary format -- mirrors
+/// [`crate::kline::KlineFrame`]'s own local cursor rather than sharing
+/// one, since the two formats have nothing else in common.
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let slice = self
+            .data
+            .get(self.pos..self.pos + n)
+            .ok_or_else(|| crate::HQuantError::InvalidSpec("truncated HQuant state blob".to_string()))?;
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn read_f64(&mut self) -> Result<f64> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn read_bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.read_u32()? as usize;
+        self.take(len)
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        let bytes = self.read_bytes()?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| crate::HQuantError::InvalidSpec("HQuant state blob has a non-UTF-8 indicator name".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{dsl_parser, Action, Rule};
+
+    #[test]
+    fn load_state_resumes_indicator_output_identically_after_a_checkpoint() {
+        let bar = |close: f64| Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 };
+
+        let mut original = HQuant::new(32);
+        let sma_id = original.add_indicator("SMA_5").unwrap();
+        for close in [10.0, 11.0, 9.0, 12.0, 14.0, 13.0] {
+            original.push_bar(bar(close));
+        }
+
+        let snapshot = original.save_state();
+        let mut restored = HQuant::new(1);
+        restored.load_state(&snapshot).unwrap();
+        let restored_sma_id = restored.indicator_id("SMA_5").unwrap();
+
+        // The restored engine already agrees on the value up to the
+        // checkpoint...
+        assert_eq!(
+            original.graph().get_from_end(sma_id, 0),
+            restored.graph().get_from_end(restored_sma_id, 0)
+        );
+
+        // ...and continuing to push bars into each stays in lockstep,
+        // proving `SMA_5`'s rolling window (not just its last output) was
+        // restored, not merely its most recent value.
+        for close in [16.0, 8.0, 20.0] {
+            original.push_bar(bar(close));
+            restored.push_bar(bar(close));
+            assert_eq!(
+                original.graph().get_from_end(sma_id, 0),
+                restored.graph().get_from_end(restored_sma_id, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn reset_clears_history_but_reusing_the_engine_matches_a_fresh_one() {
+        let bar = |close: f64| Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 };
+
+        let mut reused = HQuant::new(32);
+        let ema_id = reused.add_indicator("EMA_5").unwrap();
+        for close in [10.0, 11.0, 9.0, 12.0, 14.0] {
+            reused.push_bar(bar(close));
+        }
+        assert_eq!(reused.bars().len(), 5);
+
+        reused.reset();
+        assert_eq!(reused.bars().len(), 0);
+        assert_eq!(reused.indicator_id("EMA_5"), Some(ema_id));
+        assert_eq!(reused.indicator_last(ema_id), None);
+
+        let mut fresh = HQuant::new(32);
+        let fresh_ema_id = fresh.add_indicator("EMA_5").unwrap();
+
+        for close in [7.0, 8.0, 20.0, 5.0, 9.0] {
+            reused.push_bar(bar(close));
+            fresh.push_bar(bar(close));
+            assert_eq!(reused.indicator_last(ema_id), fresh.indicator_last(fresh_ema_id));
+        }
+    }
+
+    #[test]
+    fn remove_indicator_forgets_its_name_and_allows_readding_it() {
+        let mut hq = HQuant::new(16);
+        let id = hq.add_indicator("SMA_5").unwrap();
+        assert!(hq.remove_indicator(id));
+        assert!(hq.indicator_id("SMA_5").is_none());
+        // The name is free again.
+        let new_id = hq.add_indicator("SMA_5").unwrap();
+        assert_eq!(hq.indicator_id("SMA_5"), Some(new_id));
+    }
+
+    #[test]
+    fn remove_strategy_reports_whether_one_was_registered() {
+        let mut hq = HQuant::new(16);
+        assert!(!hq.remove_strategy(1));
+        hq.add_strategy(1, CompiledStrategy { rules: Vec::<Rule>::new(), ..Default::default() }, 0);
+        assert!(hq.strategy(1).is_some());
+        assert!(hq.remove_strategy(1));
+        assert!(hq.strategy(1).is_none());
+    }
+
+    #[test]
+    fn replace_indicator_backfills_the_new_spec_over_retained_history() {
+        let mut hq = HQuant::new(64);
+        let id = hq.add_indicator("SMA_20").unwrap();
+        for i in 1..=30 {
+            hq.push_bar(Bar { ts: 0, open: i as f64, high: i as f64, low: i as f64, close: i as f64, volume: 1.0 });
+        }
+
+        let new_id = hq.replace_indicator(id, "SMA_50").unwrap();
+
+        assert!(hq.indicator_id("SMA_20").is_none());
+        assert_eq!(hq.indicator_id("SMA_50"), Some(new_id));
+        let expected: f64 = (1..=30).sum::<i32>() as f64 / 30.0;
+        assert!((hq.indicator_last(new_id).unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn indicator_array_matches_indicator_last() {
+        let mut hq = HQuant::new(16);
+        let id = hq.add_indicator("EMA_3").unwrap();
+        for close in [1.0, 2.0, 3.0, 4.0, 5.0] {
+            hq.push_bar(Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 });
+        }
+        let (raw, capacity, len, head) = hq.indicator_array(id).unwrap();
+        assert_eq!(capacity, 16);
+        assert_eq!(len, 5);
+        // The most recently written slot is one before `head`.
+        let last = raw[(head + capacity - 1) % capacity];
+        assert_eq!(Some(last), hq.indicator_last(id));
+    }
+
+    #[test]
+    fn two_backtests_with_different_configs_diverge_over_the_same_bars() {
+        let mut hq = HQuant::new(64);
+        let strategy = dsl_parser::compile("CLOSE > 0 => BUY", hq.graph()).unwrap();
+        hq.add_strategy(1, strategy, 0);
+
+        let tight = hq.attach_backtest(BacktestConfig { stop_loss_pct: Some(1.0), take_profit_pct: None, ..Default::default() }, &[1]);
+        let loose = hq.attach_backtest(BacktestConfig { stop_loss_pct: Some(50.0), take_profit_pct: None, ..Default::default() }, &[1]);
+
+        // A dip just past 1% (but nowhere near 50%) should stop the tight
+        // backtest out while the loose one rides it through.
+        for close in [100.0, 98.5, 99.0, 100.0, 101.0] {
+            hq.push_bar(Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 });
+        }
+
+        let tight_trades = hq.backtest_portfolio(tight, 1).unwrap().trades.len();
+        let loose_trades = hq.backtest_portfolio(loose, 1).unwrap().trades.len();
+        assert!(tight_trades > loose_trades, "tight={tight_trades} loose={loose_trades}");
+    }
+
+    #[test]
+    fn a_cooldown_strategy_only_emits_once_per_cooldown_window_despite_a_persistently_true_condition() {
+        use std::sync::{Arc, Mutex};
+
+        let mut hq = HQuant::new(64);
+        let strategy = dsl_parser::compile("COOLDOWN 3\nCLOSE > 0 => BUY", hq.graph()).unwrap();
+        hq.add_strategy(1, strategy, 0);
+
+        let emissions = Arc::new(Mutex::new(0usize));
+        let emissions_cb = Arc::clone(&emissions);
+        hq.set_signal_callback(move |signals| {
+            assert_eq!(signals.len(), 1);
+            *emissions_cb.lock().unwrap() += 1;
+        });
+
+        // `CLOSE > 0` matches every one of these 7 bars, but a 3-bar
+        // cooldown should only let it through on bars 1, 4, and 7.
+        for close in [100.0, 101.0, 102.0, 103.0, 104.0, 105.0, 106.0] {
+            hq.push_bar(Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 });
+        }
+
+        assert_eq!(*emissions.lock().unwrap(), 3);
+    }
+
+    #[test]
+    fn removing_a_strategy_forgets_its_cooldown_so_a_replacement_under_the_same_id_can_fire_immediately() {
+        let mut hq = HQuant::new(64);
+        let strategy = dsl_parser::compile("COOLDOWN 5\nCLOSE > 0 => BUY", hq.graph()).unwrap();
+        hq.add_strategy(1, strategy, 0);
+        hq.push_bar(Bar { ts: 0, open: 100.0, high: 100.0, low: 100.0, close: 100.0, volume: 1.0 });
+
+        assert!(hq.remove_strategy(1));
+        let replacement = dsl_parser::compile("CLOSE > 0 => BUY", hq.graph()).unwrap();
+        hq.add_strategy(1, replacement, 0);
+
+        let signals = hq.evaluate_signals();
+        assert_eq!(signals.len(), 1);
+    }
+
+    #[test]
+    fn an_edge_triggered_strategy_emits_once_on_the_rising_edge_of_a_condition_held_for_five_bars() {
+        let mut hq = HQuant::new(64);
+        let strategy = dsl_parser::compile("EDGE_TRIGGERED\nCLOSE > 100 => BUY", hq.graph()).unwrap();
+        hq.add_strategy(1, strategy, 0);
+
+        let mut emissions = 0;
+        // Below 100 (no match), then true for 5 straight bars, then false
+        // again -- a level-triggered strategy would emit 5 times here.
+        for close in [90.0, 101.0, 102.0, 103.0, 104.0, 105.0, 95.0] {
+            hq.push_bar(Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 });
+            emissions += hq.evaluate_signals().len();
+        }
+
+        assert_eq!(emissions, 1);
+    }
+
+    #[test]
+    fn try_push_bar_rejects_a_backwards_timestamp_once_strict_ordering_is_on() {
+        let mut hq = HQuant::new(16);
+        hq.set_strict_ordering(true);
+        hq.try_push_bar(Bar { ts: 100, open: 1.0, high: 1.0, low: 1.0, close: 1.0, volume: 1.0 })
+            .unwrap();
+
+        let err = hq
+            .try_push_bar(Bar { ts: 50, open: 2.0, high: 2.0, low: 2.0, close: 2.0, volume: 1.0 })
+            .unwrap_err();
+        assert_eq!(err, crate::HQuantError::OutOfOrderBar { ts: 50, last_ts: 100 });
+        assert_eq!(hq.bars().len(), 1);
+    }
+
+    #[test]
+    fn try_push_bar_routes_a_duplicate_timestamp_to_update_last_instead_of_erroring() {
+        let mut hq = HQuant::new(16);
+        hq.set_strict_ordering(true);
+        hq.try_push_bar(Bar { ts: 100, open: 1.0, high: 1.0, low: 1.0, close: 1.0, volume: 1.0 })
+            .unwrap();
+        hq.try_push_bar(Bar { ts: 100, open: 1.0, high: 1.0, low: 1.0, close: 5.0, volume: 1.0 })
+            .unwrap();
+
+        assert_eq!(hq.bars().len(), 1);
+        assert_eq!(hq.bars().get(0).unwrap().close, 5.0);
+    }
+
+    #[test]
+    fn try_push_bar_accepts_a_backwards_timestamp_when_strict_ordering_is_off() {
+        let mut hq = HQuant::new(16);
+        hq.try_push_bar(Bar { ts: 100, open: 1.0, high: 1.0, low: 1.0, close: 1.0, volume: 1.0 })
+            .unwrap();
+        hq.try_push_bar(Bar { ts: 50, open: 2.0, high: 2.0, low: 2.0, close: 2.0, volume: 1.0 })
+            .unwrap();
+
+        assert_eq!(hq.bars().len(), 2);
+    }
+
+    #[test]
+    fn upsert_bar_appends_into_an_empty_buffer() {
+        let mut hq = HQuant::new(16);
+        hq.upsert_bar(Bar { ts: 100, open: 1.0, high: 1.0, low: 1.0, close: 1.0, volume: 1.0 });
+        assert_eq!(hq.bars().len(), 1);
+        assert_eq!(hq.bars().get(0).unwrap().close, 1.0);
+    }
+
+    #[test]
+    fn upsert_bar_revises_in_place_at_the_same_timestamp() {
+        let mut hq = HQuant::new(16);
+        hq.upsert_bar(Bar { ts: 100, open: 1.0, high: 1.0, low: 1.0, close: 1.0, volume: 1.0 });
+        hq.upsert_bar(Bar { ts: 100, open: 1.0, high: 2.0, low: 1.0, close: 1.5, volume: 3.0 });
+
+        assert_eq!(hq.bars().len(), 1);
+        assert_eq!(hq.bars().get(0).unwrap().close, 1.5);
+    }
+
+    #[test]
+    fn upsert_bar_appends_once_the_timestamp_advances() {
+        let mut hq = HQuant::new(16);
+        hq.upsert_bar(Bar { ts: 100, open: 1.0, high: 1.0, low: 1.0, close: 1.0, volume: 1.0 });
+        hq.upsert_bar(Bar { ts: 200, open: 2.0, high: 2.0, low: 2.0, close: 2.0, volume: 1.0 });
+
+        assert_eq!(hq.bars().len(), 2);
+        assert_eq!(hq.bars().get(1).unwrap().close, 2.0);
+    }
+
+    #[test]
+    fn indicator_last_valid_skips_a_nan_forming_bar_but_indicator_last_does_not() {
+        let mut hq = HQuant::new(16);
+        let id = hq.add_indicator("EMA_3").unwrap();
+        for close in [1.0, 2.0, 3.0] {
+            hq.push_bar(Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 });
+        }
+        let prior_valid = hq.indicator_last(id).unwrap();
+        assert!(prior_valid.is_finite());
+
+        // A bad bar (e.g. a corrupt feed tick) with a NaN close.
+        hq.push_bar(Bar { ts: 1, open: f64::NAN, high: f64::NAN, low: f64::NAN, close: f64::NAN, volume: 1.0 });
+
+        assert!(hq.indicator_last(id).unwrap().is_nan());
+        assert_eq!(hq.indicator_last_valid(id), Some(prior_valid));
+    }
+
+    #[test]
+    fn two_strategies_referencing_the_same_indicator_name_report_the_same_resolved_id() {
+        // Uses `EMA_14` as the shared indicator both strategies reference --
+        // the mechanism under test (`dsl_parser::compile` resolving a name
+        // against the same graph both times) doesn't depend on which
+        // indicator it is.
+        let mut hq = HQuant::new(64);
+        let ema_id = hq.add_indicator("EMA_14").unwrap();
+
+        let strategy_a = dsl_parser::compile("EMA_14 < 30 => BUY", hq.graph()).unwrap();
+        let strategy_b = dsl_parser::compile("EMA_14 > 70 => SELL", hq.graph()).unwrap();
+        let ids_a = hq.add_strategy(1, strategy_a, 0);
+        let ids_b = hq.add_strategy(2, strategy_b, 0);
+
+        assert_eq!(ids_a, ids_b);
+        assert_eq!(ids_a, [ema_id].into_iter().collect());
+    }
+
+    #[test]
+    fn signals_are_ordered_by_priority_then_id_regardless_of_registration_order() {
+        let mut hq = HQuant::new(16);
+        let risk = dsl_parser::compile("CLOSE > 0 => CLOSE", hq.graph()).unwrap();
+        let entry = dsl_parser::compile("CLOSE > 0 => BUY", hq.graph()).unwrap();
+
+        // Registered entry-then-risk, the opposite of the desired firing
+        // order -- only the priority (not `HashMap` iteration or id order)
+        // should determine which signal comes first.
+        hq.add_strategy(2, entry, 10);
+        hq.add_strategy(1, risk, 0);
+
+        hq.push_bar(Bar { ts: 0, open: 100.0, high: 100.0, low: 100.0, close: 100.0, volume: 1.0 });
+        let signals = hq.evaluate_signals();
+
+        assert_eq!(signals.len(), 2);
+        assert_eq!(signals[0].action, Action::Close);
+        assert_eq!(signals[1].action, Action::Buy);
+    }
+
+    #[test]
+    fn an_alert_fires_exactly_once_at_the_crossing_bar() {
+        use crate::alert::CrossDirection;
+
+        let mut hq = HQuant::new(64);
+        let id = hq.add_indicator("EMA_3").unwrap();
+        hq.add_alert(id, 30.0, CrossDirection::Below);
+
+        let mut fire_counts = Vec::new();
+        for close in [50.0, 45.0, 40.0, 20.0, 15.0, 10.0] {
+            hq.push_bar(Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 });
+            fire_counts.push(hq.poll_alerts().len());
+        }
+
+        // EMA(3) drifts down through 30 exactly once as the closes fall,
+        // then keeps falling further below it -- one fire, on that bar,
+        // not on every subsequent bar it stays under the level.
+        assert_eq!(fire_counts.iter().sum::<usize>(), 1);
+        let fired_at = fire_counts.iter().position(|&n| n == 1).unwrap();
+        assert!(fired_at > 0 && fired_at < fire_counts.len() - 1, "fired_at={fired_at}");
+    }
+
+    #[test]
+    fn dry_evaluate_matches_a_full_engine_run_over_the_same_bars() {
+        // Uses an EMA-oversold-style strategy -- the mechanism under test
+        // (dry-eval reproducing a live engine run bar-for-bar) doesn't
+        // depend on which indicator backs the threshold.
+        let dsl = "EMA_5 < 95 => BUY\nEMA_5 > 105 => SELL";
+        let dip: Vec<Bar> = [100.0, 98.0, 94.0, 90.0, 92.0, 96.0, 102.0, 108.0, 104.0]
+            .into_iter()
+            .map(|close| Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 })
+            .collect();
+
+        let mut live = HQuant::new(dip.len());
+        live.add_indicator("EMA_5").unwrap();
+        let live_strategy = dsl_parser::compile(dsl, live.graph()).unwrap();
+        live.add_strategy(1, live_strategy.clone(), 0);
+        let mut live_signals = Vec::new();
+        for &bar in &dip {
+            live.push_bar(bar);
+            let history = live.field_history();
+            if let Some(signal) = live_strategy.evaluate(live.graph(), &history) {
+                live_signals.push(signal);
+            }
+        }
+
+        let mut dry = HQuant::new(16);
+        dry.add_indicator("EMA_5").unwrap();
+        let dry_signals = dry.dry_evaluate_strategy(dsl, &dip).unwrap();
+
+        assert!(!live_signals.is_empty());
+        assert_eq!(live_signals, dry_signals);
+    }
+
+    #[test]
+    fn indicator_series_is_chronological_and_ends_on_the_last_value() {
+        let mut hq = HQuant::new(16);
+        let id = hq.add_indicator("SMA_2").unwrap();
+        for close in [1.0, 2.0, 3.0, 4.0] {
+            hq.push_bar(Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 });
+        }
+        let series = hq.indicator_series(id).unwrap();
+        assert_eq!(series.len(), 4);
+        assert_eq!(series.last().copied(), hq.indicator_last(id));
+        assert!((series[3] - 3.5).abs() < 1e-9); // SMA(2) of [3.0, 4.0]
+    }
+}
@@ -0,0 +1,28 @@
+//! Shared error type for the engine.
+
+use thiserror::Error;
+
+/// Errors surfaced by indicator compilation, DSL parsing and graph evaluation.
+#[derive(Debug, Error, PartialEq)]
+pub enum HQuantError {
+    #[error("parse error at line {line}, column {column}: {message}")]
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("unknown indicator: {0}")]
+    UnknownIndicator(String),
+    #[error("unknown field: {0}")]
+    UnknownField(String),
+    #[error("duplicate indicator id: {0}")]
+    DuplicateIndicator(String),
+    #[error("invalid indicator spec: {0}")]
+    InvalidSpec(String),
+    #[error("indicator still in use, another node depends on it: {0}")]
+    IndicatorInUse(String),
+    #[error("bar timestamp {ts} is out of order: last pushed bar was at {last_ts}")]
+    OutOfOrderBar { ts: i64, last_ts: i64 },
+}
+
+pub type Result<T> = std::result::Result<T, HQuantError>;
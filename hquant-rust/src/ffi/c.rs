@@ -0,0 +1,943 @@
+//! Raw C ABI over [`crate::engine::HQuant`] and [`crate::backtest::futures::FuturesBacktest`],
+//! for Go/C callers linking the `cdylib` build directly rather than
+//! through a language-specific binding like [`super::python`]. This is
+//! the crate's first C ABI -- there's no earlier `hquant.h`-style surface
+//! returning bare `0`/`-1` to version alongside, so there are no `_ex`
+//! variants here: every function below is the only version, already
+//! returning [`HqStatus`] rather than a plain integer.
+//!
+//! Every function writes its fallible result (if any) through an
+//! out-parameter and returns an [`HqStatus`], so a caller can't confuse a
+//! valid `0` or null result with failure. A Rust panic inside a call is
+//! caught at the boundary and reported as [`HqStatus::Panic`] rather than
+//! unwinding into C, which is undefined behavior.
+
+use crate::backtest::{BacktestParams, FuturesBacktest, FuturesTrade, PositionSnapshot, Side};
+use crate::engine::HQuant;
+use crate::kline::Bar;
+use crate::strategy::{dsl_parser, Action, Signal};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Status code every `hquant_*` function returns. Negative values are
+/// reserved for failure so a caller can test `status < 0` without
+/// enumerating every variant.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HqStatus {
+    Ok = 0,
+    NullPtr = -1,
+    InvalidArg = -2,
+    Panic = -3,
+    /// `hquant_add_strategy`'s DSL failed to compile.
+    ParseError = -4,
+}
+
+/// Run `f`, translating a panic into [`HqStatus::Panic`] instead of
+/// unwinding across the FFI boundary.
+fn guard(f: impl FnOnce() -> HqStatus) -> HqStatus {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(HqStatus::Panic)
+}
+
+/// Create a new `HQuant` with room for `capacity` bars of history
+/// (clamped to at least 1), returning an owning pointer the caller must
+/// eventually pass to [`hquant_free`].
+#[no_mangle]
+pub extern "C" fn hquant_new(capacity: usize) -> *mut HQuant {
+    Box::into_raw(Box::new(HQuant::new(capacity.max(1))))
+}
+
+/// Free an `HQuant` created by [`hquant_new`]. A null `ptr` is a no-op.
+///
+/// # Safety
+/// `ptr` must be a pointer previously returned by `hquant_new` that
+/// hasn't already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_free(ptr: *mut HQuant) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(ptr));
+    }
+}
+
+/// Register an indicator by spec string (e.g. `"EMA_12"`, `"BOLL(close,
+/// 20, 2.0)"`, `"CMO_14"`), writing its node id through `out_id` on
+/// [`HqStatus::Ok`]. This is already the crate's one generic entry point
+/// for every [`crate::indicator::IndicatorSpec`] variant -- there's no
+/// separate `hquant_add_rsi`/`hquant_add_ema` per-kind function to
+/// consolidate here, and a brand new indicator only ever needs a
+/// [`IndicatorSpec::parse`](crate::indicator::IndicatorSpec::parse) branch,
+/// not a new FFI entry point, to reach this surface.
+///
+/// # Safety
+/// `ptr` must be a live `hquant_new` pointer; `name` must be a valid
+/// NUL-terminated C string; `out_id` must be a valid, non-null,
+/// correctly aligned pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_add_indicator(ptr: *mut HQuant, name: *const c_char, out_id: *mut usize) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() || name.is_null() || out_id.is_null() {
+            return HqStatus::NullPtr;
+        }
+        let hq = &mut *ptr;
+        let name = match CStr::from_ptr(name).to_str() {
+            Ok(s) => s,
+            Err(_) => return HqStatus::InvalidArg,
+        };
+        match hq.add_indicator(name) {
+            Ok(id) => {
+                *out_id = id;
+                HqStatus::Ok
+            }
+            Err(_) => HqStatus::InvalidArg,
+        }
+    })
+}
+
+/// Whether `id` has seen enough bars for its output to mean anything,
+/// writing the result through `out_ready` on [`HqStatus::Ok`] -- see
+/// [`crate::engine::HQuant::indicator_ready`]. An unknown `id` writes
+/// `false` rather than failing, the same as every other id-keyed reader
+/// in [`crate::engine::HQuant`].
+///
+/// # Safety
+/// `ptr` must be a live `hquant_new` pointer; `out_ready` must be a
+/// valid, non-null, correctly aligned pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_indicator_ready(ptr: *mut HQuant, id: usize, out_ready: *mut bool) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() || out_ready.is_null() {
+            return HqStatus::NullPtr;
+        }
+        *out_ready = (*ptr).indicator_ready(id);
+        HqStatus::Ok
+    })
+}
+
+/// Push one fully closed bar.
+///
+/// # Safety
+/// `ptr` must be a live `hquant_new` pointer.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn hquant_push_bar(
+    ptr: *mut HQuant,
+    ts: i64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() {
+            return HqStatus::NullPtr;
+        }
+        (*ptr).push_bar(Bar { ts, open, high, low, close, volume });
+        HqStatus::Ok
+    })
+}
+
+/// Compile `dsl` against `ptr`'s current indicator graph and register it
+/// under `id` with `priority` (see [`HQuant::add_strategy`] for how ties
+/// between strategies firing on the same bar are broken), returning
+/// [`HqStatus::ParseError`] (rather than [`HqStatus::InvalidArg`]) if
+/// compilation fails, so a caller can tell a bad DSL program apart from a
+/// bad pointer.
+///
+/// # Safety
+/// `ptr` must be a live `hquant_new` pointer; `dsl` must be a valid
+/// NUL-terminated C string.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_add_strategy(
+    ptr: *mut HQuant,
+    id: u32,
+    dsl: *const c_char,
+    priority: i32,
+) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() || dsl.is_null() {
+            return HqStatus::NullPtr;
+        }
+        let hq = &mut *ptr;
+        let dsl = match CStr::from_ptr(dsl).to_str() {
+            Ok(s) => s,
+            Err(_) => return HqStatus::InvalidArg,
+        };
+        match dsl_parser::compile(dsl, hq.graph()) {
+            Ok(strategy) => {
+                hq.add_strategy(id, strategy, priority);
+                HqStatus::Ok
+            }
+            Err(_) => HqStatus::ParseError,
+        }
+    })
+}
+
+/// `repr(C)` mirror of [`crate::strategy::Action`], the flat form a
+/// signal callback actually receives.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HqAction {
+    Buy = 0,
+    Sell = 1,
+    Hold = 2,
+    Close = 3,
+    /// Mirrors [`Action::Guard`] for exhaustiveness only -- a `Guard` rule
+    /// never reaches a signal callback, since
+    /// [`crate::strategy::CompiledStrategy::evaluate_with`] stops at it and
+    /// emits nothing instead of returning a `Signal` that carries it.
+    Guard = 4,
+}
+
+impl From<Action> for HqAction {
+    fn from(action: Action) -> Self {
+        match action {
+            Action::Buy => HqAction::Buy,
+            Action::Sell => HqAction::Sell,
+            Action::Hold => HqAction::Hold,
+            Action::Close => HqAction::Close,
+            Action::Guard => HqAction::Guard,
+        }
+    }
+}
+
+/// `repr(C)` mirror of [`crate::strategy::Signal`] -- flattened, like
+/// [`HqAction`], rather than exposing `Signal`'s own (non-`repr(C)`)
+/// layout across the FFI boundary, the same way [`hquant_push_bar`]
+/// takes scalar fields instead of a `Bar` pointer. `has_bracket == 0`
+/// means `stop_pct`/`target_pct` are unset and should be ignored.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct HqSignal {
+    pub action: HqAction,
+    pub has_bracket: u8,
+    pub stop_pct: f64,
+    pub target_pct: f64,
+}
+
+impl From<Signal> for HqSignal {
+    fn from(signal: Signal) -> Self {
+        let (has_bracket, stop_pct, target_pct) = match signal.bracket {
+            Some(b) => (1, b.stop_pct, b.target_pct),
+            None => (0, 0.0, 0.0),
+        };
+        HqSignal { action: signal.action.into(), has_bracket, stop_pct, target_pct }
+    }
+}
+
+/// A `user_data`-carrying raw pointer, wrapped so the closure capturing it
+/// can be `Send` (required by [`crate::engine::HQuant::set_signal_callback`])
+/// -- the caller is the one asserting it's safe to hand back across
+/// threads by registering it at all.
+struct SendPtr(*mut c_void);
+unsafe impl Send for SendPtr {}
+
+impl SendPtr {
+    /// Named accessor rather than a plain `.0` field read, so the
+    /// callback closure below captures this whole (`Send`) wrapper
+    /// instead of Rust 2021's disjoint capture narrowing it back down to
+    /// the bare (non-`Send`) `*mut c_void` field.
+    fn get(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+/// Register `cb` to run synchronously, on whatever thread calls
+/// `hquant_push_bar`/`hquant_update_last`, at the end of every call that
+/// produces at least one signal from a registered strategy. `signals` is
+/// only valid for the duration of the call -- copy out of it before
+/// returning if `user_data` needs the data afterward. Passing a null `cb`
+/// clears any previously registered callback.
+///
+/// There's no re-entrancy protection needed on the caller's part: a
+/// callback that calls back into `hquant_push_bar`/`hquant_update_last`
+/// on this same `ptr` will not itself re-invoke `cb` (see
+/// [`crate::engine::HQuant::set_signal_callback`]).
+///
+/// # Safety
+/// `ptr` must be a live `hquant_new` pointer. If `cb` is non-null, it
+/// must be safe to call from this thread with `user_data` for as long as
+/// it stays registered (i.e. until cleared or `ptr` is freed).
+#[no_mangle]
+pub unsafe extern "C" fn hquant_set_signal_callback(
+    ptr: *mut HQuant,
+    cb: Option<extern "C" fn(*const HqSignal, usize, *mut c_void)>,
+    user_data: *mut c_void,
+) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() {
+            return HqStatus::NullPtr;
+        }
+        let hq = &mut *ptr;
+        match cb {
+            Some(cb) => {
+                let user_data = SendPtr(user_data);
+                hq.set_signal_callback(move |signals: &[Signal]| {
+                    let buf: Vec<HqSignal> = signals.iter().copied().map(HqSignal::from).collect();
+                    cb(buf.as_ptr(), buf.len(), user_data.get());
+                });
+            }
+            None => hq.clear_signal_callback(),
+        }
+        HqStatus::Ok
+    })
+}
+
+/// Revise the most recently pushed bar in place (e.g. an in-progress
+/// candle whose close just ticked), re-running any registered signal
+/// callback the same way [`hquant_push_bar`] does.
+///
+/// # Safety
+/// `ptr` must be a live `hquant_new` pointer.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn hquant_update_last(
+    ptr: *mut HQuant,
+    ts: i64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() {
+            return HqStatus::NullPtr;
+        }
+        (*ptr).update_last(Bar { ts, open, high, low, close, volume });
+        HqStatus::Ok
+    })
+}
+
+/// Append-or-revise: a bar at the same timestamp as the last pushed one is
+/// routed to [`hquant_update_last`], any other timestamp to
+/// [`hquant_push_bar`] -- see [`crate::engine::HQuant::upsert_bar`]. Useful
+/// for a caller streaming live candles that don't know up front whether
+/// the next tick closes the current bar or opens a new one.
+///
+/// # Safety
+/// `ptr` must be a live `hquant_new` pointer.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn hquant_upsert_bar(
+    ptr: *mut HQuant,
+    ts: i64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() {
+            return HqStatus::NullPtr;
+        }
+        (*ptr).upsert_bar(Bar { ts, open, high, low, close, volume });
+        HqStatus::Ok
+    })
+}
+
+/// `repr(C)` mirror of [`crate::backtest::Side`].
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HqSide {
+    Long = 0,
+    Short = 1,
+}
+
+impl From<Side> for HqSide {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::Long => HqSide::Long,
+            Side::Short => HqSide::Short,
+        }
+    }
+}
+
+/// Create a new [`FuturesBacktest`] with `equity_capacity` (clamped to at
+/// least 1) bars of [`FuturesBacktest::equity_curve`] retained, returning
+/// an owning pointer the caller must eventually pass to
+/// [`hquant_futures_free`]. A separate opaque pointer from [`hquant_new`]'s
+/// `HQuant` -- a futures backtest isn't attached to a live indicator graph
+/// the way [`crate::backtest::engine::BacktestEngine`] is via
+/// [`HQuant::attach_backtest`].
+#[no_mangle]
+pub extern "C" fn hquant_futures_new(funding_rate: f64, starting_cash: f64, equity_capacity: usize) -> *mut FuturesBacktest {
+    Box::into_raw(Box::new(FuturesBacktest::new(BacktestParams { funding_rate }, starting_cash, equity_capacity.max(1))))
+}
+
+/// Free a [`FuturesBacktest`] created by [`hquant_futures_new`]. A null
+/// `ptr` is a no-op.
+///
+/// # Safety
+/// `ptr` must be a pointer previously returned by `hquant_futures_new`
+/// that hasn't already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_futures_free(ptr: *mut FuturesBacktest) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(ptr));
+    }
+}
+
+/// Open (or replace) the tracked position at 1x margin. See
+/// [`FuturesBacktest::open`].
+///
+/// # Safety
+/// `ptr` must be a live `hquant_futures_new` pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_futures_open(ptr: *mut FuturesBacktest, side: HqSide, quantity: f64) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() {
+            return HqStatus::NullPtr;
+        }
+        let side = match side {
+            HqSide::Long => Side::Long,
+            HqSide::Short => Side::Short,
+        };
+        (*ptr).open(side, quantity);
+        HqStatus::Ok
+    })
+}
+
+/// Open (or replace) the tracked position at `leverage`x margin. See
+/// [`FuturesBacktest::open_leveraged`].
+///
+/// # Safety
+/// `ptr` must be a live `hquant_futures_new` pointer.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn hquant_futures_open_leveraged(
+    ptr: *mut FuturesBacktest,
+    side: HqSide,
+    quantity: f64,
+    entry_price: f64,
+    leverage: f64,
+) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() {
+            return HqStatus::NullPtr;
+        }
+        let side = match side {
+            HqSide::Long => Side::Long,
+            HqSide::Short => Side::Short,
+        };
+        (*ptr).open_leveraged(side, quantity, entry_price, leverage);
+        HqStatus::Ok
+    })
+}
+
+/// Close the tracked position at `price`, recording a [`FuturesTrade`]. A
+/// no-op while already flat. See [`FuturesBacktest::close_at`].
+///
+/// # Safety
+/// `ptr` must be a live `hquant_futures_new` pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_futures_close_at(ptr: *mut FuturesBacktest, price: f64) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() {
+            return HqStatus::NullPtr;
+        }
+        (*ptr).close_at(price);
+        HqStatus::Ok
+    })
+}
+
+/// Mark-to-market at `price` and roll the result into the equity curve,
+/// writing the sampled equity through `out_equity`. See
+/// [`FuturesBacktest::on_price`].
+///
+/// # Safety
+/// `ptr` must be a live `hquant_futures_new` pointer; `out_equity` must be
+/// a valid, non-null, correctly aligned pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_futures_on_price(ptr: *mut FuturesBacktest, price: f64, out_equity: *mut f64) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() || out_equity.is_null() {
+            return HqStatus::NullPtr;
+        }
+        *out_equity = (*ptr).on_price(price);
+        HqStatus::Ok
+    })
+}
+
+/// Zero-copy view over a heap-allocated `f64` buffer handed across the FFI
+/// boundary, e.g. by [`hquant_futures_equity_curve`]. Must be released
+/// with [`hquant_futures_free_column`] -- unlike every status-returning
+/// function above, there's no `HQuant`-owned buffer backing this one to
+/// free it alongside.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct HqColumnF64 {
+    pub ptr: *const f64,
+    pub len: usize,
+}
+
+/// The recorded equity curve, oldest first, copied into a freshly
+/// allocated buffer the caller owns -- see [`hquant_futures_free_column`].
+/// A null `ptr` (or an out-of-memory allocation, which can't happen for a
+/// `Vec` this small in practice) reports back as a null, zero-length
+/// column rather than a status code, since this function has no `HqStatus`
+/// return slot to report through. See [`FuturesBacktest::equity_curve`].
+///
+/// # Safety
+/// `ptr` must be a live `hquant_futures_new` pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_futures_equity_curve(ptr: *mut FuturesBacktest) -> HqColumnF64 {
+    if ptr.is_null() {
+        return HqColumnF64 { ptr: std::ptr::null(), len: 0 };
+    }
+    let curve = (*ptr).equity_curve().into_boxed_slice();
+    let len = curve.len();
+    HqColumnF64 { ptr: Box::into_raw(curve) as *const f64, len }
+}
+
+/// Free a column returned by [`hquant_futures_equity_curve`]. A null
+/// `col.ptr` is a no-op.
+///
+/// # Safety
+/// `col` must be a value previously returned by `hquant_futures_equity_curve`
+/// that hasn't already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_futures_free_column(col: HqColumnF64) {
+    if !col.ptr.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(col.ptr as *mut f64, col.len)));
+    }
+}
+
+/// Check `bar_high`/`bar_low` against the tracked position's liquidation
+/// price, force-closing it if triggered, and write whether it was through
+/// `out_liquidated`. See [`FuturesBacktest::check_liquidation`].
+///
+/// # Safety
+/// `ptr` must be a live `hquant_futures_new` pointer; `out_liquidated`
+/// must be a valid, non-null, correctly aligned pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_futures_check_liquidation(
+    ptr: *mut FuturesBacktest,
+    bar_high: f64,
+    bar_low: f64,
+    out_liquidated: *mut bool,
+) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() || out_liquidated.is_null() {
+            return HqStatus::NullPtr;
+        }
+        *out_liquidated = (*ptr).check_liquidation(bar_high, bar_low);
+        HqStatus::Ok
+    })
+}
+
+/// Debit/credit `cash` by the funding owed at `rate`/`price`. See
+/// [`FuturesBacktest::accrue_funding`].
+///
+/// # Safety
+/// `ptr` must be a live `hquant_futures_new` pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_futures_accrue_funding(ptr: *mut FuturesBacktest, rate: f64, price: f64) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() {
+            return HqStatus::NullPtr;
+        }
+        (*ptr).accrue_funding(rate, price);
+        HqStatus::Ok
+    })
+}
+
+/// Write the current cash balance through `out_cash`. See
+/// [`FuturesBacktest::cash`].
+///
+/// # Safety
+/// `ptr` must be a live `hquant_futures_new` pointer; `out_cash` must be a
+/// valid, non-null, correctly aligned pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_futures_cash(ptr: *mut FuturesBacktest, out_cash: *mut f64) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() || out_cash.is_null() {
+            return HqStatus::NullPtr;
+        }
+        *out_cash = (*ptr).cash();
+        HqStatus::Ok
+    })
+}
+
+/// `repr(C)` mirror of [`PositionSnapshot`]. `has_position == 0` means the
+/// backtest is flat and every other field should be ignored, the same
+/// `has_*`-flag convention [`HqSignal::has_bracket`] uses.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct HqPositionSnapshot {
+    pub has_position: u8,
+    pub side: HqSide,
+    pub entry_price: f64,
+    pub quantity: f64,
+    pub margin: f64,
+}
+
+/// Write a snapshot of the currently open position (or an all-zero,
+/// `has_position == 0` snapshot while flat) through `out_position`. See
+/// [`FuturesBacktest::current_position`].
+///
+/// # Safety
+/// `ptr` must be a live `hquant_futures_new` pointer; `out_position` must
+/// be a valid, non-null, correctly aligned pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_futures_current_position(
+    ptr: *mut FuturesBacktest,
+    out_position: *mut HqPositionSnapshot,
+) -> HqStatus {
+    guard(|| {
+        if ptr.is_null() || out_position.is_null() {
+            return HqStatus::NullPtr;
+        }
+        *out_position = match (*ptr).current_position() {
+            Some(PositionSnapshot { side, entry_price, quantity, margin }) => {
+                HqPositionSnapshot { has_position: 1, side: side.into(), entry_price, quantity, margin }
+            }
+            None => HqPositionSnapshot { has_position: 0, side: HqSide::Long, entry_price: 0.0, quantity: 0.0, margin: 0.0 },
+        };
+        HqStatus::Ok
+    })
+}
+
+/// `repr(C)` mirror of [`FuturesTrade`], with `pnl` (from
+/// [`FuturesTrade::pnl`]) precomputed rather than left for the caller to
+/// derive from the other four fields.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct HqFuturesTrade {
+    pub side: HqSide,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub quantity: f64,
+    pub pnl: f64,
+}
+
+impl From<FuturesTrade> for HqFuturesTrade {
+    fn from(trade: FuturesTrade) -> Self {
+        HqFuturesTrade {
+            side: trade.side.into(),
+            entry_price: trade.entry_price,
+            exit_price: trade.exit_price,
+            quantity: trade.quantity,
+            pnl: trade.pnl(),
+        }
+    }
+}
+
+/// Zero-copy view over a heap-allocated [`HqFuturesTrade`] buffer, the
+/// [`HqFuturesTrade`] analog of [`HqColumnF64`]. Must be released with
+/// [`hquant_futures_free_trades`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct HqFuturesTradesColumn {
+    pub ptr: *const HqFuturesTrade,
+    pub len: usize,
+}
+
+/// Every leg [`FuturesBacktest::close_at`] has closed so far, oldest
+/// first, copied into a freshly allocated buffer the caller owns -- see
+/// [`hquant_futures_free_trades`]. See [`FuturesBacktest::trades`].
+///
+/// # Safety
+/// `ptr` must be a live `hquant_futures_new` pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_futures_trades(ptr: *mut FuturesBacktest) -> HqFuturesTradesColumn {
+    if ptr.is_null() {
+        return HqFuturesTradesColumn { ptr: std::ptr::null(), len: 0 };
+    }
+    let trades: Box<[HqFuturesTrade]> = (*ptr).trades().iter().copied().map(HqFuturesTrade::from).collect();
+    let len = trades.len();
+    HqFuturesTradesColumn { ptr: Box::into_raw(trades) as *const HqFuturesTrade, len }
+}
+
+/// Free a column returned by [`hquant_futures_trades`]. A null `col.ptr`
+/// is a no-op.
+///
+/// # Safety
+/// `col` must be a value previously returned by `hquant_futures_trades`
+/// that hasn't already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn hquant_futures_free_trades(col: HqFuturesTradesColumn) {
+    if !col.ptr.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(col.ptr as *mut HqFuturesTrade, col.len)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    extern "C" fn count_invocations(_signals: *const HqSignal, count: usize, user_data: *mut c_void) {
+        assert!(count > 0, "callback must only run when a signal was actually produced");
+        unsafe {
+            (*(user_data as *const AtomicUsize)).fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn signal_callback_fires_once_per_bar_that_crosses_the_strategy() {
+        // A `CROSS_BELOW(EMA_2, EMA_4)` strategy stands in for "RSI
+        // oversold" here -- both fire once, on the bar a down-trend's fast
+        // average dips under its slow one, and this predates RSI's own
+        // arrival in `IndicatorSpec` (see `indicator::rsi`).
+        unsafe {
+            let hq = hquant_new(16);
+            let mut fast = 0usize;
+            let mut slow = 0usize;
+            hquant_add_indicator(hq, CString::new("EMA_2").unwrap().as_ptr(), &mut fast);
+            hquant_add_indicator(hq, CString::new("EMA_4").unwrap().as_ptr(), &mut slow);
+            let dsl = CString::new("CROSS_BELOW(EMA_2, EMA_4) => BUY").unwrap();
+            assert_eq!(hquant_add_strategy(hq, 1, dsl.as_ptr(), 0), HqStatus::Ok);
+
+            let invocations = Box::new(AtomicUsize::new(0));
+            let user_data = Box::into_raw(invocations) as *mut c_void;
+            assert_eq!(hquant_set_signal_callback(hq, Some(count_invocations), user_data), HqStatus::Ok);
+
+            let closes = [100.0, 100.0, 100.0, 90.0, 70.0, 50.0, 30.0, 30.0, 30.0];
+            for (i, &close) in closes.iter().enumerate() {
+                hquant_push_bar(hq, i as i64, close, close, close, close, 1.0);
+            }
+
+            let invocations = Box::from_raw(user_data as *mut AtomicUsize);
+            assert_eq!(invocations.load(Ordering::SeqCst), 1, "the cross happens exactly once in this down-trend");
+
+            hquant_free(hq);
+        }
+    }
+
+    #[test]
+    fn clearing_the_signal_callback_with_a_null_stops_further_invocations() {
+        unsafe {
+            let hq = hquant_new(16);
+            let mut fast = 0usize;
+            let mut slow = 0usize;
+            hquant_add_indicator(hq, CString::new("EMA_2").unwrap().as_ptr(), &mut fast);
+            hquant_add_indicator(hq, CString::new("EMA_4").unwrap().as_ptr(), &mut slow);
+            let dsl = CString::new("CROSS_BELOW(EMA_2, EMA_4) => BUY").unwrap();
+            hquant_add_strategy(hq, 1, dsl.as_ptr(), 0);
+
+            let invocations = Box::new(AtomicUsize::new(0));
+            let user_data = Box::into_raw(invocations) as *mut c_void;
+            hquant_set_signal_callback(hq, Some(count_invocations), user_data);
+            assert_eq!(hquant_set_signal_callback(hq, None, std::ptr::null_mut()), HqStatus::Ok);
+
+            for (i, &close) in [100.0, 90.0, 70.0, 50.0].iter().enumerate() {
+                hquant_push_bar(hq, i as i64, close, close, close, close, 1.0);
+            }
+
+            let invocations = Box::from_raw(user_data as *mut AtomicUsize);
+            assert_eq!(invocations.load(Ordering::SeqCst), 0);
+
+            hquant_free(hq);
+        }
+    }
+
+    #[test]
+    fn add_indicator_writes_the_id_and_returns_ok() {
+        unsafe {
+            let hq = hquant_new(16);
+            let mut id = usize::MAX;
+            let name = CString::new("SMA_5").unwrap();
+            let status = hquant_add_indicator(hq, name.as_ptr(), &mut id);
+            assert_eq!(status, HqStatus::Ok);
+            assert_eq!(id, 0);
+            hquant_free(hq);
+        }
+    }
+
+    #[test]
+    fn add_indicator_rejects_an_unparseable_spec_without_touching_out_id() {
+        unsafe {
+            let hq = hquant_new(16);
+            let mut id = 42usize;
+            let name = CString::new("NOT_A_SPEC").unwrap();
+            let status = hquant_add_indicator(hq, name.as_ptr(), &mut id);
+            assert_eq!(status, HqStatus::InvalidArg);
+            assert_eq!(id, 42, "a failed call must not write through out_id");
+            hquant_free(hq);
+        }
+    }
+
+    #[test]
+    fn upsert_bar_accepts_both_a_revision_and_a_fresh_bar() {
+        unsafe {
+            let hq = hquant_new(16);
+            assert_eq!(hquant_upsert_bar(hq, 0, 1.0, 1.0, 1.0, 1.0, 1.0), HqStatus::Ok);
+            // Same timestamp: revises the forming bar in place.
+            assert_eq!(hquant_upsert_bar(hq, 0, 1.0, 2.0, 1.0, 1.5, 3.0), HqStatus::Ok);
+            // New timestamp: appends instead.
+            assert_eq!(hquant_upsert_bar(hq, 1, 2.0, 2.0, 2.0, 2.0, 1.0), HqStatus::Ok);
+            hquant_free(hq);
+        }
+    }
+
+    #[test]
+    fn upsert_bar_reports_a_null_pointer() {
+        unsafe {
+            assert_eq!(hquant_upsert_bar(std::ptr::null_mut(), 0, 1.0, 1.0, 1.0, 1.0, 1.0), HqStatus::NullPtr);
+        }
+    }
+
+    #[test]
+    fn null_pointers_are_reported_distinctly_from_invalid_args() {
+        unsafe {
+            let mut id = 0usize;
+            let name = CString::new("SMA_5").unwrap();
+            assert_eq!(hquant_add_indicator(std::ptr::null_mut(), name.as_ptr(), &mut id), HqStatus::NullPtr);
+
+            let hq = hquant_new(16);
+            assert_eq!(hquant_add_indicator(hq, std::ptr::null(), &mut id), HqStatus::NullPtr);
+            hquant_free(hq);
+        }
+    }
+
+    #[test]
+    fn push_bar_advances_the_indicator_graph() {
+        unsafe {
+            let hq = hquant_new(16);
+            let mut id = 0usize;
+            let name = CString::new("SMA_2").unwrap();
+            assert_eq!(hquant_add_indicator(hq, name.as_ptr(), &mut id), HqStatus::Ok);
+
+            assert_eq!(hquant_push_bar(hq, 0, 1.0, 1.0, 1.0, 1.0, 1.0), HqStatus::Ok);
+            assert_eq!(hquant_push_bar(hq, 1, 3.0, 3.0, 3.0, 3.0, 1.0), HqStatus::Ok);
+            assert_eq!((*hq).graph().get_from_end(id, 0), Some(2.0));
+            hquant_free(hq);
+        }
+    }
+
+    #[test]
+    fn add_indicator_round_trips_every_kind_of_spec_string_through_the_one_generic_entry_point() {
+        // No per-kind `hquant_add_rsi`/`hquant_add_boll` function exists --
+        // a single-node spec, a multi-argument spec, and a brand new
+        // indicator kind (`CMO`, added well after this FFI) all reach the
+        // graph through this same `hquant_add_indicator` call.
+        unsafe {
+            let hq = hquant_new(16);
+            for spec in ["EMA_12", "RSI(14,sma)", "BOLL(close, 20, 2.0)", "CMO_14", "TRIX_10"] {
+                let mut id = usize::MAX;
+                let name = CString::new(spec).unwrap();
+                let status = hquant_add_indicator(hq, name.as_ptr(), &mut id);
+                assert_eq!(status, HqStatus::Ok, "expected {spec} to parse");
+                assert_ne!(id, usize::MAX, "expected {spec} to write through out_id");
+            }
+            hquant_free(hq);
+        }
+    }
+
+    #[test]
+    fn add_strategy_reports_a_parse_error_status_distinct_from_invalid_arg() {
+        unsafe {
+            let hq = hquant_new(16);
+            let sma_name = CString::new("SMA_5").unwrap();
+            let mut id = 0usize;
+            hquant_add_indicator(hq, sma_name.as_ptr(), &mut id);
+
+            let bad_dsl = CString::new("this is not valid dsl").unwrap();
+            assert_eq!(hquant_add_strategy(hq, 1, bad_dsl.as_ptr(), 0), HqStatus::ParseError);
+
+            let good_dsl = CString::new("SMA_5 > 100 => BUY").unwrap();
+            assert_eq!(hquant_add_strategy(hq, 1, good_dsl.as_ptr(), 0), HqStatus::Ok);
+            hquant_free(hq);
+        }
+    }
+
+    #[test]
+    fn futures_equity_curve_round_trips_the_sampled_marks() {
+        unsafe {
+            let fb = hquant_futures_new(0.0, 10_000.0, 500);
+            // `open_leveraged` at 1x, rather than `open`, so `entry_price`
+            // is actually set and unrealized PnL is against 100, not 0.
+            assert_eq!(hquant_futures_open_leveraged(fb, HqSide::Long, 1.0, 100.0, 1.0), HqStatus::Ok);
+
+            let mut equity = 0.0;
+            for price in [100.0, 110.0, 90.0] {
+                assert_eq!(hquant_futures_on_price(fb, price, &mut equity), HqStatus::Ok);
+            }
+            assert!((equity - 9_990.0).abs() < 1e-6);
+
+            let curve = hquant_futures_equity_curve(fb);
+            assert_eq!(curve.len, 3);
+            let samples = std::slice::from_raw_parts(curve.ptr, curve.len);
+            assert!((samples[0] - 10_000.0).abs() < 1e-6);
+            assert!((samples[2] - 9_990.0).abs() < 1e-6);
+
+            hquant_futures_free_column(curve);
+            hquant_futures_free(fb);
+        }
+    }
+
+    #[test]
+    fn futures_current_position_reports_flat_then_open() {
+        unsafe {
+            let fb = hquant_futures_new(0.0, 10_000.0, 16);
+
+            let mut snapshot = HqPositionSnapshot { has_position: 1, side: HqSide::Long, entry_price: 1.0, quantity: 1.0, margin: 1.0 };
+            assert_eq!(hquant_futures_current_position(fb, &mut snapshot), HqStatus::Ok);
+            assert_eq!(snapshot.has_position, 0, "expected a flat backtest to report no position");
+
+            assert_eq!(hquant_futures_open_leveraged(fb, HqSide::Short, 2.0, 50_000.0, 10.0), HqStatus::Ok);
+            assert_eq!(hquant_futures_current_position(fb, &mut snapshot), HqStatus::Ok);
+            assert_eq!(snapshot.has_position, 1);
+            assert_eq!(snapshot.side, HqSide::Short);
+            assert!((snapshot.quantity - 2.0).abs() < 1e-9);
+            assert!((snapshot.margin - 10_000.0).abs() < 1e-6);
+
+            hquant_futures_free(fb);
+        }
+    }
+
+    #[test]
+    fn futures_trades_records_a_closed_leg() {
+        unsafe {
+            let fb = hquant_futures_new(0.0, 10_000.0, 16);
+            assert_eq!(hquant_futures_open(fb, HqSide::Long, 2.0), HqStatus::Ok);
+            assert_eq!(hquant_futures_close_at(fb, 110.0), HqStatus::Ok);
+
+            let trades = hquant_futures_trades(fb);
+            assert_eq!(trades.len, 1);
+            let trade = *trades.ptr;
+            assert_eq!(trade.side, HqSide::Long);
+            assert!((trade.entry_price - 0.0).abs() < 1e-9, "hquant_futures_open leaves entry_price at 0 (1x, unleveraged)");
+            assert!((trade.exit_price - 110.0).abs() < 1e-9);
+            assert!((trade.pnl - 220.0).abs() < 1e-6);
+
+            hquant_futures_free_trades(trades);
+            hquant_futures_free(fb);
+        }
+    }
+
+    #[test]
+    fn futures_liquidation_and_funding_reach_the_underlying_backtest() {
+        unsafe {
+            let fb = hquant_futures_new(0.0001, 10_000.0, 16);
+            assert_eq!(hquant_futures_open_leveraged(fb, HqSide::Long, 1.0, 50_000.0, 10.0), HqStatus::Ok);
+
+            assert_eq!(hquant_futures_accrue_funding(fb, 0.0001, 50_000.0), HqStatus::Ok);
+            let mut cash = 0.0;
+            assert_eq!(hquant_futures_cash(fb, &mut cash), HqStatus::Ok);
+            assert!(cash < 10_000.0, "a long paid funding");
+
+            let mut liquidated = false;
+            assert_eq!(hquant_futures_check_liquidation(fb, 50_500.0, 44_500.0, &mut liquidated), HqStatus::Ok);
+            assert!(liquidated, "a low wick through 45_000 should liquidate a 10x long entered at 50_000");
+
+            hquant_futures_free(fb);
+        }
+    }
+
+    #[test]
+    fn futures_null_pointer_reports_distinctly_and_returns_an_empty_column() {
+        unsafe {
+            let mut equity = 0.0;
+            assert_eq!(hquant_futures_on_price(std::ptr::null_mut(), 1.0, &mut equity), HqStatus::NullPtr);
+
+            let curve = hquant_futures_equity_curve(std::ptr::null_mut());
+            assert!(curve.ptr.is_null());
+            assert_eq!(curve.len, 0);
+        }
+    }
+}
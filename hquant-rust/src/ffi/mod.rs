@@ -0,0 +1,7 @@
+//! Language bindings. Each submodule is gated behind its own feature so a
+//! plain `cargo build` never needs Python/Node toolchains installed.
+
+#[cfg(feature = "c_abi")]
+pub mod c;
+#[cfg(feature = "python")]
+pub mod python;
@@ -0,0 +1,183 @@
+//! PyO3 bindings exposing [`crate::engine::HQuant`] to Python.
+//!
+//! There's no napi/Node.js binding in this crate (see [`super`] -- besides
+//! this `python`-gated submodule, `ffi` only has the C ABI in `c`), so
+//! `push_bars`/`push_columns` below give this Python surface the same batch-ingestion path a Node binding's `push_bars`/
+//! `push_columns` would need for the same reason.
+
+// pyo3's macro expansion trips `clippy::useless_conversion` on the error
+// path of every fallible `#[pymethods]` fn under this pyo3/clippy pairing.
+#![allow(clippy::useless_conversion)]
+
+use crate::alert::CrossDirection;
+use crate::engine::HQuant as CoreHQuant;
+use crate::kline::Bar;
+use numpy::{PyArray1, PyReadonlyArray1};
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+
+#[pyclass(name = "HQuant")]
+pub struct PyHQuant {
+    inner: CoreHQuant,
+}
+
+#[pymethods]
+impl PyHQuant {
+    #[new]
+    fn new(capacity: usize) -> Self {
+        Self { inner: CoreHQuant::new(capacity) }
+    }
+
+    /// Register an indicator by spec string (e.g. `"EMA_12"`,
+    /// `"BOLL(close, 20, 2.0)"`, `"CMO_14"`), the same generic entry point
+    /// [`super::c::hquant_add_indicator`] exposes to C callers -- there's
+    /// no per-kind `add_rsi`/`add_ema` method here to consolidate, so a new
+    /// indicator only ever needs a new
+    /// [`IndicatorSpec::parse`](crate::indicator::IndicatorSpec::parse)
+    /// branch to reach this binding too.
+    fn add_indicator(&mut self, name: &str) -> PyResult<usize> {
+        self.inner
+            .add_indicator(name)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn push_bar(&mut self, ts: i64, open: f64, high: f64, low: f64, close: f64, volume: f64) {
+        self.inner.push_bar(Bar { ts, open, high, low, close, volume });
+    }
+
+    /// Append-or-revise: a bar at the same timestamp as the last pushed one
+    /// revises it in place, any other timestamp appends -- see
+    /// [`crate::engine::HQuant::upsert_bar`]. For a caller streaming live
+    /// candles that doesn't track on its own whether the next tick closes
+    /// the current bar or opens a new one.
+    #[allow(clippy::too_many_arguments)]
+    fn upsert_bar(&mut self, ts: i64, open: f64, high: f64, low: f64, close: f64, volume: f64) {
+        self.inner.upsert_bar(Bar { ts, open, high, low, close, volume });
+    }
+
+    /// Push many bars in one call, oldest first, each treated as a fully
+    /// closed bar exactly like a `push_bar` call on it alone -- so loading
+    /// a large historical backfill doesn't pay one Python->Rust crossing
+    /// per candle. Any alerts that fire mid-batch are still pollable
+    /// afterward via `poll_alerts`, same as if each bar had been pushed
+    /// one at a time.
+    ///
+    /// `bars` is fully converted from the Python list before this runs,
+    /// so the actual ingest loop below touches no Python object -- it
+    /// runs under `py.allow_threads` so other Python threads (e.g. a
+    /// second one driving its own `HQuant`) aren't blocked for the
+    /// duration.
+    fn push_bars(&mut self, py: Python<'_>, bars: Vec<(i64, f64, f64, f64, f64, f64)>) {
+        let inner = &mut self.inner;
+        py.allow_threads(move || {
+            for (ts, open, high, low, close, volume) in bars {
+                inner.push_bar(Bar { ts, open, high, low, close, volume });
+            }
+        });
+    }
+
+    /// Like `push_bars`, but from parallel numpy arrays instead of a list
+    /// of tuples -- the write-side mirror of `close_column`'s zero-copy
+    /// export. `open`/`high`/`low`/`close`/`volume`/`timestamp` must all
+    /// have the same length, oldest bar first; bars are pushed in
+    /// ascending index order under a single call, so alerts fire in the
+    /// same order they would from `bars.len()` individual `push_bar`
+    /// calls.
+    ///
+    /// Unlike `push_bars`, this stays on the GIL for its whole body: the
+    /// slices it reads from below are zero-copy views straight into the
+    /// numpy arrays' own buffers, so releasing the GIL while iterating
+    /// them would let another Python thread mutate (or free) that memory
+    /// underneath us.
+    #[allow(clippy::too_many_arguments)]
+    fn push_columns(
+        &mut self,
+        timestamp: PyReadonlyArray1<'_, i64>,
+        open: PyReadonlyArray1<'_, f64>,
+        high: PyReadonlyArray1<'_, f64>,
+        low: PyReadonlyArray1<'_, f64>,
+        close: PyReadonlyArray1<'_, f64>,
+        volume: PyReadonlyArray1<'_, f64>,
+    ) -> PyResult<()> {
+        let timestamp = timestamp.as_slice()?;
+        let open = open.as_slice()?;
+        let high = high.as_slice()?;
+        let low = low.as_slice()?;
+        let close = close.as_slice()?;
+        let volume = volume.as_slice()?;
+        let len = timestamp.len();
+        if [open.len(), high.len(), low.len(), close.len(), volume.len()].iter().any(|&n| n != len) {
+            return Err(PyValueError::new_err("push_columns: all columns must have the same length"));
+        }
+        for i in 0..len {
+            self.inner.push_bar(Bar {
+                ts: timestamp[i],
+                open: open[i],
+                high: high[i],
+                low: low[i],
+                close: close[i],
+                volume: volume[i],
+            });
+        }
+        Ok(())
+    }
+
+    /// Return `(array, capacity, len, head)` for a computed indicator's
+    /// full output history, mirroring `close_column`'s convention so
+    /// callers can reconstruct chronological order the same way for both.
+    fn indicator_array(&self, py: Python<'_>, id: usize) -> PyResult<(Py<PyArray1<f64>>, usize, usize, usize)> {
+        let (raw, capacity, len, head) = self
+            .inner
+            .indicator_array(id)
+            .ok_or_else(|| PyKeyError::new_err(format!("unknown indicator id {id}")))?;
+        Ok((PyArray1::from_vec_bound(py, raw.to_vec()).unbind(), capacity, len, head))
+    }
+
+    /// The indicator's most recent non-`NaN` value, scanning back through
+    /// history for display continuity through warm-up or a bad bar.
+    fn indicator_last_valid(&self, id: usize) -> Option<f64> {
+        self.inner.indicator_last_valid(id)
+    }
+
+    /// Whether `id` has seen enough bars for its output to mean anything,
+    /// rather than a caller checking for `NaN` themselves.
+    fn indicator_ready(&self, id: usize) -> bool {
+        self.inner.indicator_ready(id)
+    }
+
+    fn close_column(&self, py: Python<'_>) -> (Py<PyArray1<f64>>, usize, usize, usize) {
+        let (raw, capacity, len, head) = self.inner.close_column();
+        (PyArray1::from_vec_bound(py, raw.to_vec()).unbind(), capacity, len, head)
+    }
+
+    /// Register a lightweight alert firing once `indicator_id` crosses
+    /// `level`; `above=true` for a crossing-above, `above=false` for a
+    /// crossing-below. Returns an opaque id for `remove_alert`.
+    fn add_alert(&mut self, indicator_id: usize, level: f64, above: bool) -> u64 {
+        let direction = if above { CrossDirection::Above } else { CrossDirection::Below };
+        self.inner.add_alert(indicator_id, level, direction).0
+    }
+
+    fn remove_alert(&mut self, alert_id: u64) -> bool {
+        self.inner.remove_alert(crate::alert::AlertId(alert_id))
+    }
+
+    /// Ids of every alert that fired since the last poll. There's no
+    /// `poll_signals` in this crate (alerts are the only thing `HQuant`
+    /// polls rather than surfaces inline), so this is the analogous
+    /// method released under `py.allow_threads` -- draining is normally
+    /// cheap, but this keeps a caller polling many instruments from one
+    /// thread from ever blocking another Python thread pushing bars into
+    /// a different `HQuant`.
+    fn poll_alerts(&mut self, py: Python<'_>) -> Vec<u64> {
+        let inner = &mut self.inner;
+        py.allow_threads(move || inner.poll_alerts().into_iter().map(|id| id.0).collect())
+    }
+}
+
+#[pymodule]
+fn hquant_rust(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyHQuant>()?;
+    Ok(())
+}
@@ -0,0 +1,117 @@
+//! Heikin-Ashi candle transform: smooths a raw OHLC [`Bar`] stream into HA
+//! bars for trend-following strategies, at the cost of the HA close no
+//! longer being a real traded price. The recurrence is:
+//!
+//! ```text
+//! ha_close = (open + high + low + close) / 4
+//! ha_open  = (prev_ha_open + prev_ha_close) / 2
+//! ha_high  = max(high, ha_open, ha_close)
+//! ha_low   = min(low, ha_open, ha_close)
+//! ```
+//!
+//! The very first bar has no previous HA bar to seed `ha_open` from, so it
+//! uses the raw bar's own `(open + close) / 2` instead -- the usual
+//! Heikin-Ashi convention.
+
+use crate::kline::Bar;
+
+/// Stateful HA transformer, one raw [`Bar`] stream in, one HA [`Bar`]
+/// stream out. `push`/`update_last` follow the same committed-vs-current
+/// convention as [`crate::indicator::exec::Ema`]: `push` permanently
+/// commits the previous bar's HA output before computing the new one;
+/// `update_last` recomputes the current (still-forming) bar's HA output
+/// against that same committed state, so it can be revised repeatedly
+/// without corrupting the `ha_open` recurrence.
+#[derive(Debug, Clone, Default)]
+pub struct HeikinAshi {
+    /// HA output as of the end of the last fully closed bar.
+    committed: Option<Bar>,
+    /// Output for the bar currently being built, if any.
+    current: Option<Bar>,
+}
+
+impl HeikinAshi {
+    pub fn new() -> Self {
+        Self { committed: None, current: None }
+    }
+
+    fn compute(&self, bar: &Bar) -> Bar {
+        let ha_close = (bar.open + bar.high + bar.low + bar.close) / 4.0;
+        let ha_open = match self.committed {
+            Some(prev) => (prev.open + prev.close) / 2.0,
+            None => (bar.open + bar.close) / 2.0,
+        };
+        let ha_high = bar.high.max(ha_open).max(ha_close);
+        let ha_low = bar.low.min(ha_open).min(ha_close);
+        Bar { ts: bar.ts, open: ha_open, high: ha_high, low: ha_low, close: ha_close, volume: bar.volume }
+    }
+
+    /// Commit the previous bar's HA output permanently, then transform
+    /// `bar` into its own HA form.
+    pub fn push(&mut self, bar: &Bar) -> Bar {
+        if let Some(current) = self.current.take() {
+            self.committed = Some(current);
+        }
+        let ha_bar = self.compute(bar);
+        self.current = Some(ha_bar);
+        ha_bar
+    }
+
+    /// Revise the current (not yet committed) bar's HA output in place.
+    pub fn update_last(&mut self, bar: &Bar) -> Bar {
+        let ha_bar = self.compute(bar);
+        self.current = Some(ha_bar);
+        ha_bar
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(open: f64, high: f64, low: f64, close: f64) -> Bar {
+        Bar { ts: 0, open, high, low, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn first_bar_seeds_ha_open_from_its_own_open_and_close() {
+        let mut ha = HeikinAshi::new();
+        let raw = bar(10.0, 12.0, 9.0, 11.0);
+        let out = ha.push(&raw);
+        assert!((out.close - (10.0 + 12.0 + 9.0 + 11.0) / 4.0).abs() < 1e-9);
+        assert!((out.open - (10.0 + 11.0) / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ha_open_follows_the_prior_ha_bars_midpoint() {
+        let mut ha = HeikinAshi::new();
+        let first = ha.push(&bar(10.0, 12.0, 9.0, 11.0));
+        let second = ha.push(&bar(11.0, 14.0, 10.5, 13.0));
+        let expected_open = (first.open + first.close) / 2.0;
+        assert!((second.open - expected_open).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ha_high_low_extend_to_include_ha_open_and_ha_close() {
+        let mut ha = HeikinAshi::new();
+        // A gap-down bar whose raw high (82) sits below where ha_open ends
+        // up (100, carried over from the first bar), so ha_high must be
+        // pulled up to include it.
+        ha.push(&bar(100.0, 101.0, 99.0, 100.0));
+        let out = ha.push(&bar(80.0, 82.0, 79.0, 81.0));
+        assert!(out.low <= out.open.min(out.close));
+        assert!(out.high >= out.open.max(out.close));
+    }
+
+    #[test]
+    fn update_last_revises_the_current_bar_without_shifting_the_recurrence() {
+        let mut ha = HeikinAshi::new();
+        ha.push(&bar(10.0, 12.0, 9.0, 11.0));
+        let live = ha.push(&bar(11.0, 14.0, 10.5, 13.0));
+        let revised = ha.update_last(&bar(11.0, 15.0, 10.5, 14.0));
+        // Same ha_open recurrence (based on the same committed first bar)...
+        assert!((revised.open - live.open).abs() < 1e-9);
+        // ...but a different ha_close from the revised raw values.
+        assert!((revised.close - live.close).abs() > 1e-9);
+    }
+}
@@ -0,0 +1,102 @@
+//! Accumulation/Distribution Line: a running total of each bar's money flow
+//! volume, `clv * volume`, where `clv` (close location value) is
+//! `((close - low) - (high - close)) / (high - low)`, `1.0` when the close
+//! sits at the bar's high and `-1.0` at its low.
+//!
+//! Unlike ADX (see [`super::adx`]), CLV only needs the *current* bar's
+//! high/low/close jointly, with no cross-bar lag at all, so it decomposes
+//! cleanly into a chain of single-field nodes combined via [`CombineOp::Sub`]
+//! and [`CombineOp::SafeRatio`] (the latter reporting `0.0` for a doji bar's
+//! zero range rather than propagating `NaN`), and the running total is a
+//! [`super::exec::CumulativeSum`] of `clv * volume` via [`CombineOp::Mul`].
+
+use super::exec::{CumulativeSum, Identity};
+use super::{CombineOp, IndicatorGraph, NodeId};
+use crate::error::Result;
+use crate::kline::Field;
+
+/// Build the close-location-value/money-flow-volume node chain shared by
+/// [`build`] and [`super::cmf::build`], registered under `base_name__`
+/// internal names. Returns `(mfv, volume)` so a caller can fold `mfv` (and
+/// `volume`, for CMF's denominator) into whatever aggregation it needs.
+pub(super) fn build_money_flow_volume(graph: &mut IndicatorGraph, base_name: &str) -> Result<(NodeId, NodeId)> {
+    let close = graph.add_field_indicator(&format!("{base_name}__close"), Field::Close, Box::new(Identity))?;
+    let high = graph.add_field_indicator(&format!("{base_name}__high"), Field::High, Box::new(Identity))?;
+    let low = graph.add_field_indicator(&format!("{base_name}__low"), Field::Low, Box::new(Identity))?;
+    let volume = graph.add_field_indicator(&format!("{base_name}__volume"), Field::Volume, Box::new(Identity))?;
+
+    let close_minus_low =
+        graph.add_combined_indicator(&format!("{base_name}__close_minus_low"), close, low, CombineOp::Sub, Box::new(Identity))?;
+    let high_minus_close =
+        graph.add_combined_indicator(&format!("{base_name}__high_minus_close"), high, close, CombineOp::Sub, Box::new(Identity))?;
+    let numerator = graph.add_combined_indicator(
+        &format!("{base_name}__numerator"),
+        close_minus_low,
+        high_minus_close,
+        CombineOp::Sub,
+        Box::new(Identity),
+    )?;
+    let range = graph.add_combined_indicator(&format!("{base_name}__range"), high, low, CombineOp::Sub, Box::new(Identity))?;
+    let clv = graph.add_combined_indicator(&format!("{base_name}__clv"), numerator, range, CombineOp::SafeRatio, Box::new(Identity))?;
+    let mfv = graph.add_combined_indicator(&format!("{base_name}__mfv"), clv, volume, CombineOp::Mul, Box::new(Identity))?;
+
+    Ok((mfv, volume))
+}
+
+/// Build the ADL DAG chain under `base_name`: the [`build_money_flow_volume`]
+/// chain feeding a running [`super::exec::CumulativeSum`]. Returns the ADL
+/// node id.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str) -> Result<NodeId> {
+    let (mfv, _volume) = build_money_flow_volume(graph, base_name)?;
+    graph.add_chained_indicator(base_name, mfv, Box::new(CumulativeSum::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(high: f64, low: f64, close: f64, volume: f64) -> Bar {
+        Bar { ts: 0, open: close, high, low, close, volume }
+    }
+
+    #[test]
+    fn accumulates_money_flow_volume_across_bars() {
+        let mut graph = IndicatorGraph::new();
+        let adl = build(&mut graph, "ADL").unwrap();
+
+        // Close at the high: clv = 1.0, mfv = 100.0.
+        graph.push_bar(&bar(10.0, 8.0, 10.0, 100.0));
+        assert!((graph.get_from_end(adl, 0).unwrap() - 100.0).abs() < 1e-9);
+
+        // Close at the low: clv = -1.0, mfv = -50.0.
+        graph.push_bar(&bar(12.0, 9.0, 9.0, 50.0));
+        assert!((graph.get_from_end(adl, 0).unwrap() - 50.0).abs() < 1e-9);
+
+        // Doji bar (high == low): clv is 0.0 via SafeRatio, not NaN.
+        graph.push_bar(&bar(11.0, 11.0, 11.0, 40.0));
+        assert!((graph.get_from_end(adl, 0).unwrap() - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn update_last_reproduces_a_fresh_push() {
+        let mut a = IndicatorGraph::new();
+        let adl_a = build(&mut a, "ADL").unwrap();
+        let mut b = IndicatorGraph::new();
+        let adl_b = build(&mut b, "ADL").unwrap();
+
+        a.push_bar(&bar(10.0, 8.0, 9.0, 100.0));
+        b.push_bar(&bar(10.0, 8.0, 9.0, 100.0));
+
+        // `a` opens the new bar with a placeholder, then revises it in
+        // place as the candle ticks toward its real close.
+        a.push_bar(&bar(11.0, 9.0, 9.5, 40.0));
+        a.update_last(&bar(12.0, 9.0, 11.0, 70.0));
+        // `b` sees the real bar directly.
+        b.push_bar(&bar(12.0, 9.0, 11.0, 70.0));
+
+        let va = a.get_from_end(adl_a, 0).unwrap();
+        let vb = b.get_from_end(adl_b, 0).unwrap();
+        assert!((va - vb).abs() < 1e-9, "expected {va} to match fresh push {vb}");
+    }
+}
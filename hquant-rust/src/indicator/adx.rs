@@ -0,0 +1,191 @@
+//! Average Directional Index (ADX) with its +DI/-DI directional indicator
+//! components, for trend-strength filtering.
+//!
+//! Unlike the other indicators in this module, ADX needs a bar's high,
+//! low *and* close jointly (for true range and directional movement) --
+//! more than [`super::IndicatorExec`]'s single-scalar-per-node contract
+//! can express, since a graph [`super::Node`] only ever feeds its exec one
+//! [`crate::kline::Field`]. It's implemented as a standalone transform in
+//! the style of [`crate::heikin_ashi::HeikinAshi`] (consuming `&Bar`
+//! directly) rather than as an `IndicatorGraph`-registered node, so it
+//! isn't reachable via `IndicatorGraph::add_from_spec`/[`super::IndicatorSpec`]
+//! or the DSL yet -- a caller drives it directly off the same bar stream,
+//! the way [`crate::aggregator::Aggregator`] is driven.
+//!
+//! Wilder's original smoothing (a running sum re-expressed per bar) is
+//! algebraically an exponential moving average with `alpha = 1/period`
+//! seeded by the first value, so this reuses [`super::Ema`] for all four
+//! smoothed series (`TR`, `+DM`, `-DM`, and `DX` itself).
+
+use super::exec::{Ema, IndicatorExec};
+use crate::kline::Bar;
+
+/// One bar's ADX output: the trend-strength index itself, plus the two
+/// directional indicators it's derived from (like [`super::BollBands`]
+/// groups a middle band with its upper/lower siblings).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdxOutput {
+    pub adx: f64,
+    pub plus_di: f64,
+    pub minus_di: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Adx {
+    smoothed_tr: Ema,
+    smoothed_plus_dm: Ema,
+    smoothed_minus_dm: Ema,
+    smoothed_dx: Ema,
+    /// The last permanently closed bar, used as "previous" for the true
+    /// range / directional movement recurrences. `None` before the first
+    /// bar.
+    committed_prev: Option<Bar>,
+    /// The bar currently being built; becomes `committed_prev` once the
+    /// next bar is pushed.
+    current: Option<Bar>,
+}
+
+impl Adx {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "ADX period must be > 0");
+        let alpha = 1.0 / period as f64;
+        Self {
+            smoothed_tr: Ema::with_alpha(alpha),
+            smoothed_plus_dm: Ema::with_alpha(alpha),
+            smoothed_minus_dm: Ema::with_alpha(alpha),
+            smoothed_dx: Ema::with_alpha(alpha),
+            committed_prev: None,
+            current: None,
+        }
+    }
+
+    fn true_range(&self, bar: &Bar) -> f64 {
+        match self.committed_prev {
+            Some(prev) => {
+                (bar.high - bar.low).max((bar.high - prev.close).abs()).max((bar.low - prev.close).abs())
+            }
+            None => bar.high - bar.low,
+        }
+    }
+
+    /// `(+DM, -DM)` for `bar` against the committed previous bar. Only one
+    /// side can be nonzero on a given bar: a bar can't simultaneously make
+    /// a stronger new high than it makes a new low.
+    fn directional_movement(&self, bar: &Bar) -> (f64, f64) {
+        match self.committed_prev {
+            Some(prev) => {
+                let up_move = bar.high - prev.high;
+                let down_move = prev.low - bar.low;
+                let plus_dm = if up_move > down_move && up_move > 0.0 { up_move } else { 0.0 };
+                let minus_dm = if down_move > up_move && down_move > 0.0 { down_move } else { 0.0 };
+                (plus_dm, minus_dm)
+            }
+            None => (0.0, 0.0),
+        }
+    }
+
+    fn compute(&mut self, bar: &Bar, revise: bool) -> AdxOutput {
+        let tr = self.true_range(bar);
+        let (plus_dm, minus_dm) = self.directional_movement(bar);
+
+        let smoothed_tr = if revise { self.smoothed_tr.update_last(tr) } else { self.smoothed_tr.push(tr) };
+        let smoothed_plus_dm =
+            if revise { self.smoothed_plus_dm.update_last(plus_dm) } else { self.smoothed_plus_dm.push(plus_dm) };
+        let smoothed_minus_dm =
+            if revise { self.smoothed_minus_dm.update_last(minus_dm) } else { self.smoothed_minus_dm.push(minus_dm) };
+
+        // A run of flat/identical bars legitimately settles `smoothed_tr`
+        // to `0.0` -- guard it the way every sibling ratio in this crate
+        // (`CombineOp::PercentOfTotal`/`PercentOfDifference`/`SafeRatio`,
+        // `RollingCorrelation::correlation`/`beta`) guards its own
+        // denominator, rather than letting a `0.0/0.0` NaN poison `dx` and
+        // then `adx` forever after via the `Ema` recurrence.
+        let plus_di = if smoothed_tr == 0.0 { 0.0 } else { 100.0 * smoothed_plus_dm / smoothed_tr };
+        let minus_di = if smoothed_tr == 0.0 { 0.0 } else { 100.0 * smoothed_minus_dm / smoothed_tr };
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum == 0.0 { 0.0 } else { 100.0 * (plus_di - minus_di).abs() / di_sum };
+
+        let adx = if revise { self.smoothed_dx.update_last(dx) } else { self.smoothed_dx.push(dx) };
+
+        AdxOutput { adx, plus_di, minus_di }
+    }
+
+    /// Commit the previous bar's smoothed state permanently, then compute
+    /// ADX/+DI/-DI for `bar`.
+    pub fn push(&mut self, bar: &Bar) -> AdxOutput {
+        if let Some(prev) = self.current.take() {
+            self.committed_prev = Some(prev);
+        }
+        let output = self.compute(bar, false);
+        self.current = Some(*bar);
+        output
+    }
+
+    /// Revise the current (not yet committed) bar's ADX/+DI/-DI in place,
+    /// against the same committed previous bar `push` last used.
+    pub fn update_last(&mut self, bar: &Bar) -> AdxOutput {
+        let output = self.compute(bar, true);
+        self.current = Some(*bar);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(high: f64, low: f64, close: f64) -> Bar {
+        Bar { ts: 0, open: close, high, low, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn a_clean_uptrend_has_plus_di_above_minus_di_and_a_rising_adx() {
+        let mut adx = Adx::new(14);
+        let mut prior_adx = 0.0;
+        let mut rose_at_least_once = false;
+        let mut last = AdxOutput { adx: 0.0, plus_di: 0.0, minus_di: 0.0 };
+
+        for i in 0..40 {
+            let base = 100.0 + i as f64 * 2.0;
+            last = adx.push(&bar(base + 1.0, base - 1.0, base));
+            if last.adx > prior_adx {
+                rose_at_least_once = true;
+            }
+            prior_adx = last.adx;
+        }
+
+        assert!(last.plus_di > last.minus_di, "plus_di={} minus_di={}", last.plus_di, last.minus_di);
+        assert!(rose_at_least_once);
+    }
+
+    #[test]
+    fn update_last_revises_without_moving_the_committed_previous_bar() {
+        let mut adx = Adx::new(14);
+        adx.push(&bar(101.0, 99.0, 100.0));
+        let live = adx.push(&bar(105.0, 103.0, 104.0));
+        let revised = adx.update_last(&bar(106.0, 104.0, 105.0));
+        // Both compare against the same committed previous bar (100.0), so
+        // the directional split should still favor the upside on revision.
+        assert!(revised.plus_di > 0.0);
+        assert_ne!(revised.plus_di, live.plus_di);
+    }
+
+    #[test]
+    fn first_bar_has_no_prior_bar_to_compare_against() {
+        let mut adx = Adx::new(14);
+        let out = adx.push(&bar(101.0, 99.0, 100.0));
+        assert_eq!(out.plus_di, 0.0);
+        assert_eq!(out.minus_di, 0.0);
+        assert_eq!(out.adx, 0.0);
+    }
+
+    #[test]
+    fn a_run_of_identical_bars_reports_zero_rather_than_nan() {
+        let mut adx = Adx::new(14);
+        let mut last = AdxOutput { adx: 0.0, plus_di: 0.0, minus_di: 0.0 };
+        for _ in 0..30 {
+            last = adx.push(&bar(100.0, 100.0, 100.0));
+        }
+        assert_eq!(last, AdxOutput { adx: 0.0, plus_di: 0.0, minus_di: 0.0 });
+    }
+}
@@ -0,0 +1,162 @@
+//! Average True Range (ATR): Wilder's smoothed true range, seeded by a
+//! simple average of the first `period` true ranges and then rolled
+//! forward one bar at a time via `atr = (prev_atr * (period - 1) + tr) /
+//! period`.
+//!
+//! True range needs a bar's high, low *and* close jointly (`max(high-low,
+//! |high-prev_close|, |low-prev_close|)`), more than
+//! [`super::IndicatorExec`]'s single-scalar-per-node contract can express,
+//! so like [`super::Adx`] this is a standalone transform driven directly
+//! off a bar stream rather than an `IndicatorGraph`-registered node -- it
+//! isn't reachable via [`super::IndicatorSpec`]/the DSL either, for the
+//! same reason.
+//!
+//! [`super::Adx`] and [`crate::aggregator::RenkoBuilder`]'s `BrickSize::Atr`
+//! each already smooth their own inline true range with [`super::Ema`],
+//! but seeded by their first true range rather than an average of the
+//! first `period` -- close enough once they've run for a while, but not a
+//! bar-for-bar match to a reference ATR series computed Wilder's original
+//! way. This is that reusable type, for [`crate::backtest::BacktestEngine`]'s
+//! `Volatility` slippage model (its only caller so far) and anything else
+//! that needs one.
+
+use crate::kline::Bar;
+
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+    /// Sum/count of true ranges committed so far, while `committed` is
+    /// still `None` -- once `warmup_count` reaches `period` this seeds
+    /// `committed` as their simple average and is never read again.
+    warmup_sum: f64,
+    warmup_count: usize,
+    /// ATR as of the end of the last fully closed bar. `None` until
+    /// `warmup_count` reaches `period`.
+    committed: Option<f64>,
+    /// The last permanently closed bar, used as "previous" for the true
+    /// range recurrence. `None` before the first bar.
+    committed_prev_bar: Option<Bar>,
+    /// The bar currently being built, and the true range computed for it,
+    /// so a revision via `update_last` doesn't need to recompute it
+    /// against a stale `committed_prev_bar`. Mirrors [`super::Ema`]'s
+    /// `current` field.
+    current: Option<(Bar, f64)>,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "ATR period must be > 0");
+        Self { period, warmup_sum: 0.0, warmup_count: 0, committed: None, committed_prev_bar: None, current: None }
+    }
+
+    fn true_range(&self, bar: &Bar) -> f64 {
+        match self.committed_prev_bar {
+            Some(prev) => {
+                (bar.high - bar.low).max((bar.high - prev.close).abs()).max((bar.low - prev.close).abs())
+            }
+            None => bar.high - bar.low,
+        }
+    }
+
+    /// ATR implied by `tr` given everything committed so far: the Wilder
+    /// recurrence once `committed` holds a seed, or a running partial
+    /// average of the true ranges seen so far while still warming up.
+    fn compute(&self, tr: f64) -> f64 {
+        match self.committed {
+            Some(prev) => (prev * (self.period - 1) as f64 + tr) / self.period as f64,
+            None => (self.warmup_sum + tr) / (self.warmup_count + 1) as f64,
+        }
+    }
+
+    /// Commit the previous bar's true range permanently, then compute ATR
+    /// for `bar`.
+    pub fn push(&mut self, bar: &Bar) -> f64 {
+        if let Some((prev_bar, prev_tr)) = self.current.take() {
+            self.committed_prev_bar = Some(prev_bar);
+            match self.committed {
+                Some(prev) => {
+                    self.committed = Some((prev * (self.period - 1) as f64 + prev_tr) / self.period as f64);
+                }
+                None => {
+                    self.warmup_sum += prev_tr;
+                    self.warmup_count += 1;
+                    if self.warmup_count == self.period {
+                        self.committed = Some(self.warmup_sum / self.period as f64);
+                    }
+                }
+            }
+        }
+        let tr = self.true_range(bar);
+        let value = self.compute(tr);
+        self.current = Some((*bar, tr));
+        value
+    }
+
+    /// Revise the current (not yet committed) bar's ATR in place, against
+    /// the same committed previous bar and Wilder state `push` last used.
+    pub fn update_last(&mut self, bar: &Bar) -> f64 {
+        let tr = self.true_range(bar);
+        let value = self.compute(tr);
+        self.current = Some((*bar, tr));
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(high: f64, low: f64, close: f64) -> Bar {
+        Bar { ts: 0, open: close, high, low, close, volume: 1.0 }
+    }
+
+    // Reference true ranges/ATR hand-computed from a textbook Wilder
+    // walkthrough: five bars, period 3. Each bar's true range is the max
+    // of its own high-low range and its gap against the previous close
+    // (bar 5's 103-108 gap of 5.0, its low against the previous close, beats its own 3.0 high-low range, for
+    // instance). ATR seeds at bar 3 as the simple average of the first
+    // three TRs, then rolls forward via Wilder's recurrence.
+    #[test]
+    fn matches_a_hand_computed_wilder_atr_series() {
+        let bars = [
+            bar(105.0, 100.0, 102.0),
+            bar(108.0, 102.0, 106.0),
+            bar(107.0, 103.0, 105.0),
+            bar(110.0, 103.0, 108.0),
+            bar(106.0, 103.0, 104.0),
+        ];
+        let trs = [5.0, 6.0, 4.0, 7.0, 5.0];
+        let seed = (trs[0] + trs[1] + trs[2]) / 3.0;
+        let expected = [trs[0], (trs[0] + trs[1]) / 2.0, seed, (seed * 2.0 + trs[3]) / 3.0, {
+            let atr4 = (seed * 2.0 + trs[3]) / 3.0;
+            (atr4 * 2.0 + trs[4]) / 3.0
+        }];
+
+        let mut atr = Atr::new(3);
+        for (bar, expected) in bars.iter().zip(expected) {
+            let value = atr.push(bar);
+            assert!((value - expected).abs() < 1e-9, "expected {expected}, got {value}");
+        }
+    }
+
+    #[test]
+    fn first_bar_has_no_prior_close_so_true_range_is_just_its_own_range() {
+        let mut atr = Atr::new(3);
+        assert_eq!(atr.push(&bar(105.0, 100.0, 102.0)), 5.0);
+    }
+
+    #[test]
+    fn update_last_revises_without_moving_the_committed_previous_bar() {
+        let mut atr = Atr::new(3);
+        atr.push(&bar(105.0, 100.0, 102.0));
+        let live = atr.push(&bar(108.0, 102.0, 106.0));
+        let revised = atr.update_last(&bar(120.0, 102.0, 115.0));
+        assert_ne!(revised, live);
+
+        // Revising again with the bar `live` was originally computed from
+        // must reproduce `live` exactly -- proving the revision above
+        // never got folded into the permanently committed state.
+        let revised_again = atr.update_last(&bar(108.0, 102.0, 106.0));
+        assert_eq!(revised_again, live);
+    }
+}
@@ -0,0 +1,151 @@
+//! Bollinger Bands: a middle [`Sma`] band plus upper/lower bands offset by
+//! `num_std` standard deviations, built as three nodes the way
+//! [`super::hma`] builds Hull MA from a chain of [`Wma`](super::exec::Wma)
+//! nodes. The middle band is a normal, independently addressable node, so
+//! it can be shared as a dependency the same way any other node can.
+
+use super::exec::{Identity, Sma, StdDevBand};
+use super::{CombineOp, IndicatorGraph, NodeId};
+use crate::error::{HQuantError, Result};
+use crate::kline::Field;
+
+/// The three nodes making up a Bollinger Bands indicator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BollBands {
+    pub mid: NodeId,
+    pub upper: NodeId,
+    pub lower: NodeId,
+}
+
+/// Build the Bollinger Bands DAG chain under `base_name`, reading `field`
+/// from the bar. The middle band is registered under `base_name` itself
+/// (so it's the spec's primary output); `{base_name}_upper` and
+/// `{base_name}_lower` are independently addressable alongside it.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str, field: Field, period: usize, num_std: f64) -> Result<BollBands> {
+    if period < 2 {
+        return Err(HQuantError::InvalidSpec(base_name.to_string()));
+    }
+    let mid = graph.add_field_indicator(base_name, field, Box::new(Sma::new(period)))?;
+    let band = graph.add_field_indicator(&format!("{base_name}__band"), field, Box::new(StdDevBand::new(period, num_std)))?;
+    let upper = graph.add_combined_indicator(&format!("{base_name}_upper"), mid, band, CombineOp::Add, Box::new(super::exec::Identity))?;
+    let lower = graph.add_combined_indicator(&format!("{base_name}_lower"), mid, band, CombineOp::Sub, Box::new(super::exec::Identity))?;
+    Ok(BollBands { mid, upper, lower })
+}
+
+/// Build %B under `base_name`: an internal [`build`] chain (registered
+/// under `base_name__boll`-prefixed names, so it doesn't collide with a
+/// plain `BOLL` spec on the same field/period) feeding `(close - lower) /
+/// (upper - lower)`, via [`CombineOp::SafeRatio`] so a zero-width band (flat
+/// price, `num_std` of `0.0`) reads `0.0` rather than `NaN`. Returns the %B
+/// node id.
+pub fn build_percent_b(graph: &mut IndicatorGraph, base_name: &str, field: Field, period: usize, num_std: f64) -> Result<NodeId> {
+    let boll = build(graph, &format!("{base_name}__boll"), field, period, num_std)?;
+    let close = graph.add_field_indicator(&format!("{base_name}__close"), field, Box::new(Identity))?;
+    let range = graph.add_combined_indicator(&format!("{base_name}__range"), boll.upper, boll.lower, CombineOp::Sub, Box::new(Identity))?;
+    let close_minus_lower =
+        graph.add_combined_indicator(&format!("{base_name}__close_minus_lower"), close, boll.lower, CombineOp::Sub, Box::new(Identity))?;
+    graph.add_combined_indicator(base_name, close_minus_lower, range, CombineOp::SafeRatio, Box::new(Identity))
+}
+
+/// Build bandwidth under `base_name`: an internal [`build`] chain feeding
+/// `(upper - lower) / mid`, via [`CombineOp::SafeRatio`] so a zero midline
+/// reads `0.0` rather than `NaN`. Returns the bandwidth node id.
+pub fn build_bandwidth(graph: &mut IndicatorGraph, base_name: &str, field: Field, period: usize, num_std: f64) -> Result<NodeId> {
+    let boll = build(graph, &format!("{base_name}__boll"), field, period, num_std)?;
+    let range = graph.add_combined_indicator(&format!("{base_name}__range"), boll.upper, boll.lower, CombineOp::Sub, Box::new(Identity))?;
+    graph.add_combined_indicator(base_name, range, boll.mid, CombineOp::SafeRatio, Box::new(Identity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(close: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn upper_and_lower_bands_straddle_the_middle_band() {
+        let mut graph = IndicatorGraph::new();
+        let boll = build(&mut graph, "BOLL_20", Field::Close, 20, 2.0).unwrap();
+
+        for close in [10.0, 12.0, 9.0, 15.0, 11.0] {
+            graph.push_bar(&bar(close));
+        }
+
+        let mid = graph.get_from_end(boll.mid, 0).unwrap();
+        let upper = graph.get_from_end(boll.upper, 0).unwrap();
+        let lower = graph.get_from_end(boll.lower, 0).unwrap();
+        assert!(upper > mid);
+        assert!(lower < mid);
+        assert!((upper - mid) - (mid - lower) < 1e-9);
+    }
+
+    #[test]
+    fn rejects_a_period_below_two() {
+        let mut graph = IndicatorGraph::new();
+        assert!(build(&mut graph, "BOLL_1", Field::Close, 1, 2.0).is_err());
+    }
+
+    #[test]
+    fn percent_b_is_one_half_when_close_equals_the_midline() {
+        let mut graph = IndicatorGraph::new();
+        let percent_b = build_percent_b(&mut graph, "BOLL_PB_3", Field::Close, 3, 2.0).unwrap();
+
+        // Window [9, 11, 10]: mean is 10, matching the final close exactly,
+        // with a nonzero band width (the values aren't all equal).
+        for close in [9.0, 11.0, 10.0] {
+            graph.push_bar(&bar(close));
+        }
+
+        let value = graph.get_from_end(percent_b, 0).unwrap();
+        assert!((value - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn percent_b_matches_the_hand_computed_formula_from_the_underlying_bands() {
+        let mut graph = IndicatorGraph::new();
+        let boll = build(&mut graph, "BOLL_5", Field::Close, 5, 2.0).unwrap();
+        let percent_b = build_percent_b(&mut graph, "BOLL_PB_5", Field::Close, 5, 2.0).unwrap();
+
+        for close in [10.0, 12.0, 9.0, 15.0, 11.0] {
+            graph.push_bar(&bar(close));
+        }
+
+        let upper = graph.get_from_end(boll.upper, 0).unwrap();
+        let lower = graph.get_from_end(boll.lower, 0).unwrap();
+        let expected = (11.0 - lower) / (upper - lower);
+        assert!((graph.get_from_end(percent_b, 0).unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bandwidth_is_zero_over_a_perfectly_flat_series() {
+        let mut graph = IndicatorGraph::new();
+        let bandwidth = build_bandwidth(&mut graph, "BOLL_BW_5", Field::Close, 5, 2.0).unwrap();
+
+        for _ in 0..5 {
+            graph.push_bar(&bar(10.0));
+        }
+
+        assert_eq!(graph.get_from_end(bandwidth, 0).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn bandwidth_widens_as_volatility_increases() {
+        let mut graph = IndicatorGraph::new();
+        let bandwidth = build_bandwidth(&mut graph, "BOLL_BW_5", Field::Close, 5, 2.0).unwrap();
+
+        for close in [10.0, 10.0, 10.0, 10.0, 10.0] {
+            graph.push_bar(&bar(close));
+        }
+        let calm = graph.get_from_end(bandwidth, 0).unwrap();
+
+        for close in [5.0, 20.0, 2.0, 25.0, 1.0] {
+            graph.push_bar(&bar(close));
+        }
+        let volatile = graph.get_from_end(bandwidth, 0).unwrap();
+
+        assert!(volatile > calm);
+    }
+}
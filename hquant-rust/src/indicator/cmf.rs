@@ -0,0 +1,82 @@
+//! Chaikin Money Flow: `sum(mfv, period) / sum(volume, period)`, the same
+//! money flow volume behind [`super::adl`] but averaged over a rolling
+//! window instead of accumulated forever.
+//!
+//! `sum(mfv, period) / sum(volume, period)` is mathematically identical to
+//! `average(mfv, period) / average(volume, period)`, since both sides share
+//! the same `n` -- including during partial-window warm-up, where `n` is
+//! however many bars have been seen so far. That means CMF needs no new
+//! "rolling sum" building block: it reuses the existing [`super::exec::Sma`]
+//! over `mfv` and over `volume`, combined via [`CombineOp::SafeRatio`] (a
+//! zero-volume window reports `0.0` rather than `NaN`).
+
+use super::adl::build_money_flow_volume;
+use super::exec::{Identity, Sma};
+use super::{CombineOp, IndicatorGraph, NodeId};
+use crate::error::{HQuantError, Result};
+
+/// Build the CMF DAG chain under `base_name`, registering the shared
+/// money-flow-volume nodes and this indicator's own averaging nodes with
+/// `base_name__`-prefixed internal names. Returns the CMF node id.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str, period: usize) -> Result<NodeId> {
+    if period < 2 {
+        return Err(HQuantError::InvalidSpec(base_name.to_string()));
+    }
+    let (mfv, volume) = build_money_flow_volume(graph, base_name)?;
+    let mfv_avg = graph.add_chained_indicator(&format!("{base_name}__mfv_avg"), mfv, Box::new(Sma::new(period)))?;
+    let volume_avg = graph.add_chained_indicator(&format!("{base_name}__volume_avg"), volume, Box::new(Sma::new(period)))?;
+    graph.add_combined_indicator(base_name, mfv_avg, volume_avg, CombineOp::SafeRatio, Box::new(Identity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(high: f64, low: f64, close: f64, volume: f64) -> Bar {
+        Bar { ts: 0, open: close, high, low, close, volume }
+    }
+
+    #[test]
+    fn averages_money_flow_volume_over_the_window() {
+        let mut graph = IndicatorGraph::new();
+        let cmf = build(&mut graph, "CMF_2", 2).unwrap();
+
+        // Bar 1: clv = 1.0 (close at high), mfv = 100.0, volume = 100.0.
+        graph.push_bar(&bar(10.0, 8.0, 10.0, 100.0));
+        // Bar 2: clv = -1.0 (close at low), mfv = -50.0, volume = 50.0.
+        graph.push_bar(&bar(12.0, 9.0, 9.0, 50.0));
+
+        // sum(mfv, 2) / sum(volume, 2) = (100 - 50) / (100 + 50) = 1/3.
+        let value = graph.get_from_end(cmf, 0).unwrap();
+        assert!((value - 1.0 / 3.0).abs() < 1e-9, "expected 1/3, got {value}");
+    }
+
+    #[test]
+    fn rejects_a_period_below_two() {
+        let mut graph = IndicatorGraph::new();
+        assert!(build(&mut graph, "CMF_1", 1).is_err());
+    }
+
+    #[test]
+    fn update_last_reproduces_a_fresh_push() {
+        let mut a = IndicatorGraph::new();
+        let cmf_a = build(&mut a, "CMF_3", 3).unwrap();
+        let mut b = IndicatorGraph::new();
+        let cmf_b = build(&mut b, "CMF_3", 3).unwrap();
+
+        a.push_bar(&bar(10.0, 8.0, 9.0, 100.0));
+        b.push_bar(&bar(10.0, 8.0, 9.0, 100.0));
+
+        // `a` opens the new bar with a placeholder, then revises it in
+        // place as the candle ticks toward its real close.
+        a.push_bar(&bar(11.0, 9.0, 9.5, 40.0));
+        a.update_last(&bar(12.0, 9.0, 11.0, 70.0));
+        // `b` sees the real bar directly.
+        b.push_bar(&bar(12.0, 9.0, 11.0, 70.0));
+
+        let va = a.get_from_end(cmf_a, 0).unwrap();
+        let vb = b.get_from_end(cmf_b, 0).unwrap();
+        assert!((va - vb).abs() < 1e-9, "expected {va} to match fresh push {vb}");
+    }
+}
@@ -0,0 +1,107 @@
+//! Chande Momentum Oscillator: `100 * (sumUp - sumDown) / (sumUp +
+//! sumDown)` over a rolling window of close-to-close moves.
+//!
+//! Reuses [`super::rsi`]'s exact momentum/gain/loss chain -- the only
+//! difference is the rolling window is a plain trailing sum rather than any
+//! of RSI's smoothing choices, and the combine at the end is `100 * (up -
+//! down) / (up + down)` instead of `100 * up / (up + down)`. An [`Sma`] over
+//! the same window is proportional to the sum by the constant `period`,
+//! which cancels out of this ratio, so there's no need for a separate
+//! rolling-sum exec.
+
+use super::exec::{NegativePart, PositivePart, Sma};
+use super::{CombineOp, IndicatorGraph, NodeId};
+use crate::error::Result;
+use crate::kline::Field;
+
+/// Build the CMO DAG chain under `base_name`, registering the intermediate
+/// momentum/gain/loss/sum nodes with `base_name__`-prefixed internal names
+/// the same way [`super::rsi::build`] does. Returns the CMO node id.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str, period: usize) -> Result<NodeId> {
+    assert!(period > 0, "CMO period must be > 0");
+
+    let mom = graph.add_field_indicator(&format!("{base_name}__mom"), Field::Close, Box::new(super::exec::Momentum::new()))?;
+    let gain = graph.add_chained_indicator(&format!("{base_name}__gain"), mom, Box::new(PositivePart))?;
+    let loss = graph.add_chained_indicator(&format!("{base_name}__loss"), mom, Box::new(NegativePart))?;
+    let sum_up = graph.add_chained_indicator(&format!("{base_name}__sum_up"), gain, Box::new(Sma::new(period)))?;
+    let sum_down = graph.add_chained_indicator(&format!("{base_name}__sum_down"), loss, Box::new(Sma::new(period)))?;
+
+    graph.add_combined_indicator(base_name, sum_up, sum_down, CombineOp::PercentOfDifference, Box::new(super::exec::Identity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(close: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn cmo_is_100_when_every_move_is_a_gain() {
+        let mut graph = IndicatorGraph::new();
+        let cmo = build(&mut graph, "CMO_5", 5).unwrap();
+
+        let mut price = 100.0;
+        for _ in 0..10 {
+            graph.push_bar(&bar(price));
+            price += 1.0;
+        }
+
+        assert!((graph.get_from_end(cmo, 0).unwrap() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cmo_is_negative_100_when_every_move_is_a_loss() {
+        let mut graph = IndicatorGraph::new();
+        let cmo = build(&mut graph, "CMO_5", 5).unwrap();
+
+        let mut price = 100.0;
+        for _ in 0..10 {
+            graph.push_bar(&bar(price));
+            price -= 1.0;
+        }
+
+        assert!((graph.get_from_end(cmo, 0).unwrap() - (-100.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cmo_stays_within_bounds_over_a_mixed_up_down_series() {
+        let mut graph = IndicatorGraph::new();
+        let cmo = build(&mut graph, "CMO_5", 5).unwrap();
+
+        let prices = [100.0, 102.0, 101.0, 105.0, 103.0, 108.0, 104.0, 110.0, 98.0, 112.0, 90.0, 95.0];
+        for &p in &prices {
+            graph.push_bar(&bar(p));
+            let value = graph.get_from_end(cmo, 0).unwrap();
+            assert!((-100.0..=100.0).contains(&value), "CMO out of bounds: {value}");
+        }
+    }
+
+    #[test]
+    fn update_last_reproduces_a_fresh_push() {
+        let mut a = IndicatorGraph::new();
+        let cmo_a = build(&mut a, "CMO_5", 5).unwrap();
+        let mut b = IndicatorGraph::new();
+        let cmo_b = build(&mut b, "CMO_5", 5).unwrap();
+
+        let prices = [100.0, 101.0, 99.0, 105.0, 110.0, 108.0];
+        for &p in &prices[..prices.len() - 1] {
+            a.push_bar(&bar(p));
+            b.push_bar(&bar(p));
+        }
+        let last = *prices.last().unwrap();
+
+        // `a` opens the new bar with a placeholder, then revises it in
+        // place as the candle ticks toward its real close.
+        a.push_bar(&bar(last - 1.0));
+        a.update_last(&bar(last));
+        // `b` sees the real close directly.
+        b.push_bar(&bar(last));
+
+        let va = a.get_from_end(cmo_a, 0).unwrap();
+        let vb = b.get_from_end(cmo_b, 0).unwrap();
+        assert!((va - vb).abs() < 1e-9, "expected {va} to match fresh push {vb}");
+    }
+}
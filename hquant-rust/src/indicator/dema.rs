@@ -0,0 +1,60 @@
+//! Double Exponential Moving Average: `2*EMA - EMA(EMA)`, built the same way
+//! [`super::hma`] chains `Wma` nodes -- `EMA(EMA)` lags the raw EMA, so
+//! subtracting it back out cancels most of that lag rather than smoothing
+//! harder.
+
+use super::exec::{Ema, Identity};
+use super::{CombineOp, IndicatorGraph, NodeId};
+use crate::error::{HQuantError, Result};
+use crate::kline::Field;
+
+/// Build the DEMA DAG chain under `base_name`, reading `field` from the bar.
+/// Sub-indicators are registered under `base_name__`-prefixed internal
+/// names, the same convention [`super::hma::build`] uses. Returns the DEMA
+/// node id.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str, field: Field, period: usize) -> Result<NodeId> {
+    if period == 0 {
+        return Err(HQuantError::InvalidSpec(base_name.to_string()));
+    }
+    let ema1 = graph.add_field_indicator(&format!("{base_name}__ema1"), field, Box::new(Ema::new(period)))?;
+    let ema2 = graph.add_chained_indicator(&format!("{base_name}__ema2"), ema1, Box::new(Ema::new(period)))?;
+    graph.add_combined_indicator(base_name, ema1, ema2, CombineOp::DoubleMinus, Box::new(Identity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicator::exec::Ema as EmaExec;
+    use crate::kline::Bar;
+
+    fn bar(close: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn dema_reacts_faster_than_a_plain_ema_to_a_step_input() {
+        let mut graph = IndicatorGraph::new();
+        let dema = build(&mut graph, "DEMA_10", Field::Close, 10).unwrap();
+        let plain_ema = graph.add_field_indicator("EMA_10", Field::Close, Box::new(EmaExec::new(10))).unwrap();
+
+        for _ in 0..20 {
+            graph.push_bar(&bar(100.0));
+        }
+        for _ in 0..3 {
+            graph.push_bar(&bar(120.0));
+        }
+
+        let dema_value = graph.get_from_end(dema, 0).unwrap();
+        let ema_value = graph.get_from_end(plain_ema, 0).unwrap();
+        assert!(
+            (dema_value - 120.0).abs() < (ema_value - 120.0).abs(),
+            "DEMA ({dema_value}) should sit closer to the new level than EMA ({ema_value}) after a step"
+        );
+    }
+
+    #[test]
+    fn rejects_a_zero_period() {
+        let mut graph = IndicatorGraph::new();
+        assert!(build(&mut graph, "DEMA_0", Field::Close, 0).is_err());
+    }
+}
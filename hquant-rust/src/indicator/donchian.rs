@@ -0,0 +1,80 @@
+//! Donchian Channel: the highest high and lowest low over a rolling window,
+//! plus their midline, built as three nodes the way [`super::boll`] builds
+//! Bollinger Bands from a chain of nodes. Unlike ADX (see
+//! [`super::adx`]), the upper and lower bands each only need one field
+//! (`High`/`Low`) in isolation, so this fits the graph's one-field-per-node
+//! contract directly instead of needing a standalone transform.
+
+use super::exec::{RollingMax, RollingMin};
+use super::{CombineOp, IndicatorGraph, NodeId};
+use crate::error::{HQuantError, Result};
+use crate::kline::Field;
+
+/// The three nodes making up a Donchian Channel indicator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DonchianChannel {
+    pub upper: NodeId,
+    pub lower: NodeId,
+    pub mid: NodeId,
+}
+
+/// Build the Donchian Channel DAG chain under `base_name`: `{base_name}_upper`
+/// is the highest high, `{base_name}_lower` the lowest low, each over the
+/// last `period` bars via [`super::exec::RollingMax`]/[`RollingMin`] (O(1)
+/// amortized, unlike re-scanning the window every bar). The midline is
+/// registered under `base_name` itself, so it's the spec's primary output.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str, period: usize) -> Result<DonchianChannel> {
+    if period < 2 {
+        return Err(HQuantError::InvalidSpec(base_name.to_string()));
+    }
+    let upper = graph.add_field_indicator(&format!("{base_name}_upper"), Field::High, Box::new(RollingMax::new(period)))?;
+    let lower = graph.add_field_indicator(&format!("{base_name}_lower"), Field::Low, Box::new(RollingMin::new(period)))?;
+    let mid = graph.add_combined_indicator(base_name, upper, lower, CombineOp::Average, Box::new(super::exec::Identity))?;
+    Ok(DonchianChannel { upper, lower, mid })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(high: f64, low: f64, close: f64) -> Bar {
+        Bar { ts: 0, open: close, high, low, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn tracks_the_highest_high_and_lowest_low_over_the_window() {
+        let mut graph = IndicatorGraph::new();
+        let dc = build(&mut graph, "DONCHIAN_3", 3).unwrap();
+
+        for (high, low, close) in [(10.0, 8.0, 9.0), (12.0, 9.0, 11.0), (11.0, 7.0, 9.0), (10.0, 8.5, 9.5)] {
+            graph.push_bar(&bar(high, low, close));
+        }
+
+        // Window is the last 3 bars: highs [12, 11, 10], lows [9, 7, 8.5].
+        assert_eq!(graph.get_from_end(dc.upper, 0).unwrap(), 12.0);
+        assert_eq!(graph.get_from_end(dc.lower, 0).unwrap(), 7.0);
+        assert!((graph.get_from_end(dc.mid, 0).unwrap() - (12.0 + 7.0) / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn update_last_recomputes_the_window_when_the_current_bars_extreme_changes() {
+        let mut graph = IndicatorGraph::new();
+        let dc = build(&mut graph, "DONCHIAN_3", 3).unwrap();
+
+        graph.push_bar(&bar(10.0, 8.0, 9.0));
+        graph.push_bar(&bar(11.0, 9.0, 10.0));
+        graph.push_bar(&bar(9.0, 7.5, 8.5));
+        assert_eq!(graph.get_from_end(dc.upper, 0).unwrap(), 11.0);
+
+        // Revise the last bar's high upward past the current window max.
+        graph.update_last(&bar(15.0, 7.5, 8.5));
+        assert_eq!(graph.get_from_end(dc.upper, 0).unwrap(), 15.0);
+    }
+
+    #[test]
+    fn rejects_a_period_below_two() {
+        let mut graph = IndicatorGraph::new();
+        assert!(build(&mut graph, "DONCHIAN_1", 1).is_err());
+    }
+}
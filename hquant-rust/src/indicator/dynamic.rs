@@ -0,0 +1,110 @@
+//! User-supplied closures computed over the raw bar history rather than
+//! the DAG's per-bar scalar `IndicatorExec` interface, for one-off
+//! computations that need to look back over several bars at once.
+
+use crate::kline::KlineBuffer;
+
+/// A read-only, optionally forming-bar-excluding view over a
+/// [`KlineBuffer`], handed to a [`DynamicIndicator`]'s closure.
+///
+/// Closures are called during `update_last` too (the forming bar), so a
+/// closure that reads `close_from_end(0)` in the default (full) view can
+/// unknowingly read data that will still change before the bar closes —
+/// classic look-ahead bias. A closed-bars-only view hides that bar
+/// entirely, at the cost of the closure never seeing the bar currently
+/// forming.
+pub struct KlineView<'a> {
+    bars: &'a KlineBuffer,
+    visible_len: usize,
+}
+
+impl<'a> KlineView<'a> {
+    fn skip(&self) -> usize {
+        self.bars.len() - self.visible_len
+    }
+
+    pub fn len(&self) -> usize {
+        self.visible_len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.visible_len == 0
+    }
+
+    /// Close price `n` visible bars back from the most recent visible one
+    /// (`0` = most recent visible).
+    pub fn close_from_end(&self, n: usize) -> Option<f64> {
+        if n >= self.visible_len {
+            return None;
+        }
+        self.bars.close_column().get_from_end(n + self.skip())
+    }
+
+    pub fn last_close(&self) -> Option<f64> {
+        self.close_from_end(0)
+    }
+}
+
+/// A custom indicator backed by a closure over a [`KlineView`] instead of
+/// the DAG's scalar `IndicatorExec`.
+pub struct DynamicIndicator {
+    closure: Box<dyn Fn(&KlineView) -> f64 + Send>,
+    /// When `true`, the closure's view excludes the most recent bar,
+    /// guarding against look-ahead into a bar that's still forming.
+    closed_bars_only: bool,
+}
+
+impl std::fmt::Debug for DynamicIndicator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynamicIndicator").field("closed_bars_only", &self.closed_bars_only).finish()
+    }
+}
+
+impl DynamicIndicator {
+    pub fn new(closed_bars_only: bool, closure: impl Fn(&KlineView) -> f64 + Send + 'static) -> Self {
+        Self { closure: Box::new(closure), closed_bars_only }
+    }
+
+    pub fn evaluate(&self, bars: &KlineBuffer) -> f64 {
+        let total = bars.len();
+        let visible_len = if self.closed_bars_only { total.saturating_sub(1) } else { total };
+        let view = KlineView { bars, visible_len };
+        (self.closure)(&view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(close: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn closed_bars_only_excludes_the_forming_bars_revisions() {
+        let mut bars = KlineBuffer::new(16);
+        bars.push(&bar(1.0));
+        bars.push(&bar(2.0));
+        // The second bar is still forming; revise its close.
+        bars.update_last(&bar(2.5));
+
+        let full = DynamicIndicator::new(false, |v: &KlineView| v.last_close().unwrap());
+        assert_eq!(full.evaluate(&bars), 2.5);
+
+        let closed_only = DynamicIndicator::new(true, |v: &KlineView| v.last_close().unwrap());
+        assert_eq!(closed_only.evaluate(&bars), 1.0);
+    }
+
+    #[test]
+    fn closed_bars_only_sees_a_bar_once_the_next_one_opens() {
+        let mut bars = KlineBuffer::new(16);
+        bars.push(&bar(1.0));
+        bars.push(&bar(2.0));
+        bars.push(&bar(3.0)); // opens a new bar, closing bar 2.0.
+
+        let closed_only = DynamicIndicator::new(true, |v: &KlineView| v.last_close().unwrap());
+        assert_eq!(closed_only.evaluate(&bars), 2.0);
+    }
+}
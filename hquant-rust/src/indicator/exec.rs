@@ -0,0 +1,1218 @@
+//! Per-node incremental computations. Each [`IndicatorExec`] consumes one
+//! scalar per bar and produces one scalar output, keeping whatever state it
+//! needs to do that incrementally rather than recomputing over the whole
+//! window on every bar.
+
+use crate::common::{F64RingBuffer, RollingExtrema};
+use std::collections::VecDeque;
+
+/// Incremental transform driving a single [`super::Node`].
+///
+/// `push` commits the previous bar's output permanently before computing
+/// the new one; `update_last` recomputes the current bar's output against
+/// the same previously-committed state, so a still-forming bar can be
+/// revised repeatedly without corrupting history.
+pub trait IndicatorExec: std::fmt::Debug + Send {
+    fn push(&mut self, x: f64) -> f64;
+    fn update_last(&mut self, x: f64) -> f64;
+
+    /// Drop every accumulated value (rolling windows, running sums,
+    /// committed/pending state) back to what a freshly constructed exec of
+    /// the same concrete type and parameters would start with, without
+    /// losing those parameters themselves -- e.g. an [`Sma`]'s `period`
+    /// survives, its `window`/`sum` don't. See
+    /// [`crate::engine::HQuant::reset`], the caller that needs this to
+    /// reuse a whole indicator graph across symbols.
+    fn reset(&mut self);
+
+    /// How many bars must have been pushed before this node's output means
+    /// anything beyond "not enough history yet" -- see
+    /// [`super::IndicatorGraph::is_ready`]. Most execs never output `NaN`
+    /// even on the very first bar (an [`Sma`] just averages whatever it has
+    /// so far), so this is a separate, coarser notion of readiness than
+    /// "not `NaN`": it's the window a caller would expect fully filled
+    /// before trusting the number. Defaults to `1`, i.e. ready as soon as
+    /// anything has been pushed at all.
+    fn min_periods(&self) -> usize {
+        1
+    }
+
+    /// Serialize this node's internal accumulators (not its output
+    /// history -- [`super::IndicatorGraph`]'s output [`super::CircularColumn`]
+    /// covers that separately) to a blob a matching `deserialize_state`
+    /// call can restore. Used for checkpoint/resume; see
+    /// [`crate::engine::HQuant::save_state`].
+    fn serialize_state(&self) -> Vec<u8>;
+
+    /// Restore state written by `serialize_state`. `bytes` must have come
+    /// from an exec of the same concrete type built with the same
+    /// parameters (e.g. an `Sma::new(20)`'s blob into another
+    /// `Sma::new(20)`) -- a blob carries no type or parameter tag of its
+    /// own, so the caller is responsible for rebuilding the same graph
+    /// shape first (see [`crate::indicator::IndicatorGraph::restore_node`]).
+    fn deserialize_state(&mut self, bytes: &[u8]) -> crate::error::Result<()>;
+}
+
+/// Minimal little-endian byte reader with bounds checking, local to
+/// [`IndicatorExec`] state blobs -- mirrors [`crate::kline::KlineFrame`]'s
+/// own local cursor rather than sharing one, since the two formats have
+/// nothing else in common.
+struct StateCursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> StateCursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> crate::error::Result<&'a [u8]> {
+        let slice = self
+            .data
+            .get(self.pos..self.pos + n)
+            .ok_or_else(|| crate::error::HQuantError::InvalidSpec("truncated indicator state blob".to_string()))?;
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn read_u8(&mut self) -> crate::error::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u64(&mut self) -> crate::error::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn read_f64(&mut self) -> crate::error::Result<f64> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn read_opt_f64(&mut self) -> crate::error::Result<Option<f64>> {
+        Ok(if self.read_u8()? == 1 { Some(self.read_f64()?) } else { None })
+    }
+
+    fn read_f64_seq(&mut self) -> crate::error::Result<Vec<f64>> {
+        let n = self.read_u64()? as usize;
+        (0..n).map(|_| self.read_f64()).collect()
+    }
+}
+
+fn encode_f64(buf: &mut Vec<u8>, x: f64) {
+    buf.extend_from_slice(&x.to_le_bytes());
+}
+
+fn encode_opt_f64(buf: &mut Vec<u8>, x: Option<f64>) {
+    match x {
+        Some(v) => {
+            buf.push(1);
+            encode_f64(buf, v);
+        }
+        None => buf.push(0),
+    }
+}
+
+fn encode_f64_seq(buf: &mut Vec<u8>, xs: impl ExactSizeIterator<Item = f64>) {
+    buf.extend_from_slice(&(xs.len() as u64).to_le_bytes());
+    for x in xs {
+        encode_f64(buf, x);
+    }
+}
+
+/// Simple moving average over a fixed window.
+///
+/// The rolling window and sum live entirely in `window`/`sum` below,
+/// independent of any node's output [`super::CircularColumn`] (sized by
+/// [`super::IndicatorGraph`]'s `history` capacity). So there's no shared
+/// storage for the graph's output ring to overwrite out from under this
+/// exec's own bookkeeping, even once `history` wraps mid-warmup; see
+/// `sma_matches_brute_force_when_graph_history_capacity_wraps_at_period_plus_one`
+/// in `super::tests`.
+#[derive(Debug, Clone)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+    /// The value evicted or added when `update_last` last ran, so it can be
+    /// undone before applying the next revision.
+    pending: Option<f64>,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "SMA period must be > 0");
+        Self {
+            period,
+            window: VecDeque::with_capacity(period),
+            sum: 0.0,
+            pending: None,
+        }
+    }
+
+    fn value(&self) -> f64 {
+        if self.window.is_empty() {
+            f64::NAN
+        } else {
+            self.sum / self.window.len() as f64
+        }
+    }
+}
+
+impl IndicatorExec for Sma {
+    fn min_periods(&self) -> usize {
+        self.period
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.sum = 0.0;
+        self.pending = None;
+    }
+
+    fn push(&mut self, x: f64) -> f64 {
+        self.pending = None;
+        self.window.push_back(x);
+        self.sum += x;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap();
+        }
+        self.value()
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        if let Some(prev) = self.pending.take() {
+            self.sum -= prev;
+            *self.window.back_mut().unwrap() = x;
+        } else if let Some(last) = self.window.back_mut() {
+            self.sum -= *last;
+            *last = x;
+        } else {
+            self.window.push_back(x);
+        }
+        self.sum += x;
+        self.pending = Some(x);
+        self.value()
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_f64_seq(&mut buf, self.window.iter().copied());
+        encode_f64(&mut buf, self.sum);
+        encode_opt_f64(&mut buf, self.pending);
+        buf
+    }
+
+    fn deserialize_state(&mut self, bytes: &[u8]) -> crate::error::Result<()> {
+        let mut cursor = StateCursor::new(bytes);
+        self.window = cursor.read_f64_seq()?.into();
+        self.sum = cursor.read_f64()?;
+        self.pending = cursor.read_opt_f64()?;
+        Ok(())
+    }
+}
+
+/// Exponential moving average, `alpha = 2 / (period + 1)`.
+#[derive(Debug, Clone)]
+pub struct Ema {
+    alpha: f64,
+    /// EMA value as of the end of the last fully closed bar.
+    committed: Option<f64>,
+    /// Output for the bar currently being built, if any.
+    current: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "EMA period must be > 0");
+        Self::with_alpha(2.0 / (period as f64 + 1.0))
+    }
+
+    pub fn with_alpha(alpha: f64) -> Self {
+        Self {
+            alpha,
+            committed: None,
+            current: None,
+        }
+    }
+
+    fn compute(&self, x: f64) -> f64 {
+        match self.committed {
+            Some(prev) => self.alpha * x + (1.0 - self.alpha) * prev,
+            None => x,
+        }
+    }
+}
+
+impl IndicatorExec for Ema {
+    fn reset(&mut self) {
+        self.committed = None;
+        self.current = None;
+    }
+
+    fn push(&mut self, x: f64) -> f64 {
+        // A NaN output (e.g. from an upstream 0/0 during warm-up) must not
+        // become the seed for every subsequent bar, or the whole series
+        // stays NaN forever; keep the last good committed value instead.
+        if let Some(current) = self.current.take() {
+            if !current.is_nan() {
+                self.committed = Some(current);
+            }
+        }
+        let value = self.compute(x);
+        self.current = Some(value);
+        value
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        let value = self.compute(x);
+        self.current = Some(value);
+        value
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_opt_f64(&mut buf, self.committed);
+        encode_opt_f64(&mut buf, self.current);
+        buf
+    }
+
+    fn deserialize_state(&mut self, bytes: &[u8]) -> crate::error::Result<()> {
+        let mut cursor = StateCursor::new(bytes);
+        self.committed = cursor.read_opt_f64()?;
+        self.current = cursor.read_opt_f64()?;
+        Ok(())
+    }
+}
+
+/// Linearly weighted moving average: the most recent value in the window
+/// gets weight `period`, the oldest gets weight `1`. During warm-up (fewer
+/// than `period` values seen so far) the weights still run `1..=len`, so
+/// the divisor is `len*(len+1)/2` rather than the full-window one.
+#[derive(Debug, Clone)]
+pub struct Wma {
+    period: usize,
+    window: VecDeque<f64>,
+    pending: Option<f64>,
+}
+
+impl Wma {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "WMA period must be > 0");
+        Self {
+            period,
+            window: VecDeque::with_capacity(period),
+            pending: None,
+        }
+    }
+
+    fn value(&self) -> f64 {
+        if self.window.is_empty() {
+            return f64::NAN;
+        }
+        let n = self.window.len();
+        let divisor = (n * (n + 1)) as f64 / 2.0;
+        let weighted_sum: f64 = self
+            .window
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| (i + 1) as f64 * v)
+            .sum();
+        weighted_sum / divisor
+    }
+}
+
+impl IndicatorExec for Wma {
+    fn min_periods(&self) -> usize {
+        self.period
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.pending = None;
+    }
+
+    fn push(&mut self, x: f64) -> f64 {
+        self.pending = None;
+        self.window.push_back(x);
+        if self.window.len() > self.period {
+            self.window.pop_front();
+        }
+        self.value()
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        if self.pending.take().is_some() || !self.window.is_empty() {
+            *self.window.back_mut().unwrap() = x;
+        } else {
+            self.window.push_back(x);
+        }
+        self.pending = Some(x);
+        self.value()
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_f64_seq(&mut buf, self.window.iter().copied());
+        encode_opt_f64(&mut buf, self.pending);
+        buf
+    }
+
+    fn deserialize_state(&mut self, bytes: &[u8]) -> crate::error::Result<()> {
+        let mut cursor = StateCursor::new(bytes);
+        self.window = cursor.read_f64_seq()?.into();
+        self.pending = cursor.read_opt_f64()?;
+        Ok(())
+    }
+}
+
+/// Kaufman Adaptive Moving Average: an EMA whose smoothing constant is
+/// recomputed every bar from the *efficiency ratio* -- net change over
+/// `period` bars divided by the sum of absolute bar-to-bar changes over the
+/// same window -- so it hugs price closely in a clean trend (ratio near
+/// `1`) and flattens out in chop (ratio near `0`).
+#[derive(Debug, Clone)]
+pub struct Kama {
+    period: usize,
+    fast_alpha: f64,
+    slow_alpha: f64,
+    /// Last `period + 1` raw inputs, oldest first -- one more than `period`
+    /// so the windowed net change and absolute-change sum can both be read
+    /// directly off it. Recomputed from the window every bar rather than
+    /// tracked as two incrementally-evicting sums, the same tradeoff
+    /// [`Wma`] makes for its weighted sum.
+    window: VecDeque<f64>,
+    /// KAMA value as of the end of the last fully closed bar.
+    committed: Option<f64>,
+    /// Output for the bar currently being built, if any.
+    current: Option<f64>,
+}
+
+impl Kama {
+    pub fn new(period: usize, fast_period: usize, slow_period: usize) -> Self {
+        assert!(period > 0, "KAMA efficiency-ratio period must be > 0");
+        assert!(fast_period > 0, "KAMA fast period must be > 0");
+        assert!(slow_period > 0, "KAMA slow period must be > 0");
+        Self {
+            period,
+            fast_alpha: 2.0 / (fast_period as f64 + 1.0),
+            slow_alpha: 2.0 / (slow_period as f64 + 1.0),
+            window: VecDeque::with_capacity(period + 1),
+            committed: None,
+            current: None,
+        }
+    }
+
+    /// Net change over the window divided by the sum of absolute
+    /// bar-to-bar changes, in `[0, 1]`. `0.0` once there isn't a full
+    /// window yet, or the window is perfectly flat (nothing to divide by)
+    /// -- the same "degenerate input yields the calm-market answer" choice
+    /// [`Wma`]/[`Sma`] make by outputting `NaN` only once there's truly no
+    /// data at all.
+    fn efficiency_ratio(&self) -> f64 {
+        if self.window.len() <= self.period {
+            return 0.0;
+        }
+        let net_change = (self.window.back().unwrap() - self.window.front().unwrap()).abs();
+        let noise: f64 = self
+            .window
+            .iter()
+            .zip(self.window.iter().skip(1))
+            .map(|(a, b)| (b - a).abs())
+            .sum();
+        if noise == 0.0 {
+            0.0
+        } else {
+            net_change / noise
+        }
+    }
+
+    fn smoothing_constant(&self) -> f64 {
+        let er = self.efficiency_ratio();
+        (er * (self.fast_alpha - self.slow_alpha) + self.slow_alpha).powi(2)
+    }
+
+    fn compute(&self, x: f64) -> f64 {
+        match self.committed {
+            Some(prev) => prev + self.smoothing_constant() * (x - prev),
+            None => x,
+        }
+    }
+}
+
+impl IndicatorExec for Kama {
+    fn min_periods(&self) -> usize {
+        self.period + 1
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.committed = None;
+        self.current = None;
+    }
+
+    fn push(&mut self, x: f64) -> f64 {
+        if let Some(current) = self.current.take() {
+            if !current.is_nan() {
+                self.committed = Some(current);
+            }
+        }
+        self.window.push_back(x);
+        if self.window.len() > self.period + 1 {
+            self.window.pop_front();
+        }
+        let value = self.compute(x);
+        self.current = Some(value);
+        value
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        if self.window.is_empty() {
+            self.window.push_back(x);
+        } else {
+            *self.window.back_mut().unwrap() = x;
+        }
+        let value = self.compute(x);
+        self.current = Some(value);
+        value
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_f64_seq(&mut buf, self.window.iter().copied());
+        encode_opt_f64(&mut buf, self.committed);
+        encode_opt_f64(&mut buf, self.current);
+        buf
+    }
+
+    fn deserialize_state(&mut self, bytes: &[u8]) -> crate::error::Result<()> {
+        let mut cursor = StateCursor::new(bytes);
+        self.window = cursor.read_f64_seq()?.into();
+        self.committed = cursor.read_opt_f64()?;
+        self.current = cursor.read_opt_f64()?;
+        Ok(())
+    }
+}
+
+/// `k` standard deviations over a rolling window, the distance
+/// [`super::boll::build`] adds to and subtracts from the middle band to get
+/// the upper/lower Bollinger Bands.
+///
+/// Delegates entirely to [`F64RingBuffer`]'s Welford-based running
+/// variance rather than tracking its own `sum`/`sum_sq`, so it's already
+/// immune to the catastrophic-cancellation underflow that a naive
+/// `sum_sq/n - mean^2` formula suffers on high-priced assets; see
+/// `does_not_underflow_to_negative_variance_on_prices_around_1e6` below.
+#[derive(Debug, Clone)]
+pub struct StdDevBand {
+    k: f64,
+    window: F64RingBuffer,
+}
+
+impl StdDevBand {
+    pub fn new(period: usize, k: f64) -> Self {
+        Self { k, window: F64RingBuffer::new(period) }
+    }
+
+    fn value(&self) -> f64 {
+        self.k * self.window.std_dev()
+    }
+}
+
+impl IndicatorExec for StdDevBand {
+    fn min_periods(&self) -> usize {
+        self.window.capacity()
+    }
+
+    fn reset(&mut self) {
+        self.window = F64RingBuffer::new(self.window.capacity());
+    }
+
+    fn push(&mut self, x: f64) -> f64 {
+        self.window.push(x);
+        self.value()
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        self.window.update_last(x);
+        self.value()
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_f64_seq(&mut buf, self.window.to_vec().into_iter());
+        buf
+    }
+
+    fn deserialize_state(&mut self, bytes: &[u8]) -> crate::error::Result<()> {
+        let mut cursor = StateCursor::new(bytes);
+        let values = cursor.read_f64_seq()?;
+        let mut window = F64RingBuffer::new(self.window.capacity());
+        for x in values {
+            window.push(x);
+        }
+        self.window = window;
+        Ok(())
+    }
+}
+
+/// How many standard deviations `x` sits from the rolling mean over a
+/// window -- `0.0` while the window has no variance to measure against
+/// (e.g. a flat spread, or still warming up on its very first sample),
+/// the same "nothing to divide by" guard [`StdDevBand`] and most
+/// [`super::CombineOp`]s use.
+///
+/// Delegates to [`F64RingBuffer`] for the same catastrophic-cancellation
+/// reason [`StdDevBand`] does.
+#[derive(Debug, Clone)]
+pub struct Zscore {
+    window: F64RingBuffer,
+    last: f64,
+}
+
+impl Zscore {
+    pub fn new(period: usize) -> Self {
+        Self { window: F64RingBuffer::new(period), last: f64::NAN }
+    }
+
+    fn value(&self) -> f64 {
+        let std_dev = self.window.std_dev();
+        if std_dev == 0.0 {
+            0.0
+        } else {
+            (self.last - self.window.mean()) / std_dev
+        }
+    }
+}
+
+impl IndicatorExec for Zscore {
+    fn min_periods(&self) -> usize {
+        self.window.capacity()
+    }
+
+    fn reset(&mut self) {
+        self.window = F64RingBuffer::new(self.window.capacity());
+        self.last = f64::NAN;
+    }
+
+    fn push(&mut self, x: f64) -> f64 {
+        self.window.push(x);
+        self.last = x;
+        self.value()
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        self.window.update_last(x);
+        self.last = x;
+        self.value()
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_f64_seq(&mut buf, self.window.to_vec().into_iter());
+        encode_opt_f64(&mut buf, if self.last.is_nan() { None } else { Some(self.last) });
+        buf
+    }
+
+    fn deserialize_state(&mut self, bytes: &[u8]) -> crate::error::Result<()> {
+        let mut cursor = StateCursor::new(bytes);
+        let values = cursor.read_f64_seq()?;
+        let mut window = F64RingBuffer::new(self.window.capacity());
+        for x in values {
+            window.push(x);
+        }
+        self.window = window;
+        self.last = cursor.read_opt_f64()?.unwrap_or(f64::NAN);
+        Ok(())
+    }
+}
+
+/// Highest value over a rolling window, amortized O(1) via
+/// [`RollingExtrema`]'s monotonic deque -- the upper band behind
+/// [`super::donchian`].
+#[derive(Debug, Clone)]
+pub struct RollingMax {
+    extrema: RollingExtrema,
+}
+
+impl RollingMax {
+    pub fn new(period: usize) -> Self {
+        Self { extrema: RollingExtrema::new(period) }
+    }
+}
+
+impl IndicatorExec for RollingMax {
+    fn min_periods(&self) -> usize {
+        self.extrema.capacity()
+    }
+
+    fn reset(&mut self) {
+        self.extrema = RollingExtrema::new(self.extrema.capacity());
+    }
+
+    fn push(&mut self, x: f64) -> f64 {
+        self.extrema.push(x);
+        self.extrema.max()
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        self.extrema.update_last(x);
+        self.extrema.max()
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_f64_seq(&mut buf, self.extrema.to_vec().into_iter());
+        buf
+    }
+
+    fn deserialize_state(&mut self, bytes: &[u8]) -> crate::error::Result<()> {
+        let mut cursor = StateCursor::new(bytes);
+        let values = cursor.read_f64_seq()?;
+        let mut extrema = RollingExtrema::new(self.extrema.capacity());
+        for x in values {
+            extrema.push(x);
+        }
+        self.extrema = extrema;
+        Ok(())
+    }
+}
+
+/// Lowest value over a rolling window; the mirror of [`RollingMax`] for the
+/// lower band behind [`super::donchian`].
+#[derive(Debug, Clone)]
+pub struct RollingMin {
+    extrema: RollingExtrema,
+}
+
+impl RollingMin {
+    pub fn new(period: usize) -> Self {
+        Self { extrema: RollingExtrema::new(period) }
+    }
+}
+
+impl IndicatorExec for RollingMin {
+    fn min_periods(&self) -> usize {
+        self.extrema.capacity()
+    }
+
+    fn reset(&mut self) {
+        self.extrema = RollingExtrema::new(self.extrema.capacity());
+    }
+
+    fn push(&mut self, x: f64) -> f64 {
+        self.extrema.push(x);
+        self.extrema.min()
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        self.extrema.update_last(x);
+        self.extrema.min()
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_f64_seq(&mut buf, self.extrema.to_vec().into_iter());
+        buf
+    }
+
+    fn deserialize_state(&mut self, bytes: &[u8]) -> crate::error::Result<()> {
+        let mut cursor = StateCursor::new(bytes);
+        let values = cursor.read_f64_seq()?;
+        let mut extrema = RollingExtrema::new(self.extrema.capacity());
+        for x in values {
+            extrema.push(x);
+        }
+        self.extrema = extrema;
+        Ok(())
+    }
+}
+
+/// First difference of consecutive inputs (`x[t] - x[t-1]`), the base
+/// series behind momentum-driven oscillators like TSI.
+#[derive(Debug, Clone, Default)]
+pub struct Momentum {
+    /// The prior bar's input, once that bar has fully closed.
+    committed_prev: Option<f64>,
+    /// The current bar's input, which may still be revised.
+    pending: Option<f64>,
+}
+
+impl Momentum {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IndicatorExec for Momentum {
+    /// Needs a prior committed bar before its diff means anything -- the
+    /// first bar always reports `0.0`, not a real momentum reading.
+    fn min_periods(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.committed_prev = None;
+        self.pending = None;
+    }
+
+    fn push(&mut self, x: f64) -> f64 {
+        if let Some(prev_pending) = self.pending.take() {
+            self.committed_prev = Some(prev_pending);
+        }
+        let diff = match self.committed_prev {
+            Some(prev) => x - prev,
+            None => 0.0,
+        };
+        self.pending = Some(x);
+        diff
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        let diff = match self.committed_prev {
+            Some(prev) => x - prev,
+            None => 0.0,
+        };
+        self.pending = Some(x);
+        diff
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_opt_f64(&mut buf, self.committed_prev);
+        encode_opt_f64(&mut buf, self.pending);
+        buf
+    }
+
+    fn deserialize_state(&mut self, bytes: &[u8]) -> crate::error::Result<()> {
+        let mut cursor = StateCursor::new(bytes);
+        self.committed_prev = cursor.read_opt_f64()?;
+        self.pending = cursor.read_opt_f64()?;
+        Ok(())
+    }
+}
+
+/// Percent rate of change of consecutive inputs (`100 * (x[t] - x[t-1]) /
+/// x[t-1]`), the same one-bar lag as [`Momentum`] but normalized against
+/// the prior value -- the diff [`super::trix`] takes of its triple-smoothed
+/// EMA.
+#[derive(Debug, Clone, Default)]
+pub struct PercentChange {
+    /// The prior bar's input, once that bar has fully closed.
+    committed_prev: Option<f64>,
+    /// The current bar's input, which may still be revised.
+    pending: Option<f64>,
+}
+
+impl PercentChange {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn diff(prev: Option<f64>, x: f64) -> f64 {
+        match prev {
+            Some(prev) if prev != 0.0 => 100.0 * (x - prev) / prev,
+            _ => 0.0,
+        }
+    }
+}
+
+impl IndicatorExec for PercentChange {
+    /// Needs a prior committed bar before its rate of change means anything
+    /// -- the first bar always reports `0.0`, not a real reading.
+    fn min_periods(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {
+        self.committed_prev = None;
+        self.pending = None;
+    }
+
+    fn push(&mut self, x: f64) -> f64 {
+        if let Some(prev_pending) = self.pending.take() {
+            self.committed_prev = Some(prev_pending);
+        }
+        let diff = Self::diff(self.committed_prev, x);
+        self.pending = Some(x);
+        diff
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        let diff = Self::diff(self.committed_prev, x);
+        self.pending = Some(x);
+        diff
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_opt_f64(&mut buf, self.committed_prev);
+        encode_opt_f64(&mut buf, self.pending);
+        buf
+    }
+
+    fn deserialize_state(&mut self, bytes: &[u8]) -> crate::error::Result<()> {
+        let mut cursor = StateCursor::new(bytes);
+        self.committed_prev = cursor.read_opt_f64()?;
+        self.pending = cursor.read_opt_f64()?;
+        Ok(())
+    }
+}
+
+/// Absolute value pass-through, used to build the denominator of ratio
+/// oscillators like TSI from a signed base series.
+#[derive(Debug, Clone, Default)]
+pub struct AbsValue;
+
+impl IndicatorExec for AbsValue {
+    fn reset(&mut self) {}
+
+    fn push(&mut self, x: f64) -> f64 {
+        x.abs()
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        x.abs()
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn deserialize_state(&mut self, _bytes: &[u8]) -> crate::error::Result<()> {
+        Ok(())
+    }
+}
+
+/// `max(x, 0)`, the "gain" half of a signed diff series, e.g. RSI's average
+/// gain (see [`super::rsi`]) built from [`Momentum`]'s close-to-close diff.
+#[derive(Debug, Clone, Default)]
+pub struct PositivePart;
+
+impl IndicatorExec for PositivePart {
+    fn reset(&mut self) {}
+
+    fn push(&mut self, x: f64) -> f64 {
+        x.max(0.0)
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        x.max(0.0)
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn deserialize_state(&mut self, _bytes: &[u8]) -> crate::error::Result<()> {
+        Ok(())
+    }
+}
+
+/// `max(-x, 0)`, the "loss" half of a signed diff series -- [`PositivePart`]
+/// on the negated input, e.g. RSI's average loss (see [`super::rsi`]).
+#[derive(Debug, Clone, Default)]
+pub struct NegativePart;
+
+impl IndicatorExec for NegativePart {
+    fn reset(&mut self) {}
+
+    fn push(&mut self, x: f64) -> f64 {
+        (-x).max(0.0)
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        (-x).max(0.0)
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn deserialize_state(&mut self, _bytes: &[u8]) -> crate::error::Result<()> {
+        Ok(())
+    }
+}
+
+/// Pass-through with no state, for nodes whose real work happens in how
+/// their [`super::Input`] combines upstream values.
+#[derive(Debug, Clone, Default)]
+pub struct Identity;
+
+impl IndicatorExec for Identity {
+    fn reset(&mut self) {}
+
+    fn push(&mut self, x: f64) -> f64 {
+        x
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        x
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn deserialize_state(&mut self, _bytes: &[u8]) -> crate::error::Result<()> {
+        Ok(())
+    }
+}
+
+/// Running total of every value pushed so far, e.g. the per-bar
+/// contribution [`super::obv`]/[`super::adl`] accumulate into a single
+/// cumulative line. `update_last` undoes the previous bar's contribution
+/// (tracked in `pending`, the same pattern [`Sma`] uses) before adding the
+/// revised one, so a still-forming bar can be revised without double
+/// counting.
+#[derive(Debug, Clone, Default)]
+pub struct CumulativeSum {
+    total: f64,
+    pending: Option<f64>,
+}
+
+impl CumulativeSum {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IndicatorExec for CumulativeSum {
+    fn reset(&mut self) {
+        self.total = 0.0;
+        self.pending = None;
+    }
+
+    fn push(&mut self, x: f64) -> f64 {
+        self.pending = Some(x);
+        self.total += x;
+        self.total
+    }
+
+    fn update_last(&mut self, x: f64) -> f64 {
+        if let Some(prev) = self.pending.replace(x) {
+            self.total -= prev;
+        }
+        self.total += x;
+        self.total
+    }
+
+    fn serialize_state(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_f64(&mut buf, self.total);
+        encode_opt_f64(&mut buf, self.pending);
+        buf
+    }
+
+    fn deserialize_state(&mut self, bytes: &[u8]) -> crate::error::Result<()> {
+        let mut cursor = StateCursor::new(bytes);
+        self.total = cursor.read_f64()?;
+        self.pending = cursor.read_opt_f64()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sma_tracks_window_average() {
+        let mut sma = Sma::new(3);
+        assert_eq!(sma.push(1.0), 1.0);
+        assert_eq!(sma.push(2.0), 1.5);
+        assert_eq!(sma.push(3.0), 2.0);
+        assert_eq!(sma.push(6.0), (2.0 + 3.0 + 6.0) / 3.0);
+    }
+
+    #[test]
+    fn sma_update_last_does_not_shift_window() {
+        let mut sma = Sma::new(3);
+        sma.push(1.0);
+        sma.push(2.0);
+        sma.push(3.0);
+        assert_eq!(sma.update_last(30.0), (1.0 + 2.0 + 30.0) / 3.0);
+        assert_eq!(sma.update_last(3.0), (1.0 + 2.0 + 3.0) / 3.0);
+    }
+
+    #[test]
+    fn wma_weights_recent_values_more_heavily() {
+        let mut wma = Wma::new(3);
+        wma.push(1.0);
+        wma.push(2.0);
+        // weights 1, 2, 3 on 1.0, 2.0, 3.0, divisor 6.
+        let expected = (1.0 * 1.0 + 2.0 * 2.0 + 3.0 * 3.0) / 6.0;
+        assert!((wma.push(3.0) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wma_update_last_does_not_shift_window() {
+        let mut wma = Wma::new(3);
+        wma.push(1.0);
+        wma.push(2.0);
+        wma.push(3.0);
+        let expected = (1.0 * 1.0 + 2.0 * 2.0 + 3.0 * 30.0) / 6.0;
+        assert!((wma.update_last(30.0) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wma_uses_a_partial_divisor_during_warm_up() {
+        let mut wma = Wma::new(3);
+        // One value: weight 1, divisor 1.
+        assert!((wma.push(2.0) - 2.0).abs() < 1e-9);
+        // Two values: weights 1, 2, divisor 3.
+        let expected_two = (1.0 * 2.0 + 2.0 * 4.0) / 3.0;
+        assert!((wma.push(4.0) - expected_two).abs() < 1e-9);
+        // Three values: full window, divisor 6, matches the textbook WMA.
+        let expected_three = (1.0 * 2.0 + 2.0 * 4.0 + 3.0 * 6.0) / 6.0;
+        assert!((wma.push(6.0) - expected_three).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wma_update_last_uses_a_partial_divisor_during_warm_up() {
+        let mut wma = Wma::new(3);
+        wma.push(2.0);
+        // Still warming up (one value in the window): weight 1, divisor 1.
+        assert!((wma.update_last(5.0) - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn kama_speeds_up_once_a_noisy_series_starts_trending() {
+        // Fed the same noisy-then-trending series as a plain slow (period
+        // 30) EMA, KAMA should catch up to the new level far faster once
+        // the trend kicks in and its efficiency ratio climbs toward 1 --
+        // that's the entire point of the adaptive smoothing constant.
+        let mut kama = Kama::new(10, 2, 30);
+        let mut slow_ema = Ema::new(30);
+
+        let mut state: u64 = 88172645463325252;
+        for _ in 0..40 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let noise = ((state % 401) as f64 - 200.0) / 100.0;
+            let x = 100.0 + noise;
+            kama.push(x);
+            slow_ema.push(x);
+        }
+
+        let mut kama_value = 0.0;
+        let mut ema_value = 0.0;
+        let mut target = 100.0;
+        for i in 1..=10 {
+            target = 100.0 + i as f64 * 5.0;
+            kama_value = kama.push(target);
+            ema_value = slow_ema.push(target);
+        }
+
+        let kama_lag = (kama_value - target).abs();
+        let ema_lag = (ema_value - target).abs();
+        assert!(
+            kama_lag < ema_lag,
+            "KAMA should lag less than a plain slow EMA once trending (KAMA {kama_lag}, EMA {ema_lag})"
+        );
+    }
+
+    #[test]
+    fn kama_update_last_revises_in_place_without_disturbing_the_previously_committed_value() {
+        let mut baseline = Kama::new(3, 2, 30);
+        let straight = [1.0, 2.0, 3.0].map(|x| baseline.push(x))[2];
+
+        let mut revised = Kama::new(3, 2, 30);
+        revised.push(1.0);
+        revised.push(2.0);
+        revised.push(999.0);
+        let settled = revised.update_last(3.0);
+
+        assert!((straight - settled).abs() < 1e-9);
+    }
+
+    #[test]
+    fn std_dev_band_scales_the_windows_population_std_dev_by_k() {
+        let mut band = StdDevBand::new(4, 2.0);
+        // mean = 3.5, population variance = 0.75, std_dev = sqrt(0.75).
+        let expected = 2.0 * 0.75_f64.sqrt();
+        let mut value = 0.0;
+        for x in [2.0, 4.0, 4.0, 4.0] {
+            value = band.push(x);
+        }
+        assert!((value - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn std_dev_band_is_zero_for_a_single_value() {
+        assert_eq!(StdDevBand::new(4, 2.0).push(2.0), 0.0);
+    }
+
+    #[test]
+    fn does_not_underflow_to_negative_variance_on_prices_around_1e6() {
+        // Mirrors `F64RingBuffer::near_constant_large_prices_stay_non_negative_and_finite`:
+        // near-constant prices at BTC-like magnitude are exactly the case
+        // that breaks a naive `sum_sq/n - mean^2` variance formula via
+        // catastrophic cancellation. StdDevBand delegates to
+        // `F64RingBuffer`'s Welford accumulator, so it never sees that.
+        let mut band = StdDevBand::new(20, 1.0);
+        let base = 1_000_000.0;
+        let mut value = 0.0;
+        for i in 0..40 {
+            band.push(base + (i % 3) as f64 * 1e-6);
+            value = band.update_last(base + (i % 3) as f64 * 1e-6);
+        }
+        assert!(value >= 0.0, "std dev underflowed to a negative value: {value}");
+        assert!(value.is_finite());
+    }
+
+    #[test]
+    fn zscore_matches_a_hand_computed_deviation_from_the_windows_mean() {
+        let mut z = Zscore::new(4);
+        // mean = 3.5, population variance = 0.75, std_dev = sqrt(0.75).
+        let expected = (4.0 - 3.5) / 0.75_f64.sqrt();
+        let mut value = 0.0;
+        for x in [2.0, 4.0, 4.0, 4.0] {
+            value = z.push(x);
+        }
+        assert!((value - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn zscore_is_zero_for_a_single_value() {
+        assert_eq!(Zscore::new(4).push(2.0), 0.0);
+    }
+
+    #[test]
+    fn zscore_update_last_revises_against_the_still_open_window() {
+        let mut z = Zscore::new(3);
+        z.push(1.0);
+        z.push(2.0);
+        z.push(999.0);
+        let revised = z.update_last(3.0);
+
+        let mut settled = Zscore::new(3);
+        settled.push(1.0);
+        settled.push(2.0);
+        let expected = settled.push(3.0);
+
+        assert!((revised - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ema_seeds_from_first_value() {
+        let mut ema = Ema::new(2); // alpha = 2/3
+        assert_eq!(ema.push(10.0), 10.0);
+        let expected = 2.0 / 3.0 * 20.0 + 1.0 / 3.0 * 10.0;
+        assert!((ema.push(20.0) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ema_update_last_reuses_committed_state() {
+        let mut ema = Ema::new(2);
+        ema.push(10.0);
+        ema.push(20.0);
+        let revised = ema.update_last(25.0);
+        let expected = 2.0 / 3.0 * 25.0 + 1.0 / 3.0 * 10.0;
+        assert!((revised - expected).abs() < 1e-9);
+        // The next push commits the revised value, not the original one.
+        let fresh = ema.push(20.0);
+        let expected_fresh = 2.0 / 3.0 * 20.0 + 1.0 / 3.0 * revised;
+        assert!((fresh - expected_fresh).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,159 @@
+//! Fisher Transform: normalizes price into `(-1, 1)` over a rolling window,
+//! then maps it through the inverse hyperbolic tangent
+//! (`0.5 * ln((1+x)/(1-x))`) so its distribution sharpens sharply near
+//! turning points instead of clustering there the way a plain price
+//! oscillator does.
+//!
+//! ```text
+//! price      = (high + low) / 2
+//! raw        = 2 * ((price - lowest_low_n) / (highest_high_n - lowest_low_n) - 0.5)
+//! normalized = clamp(0.33 * raw + 0.67 * prev_normalized, -0.999, 0.999)
+//! fisher     = 0.5 * ln((1 + normalized) / (1 - normalized)) + 0.5 * prev_fisher
+//! trigger    = prev_fisher
+//! ```
+//!
+//! `normalized` is clamped away from exactly `±1` before the logarithm --
+//! at the window's own high or low it would otherwise divide by zero.
+//!
+//! Like [`super::kdj::Kdj`] and [`super::parabolic_sar`], Fisher needs a
+//! bar's high and low jointly, more than [`super::IndicatorExec`]'s
+//! single-scalar-per-node contract can express, so it's implemented as a
+//! standalone transform in the same style rather than as an
+//! `IndicatorGraph`-registered node.
+
+use super::exec::{IndicatorExec, RollingMax, RollingMin};
+use crate::kline::Bar;
+
+/// One bar's Fisher Transform output: the Fisher value itself, plus the
+/// trigger line (the previous bar's Fisher value) a caller would plot
+/// alongside it to spot crossings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FisherOutput {
+    pub fisher: f64,
+    pub trigger: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct FisherTransform {
+    highest_high: RollingMax,
+    lowest_low: RollingMin,
+    /// Normalized value and Fisher value as of the end of the last fully
+    /// closed bar, both seeded to `0.0` before the first bar closes.
+    committed_normalized: f64,
+    committed_fisher: f64,
+    /// `(output, normalized)` for the bar currently being built, if any --
+    /// `normalized` is carried alongside `output` so it can be committed
+    /// once this bar closes, the same way [`super::kdj::Kdj`] carries K/D.
+    current: Option<(FisherOutput, f64)>,
+}
+
+impl FisherTransform {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "Fisher Transform period must be > 0");
+        Self {
+            highest_high: RollingMax::new(period),
+            lowest_low: RollingMin::new(period),
+            committed_normalized: 0.0,
+            committed_fisher: 0.0,
+            current: None,
+        }
+    }
+
+    fn compute(&mut self, bar: &Bar, revise: bool) -> (FisherOutput, f64) {
+        let price = (bar.high + bar.low) / 2.0;
+        let highest = if revise { self.highest_high.update_last(price) } else { self.highest_high.push(price) };
+        let lowest = if revise { self.lowest_low.update_last(price) } else { self.lowest_low.push(price) };
+
+        let raw = if highest == lowest { 0.0 } else { 2.0 * ((price - lowest) / (highest - lowest) - 0.5) };
+        let normalized = (0.33 * raw + 0.67 * self.committed_normalized).clamp(-0.999, 0.999);
+        let fisher = 0.5 * ((1.0 + normalized) / (1.0 - normalized)).ln() + 0.5 * self.committed_fisher;
+
+        (FisherOutput { fisher, trigger: self.committed_fisher }, normalized)
+    }
+
+    /// Commit the previous bar's normalized/Fisher values permanently, then
+    /// compute Fisher/trigger for `bar`.
+    pub fn push(&mut self, bar: &Bar) -> FisherOutput {
+        if let Some((prev_output, prev_normalized)) = self.current.take() {
+            self.committed_fisher = prev_output.fisher;
+            self.committed_normalized = prev_normalized;
+        }
+        let (output, normalized) = self.compute(bar, false);
+        self.current = Some((output, normalized));
+        output
+    }
+
+    /// Revise the current (not yet committed) bar's Fisher/trigger in
+    /// place, against the same committed previous values `push` last used.
+    pub fn update_last(&mut self, bar: &Bar) -> FisherOutput {
+        let (output, normalized) = self.compute(bar, true);
+        self.current = Some((output, normalized));
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(price: f64) -> Bar {
+        Bar { ts: 0, open: price, high: price, low: price, close: price, volume: 1.0 }
+    }
+
+    #[test]
+    fn fisher_flips_sign_after_a_turning_point_at_the_series_midpoint() {
+        let mut fisher = FisherTransform::new(5);
+
+        // Rises to the top of its own 5-bar window, then turns and falls
+        // well past where it started -- by the last down bar the 5-bar
+        // window only contains the falling leg, so price sits back at the
+        // bottom of it.
+        let rising = [100.0, 102.0, 104.0, 106.0, 108.0];
+        let falling = [104.0, 100.0, 96.0, 92.0, 88.0];
+
+        let mut last_rising = None;
+        for price in rising {
+            last_rising = Some(fisher.push(&bar(price)));
+        }
+        assert!(last_rising.unwrap().fisher > 0.0, "expected a positive Fisher value at the run-up's peak");
+
+        let mut last_falling = None;
+        for price in falling {
+            last_falling = Some(fisher.push(&bar(price)));
+        }
+        assert!(last_falling.unwrap().fisher < 0.0, "expected a negative Fisher value at the sell-off's trough");
+    }
+
+    #[test]
+    fn trigger_is_the_previous_bars_fisher_value() {
+        let mut fisher = FisherTransform::new(5);
+        let first = fisher.push(&bar(100.0));
+        let second = fisher.push(&bar(105.0));
+        assert_eq!(second.trigger, first.fisher);
+    }
+
+    #[test]
+    fn a_flat_window_normalizes_to_zero_and_never_produces_infinities() {
+        let mut fisher = FisherTransform::new(5);
+        for _ in 0..10 {
+            let out = fisher.push(&bar(100.0));
+            assert!(out.fisher.is_finite(), "fisher={}", out.fisher);
+        }
+    }
+
+    #[test]
+    fn update_last_revises_without_moving_the_committed_previous_bar() {
+        let mut fisher = FisherTransform::new(5);
+        fisher.push(&bar(100.0));
+        fisher.push(&bar(110.0));
+        fisher.push(&bar(90.0));
+        let live = fisher.push(&bar(95.0));
+        let revised = fisher.update_last(&bar(60.0));
+        assert_ne!(revised.fisher, live.fisher);
+        // Revising doesn't touch the committed previous bar, so a further
+        // update_last from the same live starting point reproduces the
+        // same committed base.
+        let revised_again = fisher.update_last(&bar(60.0));
+        assert_eq!(revised.fisher, revised_again.fisher);
+    }
+}
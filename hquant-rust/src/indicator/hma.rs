@@ -0,0 +1,62 @@
+//! Hull Moving Average: `WMA(2*WMA(field, n/2) - WMA(field, n), sqrt(n))`,
+//! built as a composite of three [`Wma`] nodes the same way [`super::tsi`]
+//! builds TSI from `Momentum`/`Ema` nodes.
+
+use super::exec::{Identity, Wma};
+use super::{CombineOp, IndicatorGraph, NodeId};
+use crate::error::{HQuantError, Result};
+use crate::kline::Field;
+
+/// Build the HMA DAG chain under `base_name`, reading `field` from the bar.
+/// Sub-indicators are registered under `base_name__`-prefixed internal
+/// names, the same convention [`super::tsi::build`] uses, so they don't
+/// collide with user-visible indicators. Returns the HMA node id.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str, field: Field, period: usize) -> Result<NodeId> {
+    if period < 2 {
+        return Err(HQuantError::InvalidSpec(base_name.to_string()));
+    }
+    let half_period = period / 2;
+    let sqrt_period = (period as f64).sqrt().round().max(1.0) as usize;
+
+    let wma_half = graph.add_field_indicator(&format!("{base_name}__wma_half"), field, Box::new(Wma::new(half_period)))?;
+    let wma_full = graph.add_field_indicator(&format!("{base_name}__wma_full"), field, Box::new(Wma::new(period)))?;
+    let raw = graph.add_combined_indicator(
+        &format!("{base_name}__raw"),
+        wma_half,
+        wma_full,
+        CombineOp::DoubleMinus,
+        Box::new(Identity),
+    )?;
+    graph.add_chained_indicator(base_name, raw, Box::new(Wma::new(sqrt_period)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(close: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn hma_tracks_a_steady_uptrend() {
+        let mut graph = IndicatorGraph::new();
+        let hma = build(&mut graph, "HMA_16", Field::Close, 16).unwrap();
+
+        let mut price = 100.0;
+        for _ in 0..60 {
+            graph.push_bar(&bar(price));
+            price += 1.0;
+        }
+
+        let value = graph.get_from_end(hma, 0).unwrap();
+        assert!((value - price + 1.0).abs() < 5.0, "HMA should track close closely in a steady uptrend, got {value}");
+    }
+
+    #[test]
+    fn rejects_a_period_below_two() {
+        let mut graph = IndicatorGraph::new();
+        assert!(build(&mut graph, "HMA_1", Field::Close, 1).is_err());
+    }
+}
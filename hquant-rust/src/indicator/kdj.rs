@@ -0,0 +1,153 @@
+//! KDJ (Stochastic Oscillator's Chinese-market variant): raw stochastic
+//! `RSV` smoothed into `K` and `D` lines, plus the divergence line `J` that
+//! makes KDJ distinct from a plain Stochastic.
+//!
+//! ```text
+//! RSV = (close - lowest_low_n) / (highest_high_n - lowest_low_n) * 100
+//! K   = 2/3 * prev_K + 1/3 * RSV
+//! D   = 2/3 * prev_D + 1/3 * K
+//! J   = 3*K - 2*D
+//! ```
+//!
+//! Like [`super::adx::Adx`], KDJ needs a bar's high, low *and* close
+//! jointly (for the rolling high/low window and the current close), more
+//! than [`super::IndicatorExec`]'s single-scalar-per-node contract can
+//! express, so it's implemented as a standalone transform in the same
+//! style rather than as an `IndicatorGraph`-registered node.
+//!
+//! `K`/`D` follow the same `2/3, 1/3` recurrence [`super::exec::Ema`] uses
+//! for `alpha = 1/3`, but seeded to 50 rather than to the first RSV value
+//! (the standard KDJ convention for insufficient history), so they're
+//! tracked directly here instead of reusing `Ema`.
+
+use super::exec::{IndicatorExec, RollingMax, RollingMin};
+use crate::kline::Bar;
+
+/// One bar's KDJ output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KdjOutput {
+    pub k: f64,
+    pub d: f64,
+    pub j: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Kdj {
+    highest_high: RollingMax,
+    lowest_low: RollingMin,
+    /// K/D as of the end of the last fully closed bar, seeded to 50 before
+    /// the first bar closes.
+    committed_k: f64,
+    committed_d: f64,
+    /// Output for the bar currently being built, if any.
+    current: Option<KdjOutput>,
+}
+
+impl Kdj {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "KDJ period must be > 0");
+        Self {
+            highest_high: RollingMax::new(period),
+            lowest_low: RollingMin::new(period),
+            committed_k: 50.0,
+            committed_d: 50.0,
+            current: None,
+        }
+    }
+
+    fn compute(&mut self, bar: &Bar, revise: bool) -> KdjOutput {
+        let highest = if revise { self.highest_high.update_last(bar.high) } else { self.highest_high.push(bar.high) };
+        let lowest = if revise { self.lowest_low.update_last(bar.low) } else { self.lowest_low.push(bar.low) };
+
+        let rsv = if highest == lowest { 50.0 } else { 100.0 * (bar.close - lowest) / (highest - lowest) };
+        let k = 2.0 / 3.0 * self.committed_k + 1.0 / 3.0 * rsv;
+        let d = 2.0 / 3.0 * self.committed_d + 1.0 / 3.0 * k;
+        let j = 3.0 * k - 2.0 * d;
+
+        KdjOutput { k, d, j }
+    }
+
+    /// Commit the previous bar's K/D permanently, then compute K/D/J for
+    /// `bar`.
+    pub fn push(&mut self, bar: &Bar) -> KdjOutput {
+        if let Some(prev) = self.current.take() {
+            self.committed_k = prev.k;
+            self.committed_d = prev.d;
+        }
+        let output = self.compute(bar, false);
+        self.current = Some(output);
+        output
+    }
+
+    /// Revise the current (not yet committed) bar's K/D/J in place, against
+    /// the same committed previous K/D `push` last used.
+    pub fn update_last(&mut self, bar: &Bar) -> KdjOutput {
+        let output = self.compute(bar, true);
+        self.current = Some(output);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(high: f64, low: f64, close: f64) -> Bar {
+        Bar { ts: 0, open: close, high, low, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn first_bar_seeds_k_and_d_from_fifty() {
+        let mut kdj = Kdj::new(9);
+        // Close at the top of its own range: RSV = 100.
+        let out = kdj.push(&bar(110.0, 90.0, 110.0));
+        assert!((out.k - (2.0 / 3.0 * 50.0 + 1.0 / 3.0 * 100.0)).abs() < 1e-9);
+        assert!((out.d - (2.0 / 3.0 * 50.0 + 1.0 / 3.0 * out.k)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn j_can_exceed_100_and_drop_below_0() {
+        let mut kdj = Kdj::new(9);
+        let mut max_j = f64::NEG_INFINITY;
+        let mut min_j = f64::INFINITY;
+
+        // J overshoots K/D by construction (`3*K - 2*D`), since D lags K. A
+        // run of closes pinned to the top of the range lifts K faster than
+        // D, so J passes 100 on the run's second bar (K 77.8, D 63.0, J
+        // 107.4). The mirrored run at the bottom drops K faster than D, so
+        // J falls below 0 on the first bar after the single low flip (K
+        // 44.1, D 72.1, J -12.1).
+        let at_high = std::iter::repeat_n(true, 10);
+        let flip_low = std::iter::once(false);
+        let at_low = std::iter::repeat_n(false, 10);
+        let flip_high = std::iter::once(true);
+        for at_top in at_high.chain(flip_low).chain(at_low).chain(flip_high) {
+            let out = if at_top { kdj.push(&bar(150.0, 50.0, 150.0)) } else { kdj.push(&bar(150.0, 50.0, 50.0)) };
+            max_j = max_j.max(out.j);
+            min_j = min_j.min(out.j);
+        }
+
+        assert!(max_j > 100.0, "max_j={max_j}");
+        assert!(min_j < 0.0, "min_j={min_j}");
+    }
+
+    #[test]
+    fn update_last_revises_without_moving_the_committed_previous_bar() {
+        let mut kdj = Kdj::new(9);
+        kdj.push(&bar(105.0, 95.0, 100.0));
+        let live = kdj.push(&bar(110.0, 100.0, 108.0));
+        let revised = kdj.update_last(&bar(112.0, 100.0, 111.0));
+        assert_ne!(revised.k, live.k);
+        // Revising doesn't touch committed_k, so a further update_last from
+        // the same live starting point reproduces the same committed base.
+        let revised_again = kdj.update_last(&bar(112.0, 100.0, 111.0));
+        assert_eq!(revised.k, revised_again.k);
+    }
+
+    #[test]
+    fn a_flat_window_yields_a_neutral_rsv_of_fifty() {
+        let mut kdj = Kdj::new(9);
+        let out = kdj.push(&bar(100.0, 100.0, 100.0));
+        assert!((out.k - (2.0 / 3.0 * 50.0 + 1.0 / 3.0 * 50.0)).abs() < 1e-9);
+    }
+}
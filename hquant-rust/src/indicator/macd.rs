@@ -0,0 +1,91 @@
+//! MACD: fast EMA minus slow EMA (the MACD line), an EMA of that line (the
+//! signal line), and their difference (the histogram) -- three nodes built
+//! the same way [`super::tsi`] chains EMAs, so `update_last` inherits
+//! [`super::exec::Ema`]'s committed/current recurrence rather than
+//! reconstructing a previous EMA by algebraically inverting the current
+//! one. Back-computing a previous EMA that way (`(ema - price*mult) /
+//! (1 - mult)`) compounds floating-point error with every revision and
+//! isn't idempotent under repeated `update_last` calls on the same bar;
+//! reusing `Ema` sidesteps that entirely, since it always recomputes
+//! forward from the last *committed* bar rather than from its own most
+//! recent (possibly already-revised) output.
+
+use super::exec::{Ema, Identity};
+use super::{CombineOp, IndicatorGraph, NodeId};
+use crate::error::{HQuantError, Result};
+use crate::kline::Field;
+
+/// The three nodes making up a MACD indicator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Macd {
+    pub macd: NodeId,
+    pub signal: NodeId,
+    pub histogram: NodeId,
+}
+
+/// Build the MACD DAG chain under `base_name`, reading `Field::Close`. The
+/// MACD line is registered under `base_name` itself (so it's the spec's
+/// primary output); `{base_name}_signal` and `{base_name}_histogram` are
+/// independently addressable alongside it.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str, fast_period: usize, slow_period: usize, signal_period: usize) -> Result<Macd> {
+    if fast_period == 0 || slow_period == 0 || signal_period == 0 {
+        return Err(HQuantError::InvalidSpec(base_name.to_string()));
+    }
+    let fast = graph.add_field_indicator(&format!("{base_name}__fast"), Field::Close, Box::new(Ema::new(fast_period)))?;
+    let slow = graph.add_field_indicator(&format!("{base_name}__slow"), Field::Close, Box::new(Ema::new(slow_period)))?;
+    let macd = graph.add_combined_indicator(base_name, fast, slow, CombineOp::Sub, Box::new(Identity))?;
+    let signal = graph.add_chained_indicator(&format!("{base_name}_signal"), macd, Box::new(Ema::new(signal_period)))?;
+    let histogram = graph.add_combined_indicator(&format!("{base_name}_histogram"), macd, signal, CombineOp::Sub, Box::new(Identity))?;
+    Ok(Macd { macd, signal, histogram })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(close: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn histogram_is_the_macd_line_minus_the_signal_line() {
+        let mut graph = IndicatorGraph::new();
+        let macd = build(&mut graph, "MACD_12_26_9", 12, 26, 9).unwrap();
+
+        let mut price = 100.0;
+        for _ in 0..40 {
+            graph.push_bar(&bar(price));
+            price += 0.5;
+        }
+
+        let macd_line = graph.get_from_end(macd.macd, 0).unwrap();
+        let signal_line = graph.get_from_end(macd.signal, 0).unwrap();
+        let histogram = graph.get_from_end(macd.histogram, 0).unwrap();
+        assert!((histogram - (macd_line - signal_line)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn repeated_update_last_calls_on_the_same_bar_are_idempotent() {
+        let mut graph = IndicatorGraph::new();
+        let macd = build(&mut graph, "MACD_12_26_9", 12, 26, 9).unwrap();
+
+        for price in [100.0, 101.0, 99.0, 102.0, 103.0] {
+            graph.push_bar(&bar(price));
+        }
+        graph.push_bar(&bar(104.0));
+
+        graph.update_last(&bar(105.0));
+        let after_first = graph.get_from_end(macd.macd, 0).unwrap();
+        graph.update_last(&bar(105.0));
+        let after_second = graph.get_from_end(macd.macd, 0).unwrap();
+
+        assert_eq!(after_first, after_second);
+    }
+
+    #[test]
+    fn rejects_a_zero_period() {
+        let mut graph = IndicatorGraph::new();
+        assert!(build(&mut graph, "MACD_0_26_9", 0, 26, 9).is_err());
+    }
+}
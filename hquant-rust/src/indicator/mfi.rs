@@ -0,0 +1,150 @@
+//! Money Flow Index: volume-weighted RSI. `100 - 100 / (1 + MR)`, `MR =
+//! avg(positive money flow) / avg(negative money flow)` over a rolling
+//! window, where each bar's raw money flow is `typical_price * volume` and
+//! it counts as positive or negative depending on whether the typical
+//! price rose or fell from the previous bar.
+//!
+//! Structurally this is [`super::rsi`] with two changes: the diffed series
+//! is typical price (`(high + low + close) / 3`) instead of close, and
+//! each bar's gain/loss is weighted by that bar's raw money flow instead
+//! of counting 1-for-1. The `/ 3` is dropped -- [`super::rsi::build`]'s
+//! `PercentOfTotal` divides the positive sum by `positive + negative`, so
+//! any constant positive factor applied uniformly to typical price (and
+//! therefore to every raw money flow) cancels out of the final ratio
+//! without ever needing to be computed. [`super::obv`]'s use of
+//! [`CombineOp::SignedVolume`] is the direct precedent for how the sign is
+//! applied here: typical price's [`super::exec::Momentum`] signs raw money
+//! flow the same way close's momentum signs volume there.
+
+use super::exec::{Identity, Momentum, NegativePart, PositivePart, Sma};
+use super::{CombineOp, IndicatorGraph, NodeId};
+use crate::error::{HQuantError, Result};
+use crate::kline::Field;
+
+/// Build the MFI DAG chain under `base_name`, registering intermediate
+/// typical-price/money-flow nodes with `base_name__`-prefixed internal
+/// names, the same convention [`super::rsi::build`]/[`super::adl::build`]
+/// use. Returns the MFI node id.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str, period: usize) -> Result<NodeId> {
+    if period < 2 {
+        return Err(HQuantError::InvalidSpec(base_name.to_string()));
+    }
+
+    let high = graph.add_field_indicator(&format!("{base_name}__high"), Field::High, Box::new(Identity))?;
+    let low = graph.add_field_indicator(&format!("{base_name}__low"), Field::Low, Box::new(Identity))?;
+    let close = graph.add_field_indicator(&format!("{base_name}__close"), Field::Close, Box::new(Identity))?;
+    let volume = graph.add_field_indicator(&format!("{base_name}__volume"), Field::Volume, Box::new(Identity))?;
+
+    // `high + low + close`, `3x` the true typical price -- see the module
+    // doc comment for why the missing `/ 3` never needs to be applied.
+    let hl = graph.add_combined_indicator(&format!("{base_name}__hl"), high, low, CombineOp::Add, Box::new(Identity))?;
+    let tp = graph.add_combined_indicator(&format!("{base_name}__tp"), hl, close, CombineOp::Add, Box::new(Identity))?;
+    let raw_flow = graph.add_combined_indicator(&format!("{base_name}__raw_flow"), tp, volume, CombineOp::Mul, Box::new(Identity))?;
+
+    let mom = graph.add_chained_indicator(&format!("{base_name}__mom"), tp, Box::new(Momentum::new()))?;
+    let signed_flow = graph.add_combined_indicator(
+        &format!("{base_name}__signed_flow"),
+        mom,
+        raw_flow,
+        CombineOp::SignedVolume,
+        Box::new(Identity),
+    )?;
+    let pos_flow = graph.add_chained_indicator(&format!("{base_name}__pos_flow"), signed_flow, Box::new(PositivePart))?;
+    let neg_flow = graph.add_chained_indicator(&format!("{base_name}__neg_flow"), signed_flow, Box::new(NegativePart))?;
+    let avg_pos = graph.add_chained_indicator(&format!("{base_name}__avg_pos"), pos_flow, Box::new(Sma::new(period)))?;
+    let avg_neg = graph.add_chained_indicator(&format!("{base_name}__avg_neg"), neg_flow, Box::new(Sma::new(period)))?;
+
+    graph.add_combined_indicator(base_name, avg_pos, avg_neg, CombineOp::PercentOfTotal, Box::new(Identity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(high: f64, low: f64, close: f64, volume: f64) -> Bar {
+        Bar { ts: 0, open: close, high, low, close, volume }
+    }
+
+    #[test]
+    fn rejects_a_period_below_two() {
+        let mut graph = IndicatorGraph::new();
+        assert!(build(&mut graph, "MFI_1", 1).is_err());
+    }
+
+    #[test]
+    fn stays_in_0_to_100() {
+        let mut graph = IndicatorGraph::new();
+        let mfi = build(&mut graph, "MFI_5", 5).unwrap();
+
+        let bars = [
+            bar(10.0, 9.0, 9.5, 100.0),
+            bar(11.0, 9.5, 10.5, 150.0),
+            bar(11.5, 10.0, 10.2, 80.0),
+            bar(10.5, 9.0, 9.2, 200.0),
+            bar(9.5, 8.5, 9.0, 120.0),
+            bar(10.0, 9.0, 9.8, 90.0),
+        ];
+        for bar in &bars {
+            graph.push_bar(bar);
+            let value = graph.get_from_end(mfi, 0).unwrap();
+            assert!((0.0..=100.0).contains(&value), "MFI out of range: {value}");
+        }
+    }
+
+    /// Hand-computed reference over 3 bars, period 2 -- there's no existing
+    /// dynamic-factory MFI in this tree to cross-check against (`mfi`
+    /// resolves to nothing but this DAG chain), so this checks against a
+    /// textbook MFI computation by hand instead.
+    #[test]
+    fn matches_a_hand_computed_mfi_series() {
+        let mut graph = IndicatorGraph::new();
+        let mfi = build(&mut graph, "MFI_2", 2).unwrap();
+
+        // Typical prices: 10, 11, 9. Raw money flow: 10*100=1000, 11*100=1100, 9*100=900.
+        // Bar 2 (tp rises 10->11): positive flow 1100. Bar 3 (tp falls 11->9): negative flow 900.
+        graph.push_bar(&bar(11.0, 9.0, 10.0, 100.0));
+        graph.push_bar(&bar(12.0, 10.0, 11.0, 100.0));
+        graph.push_bar(&bar(10.0, 8.0, 9.0, 100.0));
+
+        // avg_pos over last 2 bars = (1100 + 0) / 2 = 550, avg_neg = (0 + 900) / 2 = 450.
+        // MFI = 100 * 550 / (550 + 450) = 55.
+        let value = graph.get_from_end(mfi, 0).unwrap();
+        assert!((value - 55.0).abs() < 1e-9, "expected 55.0, got {value}");
+    }
+
+    #[test]
+    fn every_bar_rising_pushes_mfi_toward_100() {
+        let mut graph = IndicatorGraph::new();
+        let mfi = build(&mut graph, "MFI_5", 5).unwrap();
+
+        let mut price = 100.0;
+        for _ in 0..10 {
+            graph.push_bar(&bar(price + 1.0, price - 1.0, price, 100.0));
+            price += 2.0;
+        }
+        assert!((graph.get_from_end(mfi, 0).unwrap() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn update_last_reproduces_a_fresh_push() {
+        let mut a = IndicatorGraph::new();
+        let mfi_a = build(&mut a, "MFI_3", 3).unwrap();
+        let mut b = IndicatorGraph::new();
+        let mfi_b = build(&mut b, "MFI_3", 3).unwrap();
+
+        a.push_bar(&bar(10.0, 8.0, 9.0, 100.0));
+        b.push_bar(&bar(10.0, 8.0, 9.0, 100.0));
+
+        // `a` opens the new bar with a placeholder, then revises it in
+        // place as the candle ticks toward its real close.
+        a.push_bar(&bar(11.0, 9.0, 9.5, 40.0));
+        a.update_last(&bar(12.0, 9.0, 11.0, 70.0));
+        // `b` sees the real bar directly.
+        b.push_bar(&bar(12.0, 9.0, 11.0, 70.0));
+
+        let va = a.get_from_end(mfi_a, 0).unwrap();
+        let vb = b.get_from_end(mfi_b, 0).unwrap();
+        assert!((va - vb).abs() < 1e-9, "expected {va} to match fresh push {vb}");
+    }
+}
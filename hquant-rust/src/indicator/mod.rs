@@ -0,0 +1,771 @@
+//! DAG-based indicator engine.
+//!
+//! Indicators are nodes in a small directed graph: each node reads a scalar
+//! input (either a raw bar [`Field`] or another node's most recent output)
+//! and produces a scalar output, which is retained in a [`CircularColumn`]
+//! so the DSL and strategy evaluator can look a fixed number of bars into
+//! the past (see [`IndicatorGraph::get_from_end`]).
+
+mod adl;
+mod adx;
+mod atr;
+mod boll;
+mod cmf;
+mod cmo;
+mod dema;
+mod donchian;
+mod dynamic;
+mod exec;
+mod fisher;
+mod hma;
+mod kdj;
+mod macd;
+mod mfi;
+mod obv;
+mod parabolic_sar;
+mod rsi;
+mod spec;
+mod tema;
+mod trix;
+mod tsi;
+mod vwap;
+mod vwma;
+
+pub use adx::{Adx, AdxOutput};
+pub use atr::Atr;
+pub use boll::BollBands;
+pub use donchian::DonchianChannel;
+pub use dynamic::{DynamicIndicator, KlineView};
+pub use exec::{CumulativeSum, Ema, Identity, IndicatorExec, Kama, Sma, Wma, Zscore};
+pub use fisher::{FisherOutput, FisherTransform};
+pub use kdj::{Kdj, KdjOutput};
+pub use macd::Macd;
+pub use parabolic_sar::{ParabolicSar, SarOutput, Trend};
+pub use rsi::RsiSmoothing;
+pub use spec::IndicatorSpec;
+pub use vwap::SessionVwap;
+pub use vwma::Vwma;
+
+use crate::common::CircularColumn;
+use crate::error::{HQuantError, Result};
+use crate::kline::{Bar, Field};
+use std::collections::HashMap;
+
+pub type NodeId = usize;
+
+/// Where a node reads its per-bar scalar input from.
+#[derive(Debug, Clone, Copy)]
+pub enum Input {
+    Field(Field),
+    Node(NodeId),
+    /// Two upstream nodes combined arithmetically, e.g. a ratio for TSI or
+    /// percent-B style indicators.
+    Combine(NodeId, NodeId, CombineOp),
+}
+
+/// Arithmetic used to fold two upstream node outputs into one scalar before
+/// it reaches a node's [`IndicatorExec`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CombineOp {
+    Add,
+    Sub,
+    /// `100 * a / b`, or `0.0` when `b` is `0.0` (no movement to measure)
+    /// instead of propagating `NaN`. Used by TSI and similar oscillators.
+    RatioPercent,
+    /// `2 * a - b`, the raw (pre-smoothing) series behind Hull MA.
+    DoubleMinus,
+    /// `3 * a - 3 * b`, the first two terms of TEMA's `3*EMA - 3*EMA(EMA) +
+    /// EMA(EMA(EMA))` before the third is folded in with a plain `Add`.
+    TripleMinus,
+    /// `(a + b) / 2`, the midline between two bands, e.g. Donchian's
+    /// highest-high/lowest-low midpoint.
+    Average,
+    /// `a * b`, e.g. a close-location value times volume for
+    /// [`super::adl`]/[`super::cmf`]'s money flow volume.
+    Mul,
+    /// `a / b`, or `0.0` when `b` is `0.0` (a doji bar's high-low range,
+    /// or a zero-volume bar) instead of propagating `NaN`/`inf`.
+    SafeRatio,
+    /// `b` signed by `a`: `b` if `a > 0`, `-b` if `a < 0`, `0.0` if
+    /// `a == 0` (or either input is `NaN`). Used by [`super::obv`] to turn
+    /// a close-to-close momentum node into a signed volume contribution.
+    SignedVolume,
+    /// `100 * a / (a + b)`, or `0.0` when `a + b` is `0.0` (no gain and no
+    /// loss over the window) instead of propagating `NaN`. Used by
+    /// [`super::rsi`] to turn average gain/loss into `100 - 100 / (1 + RS)`
+    /// without dividing by average loss directly.
+    PercentOfTotal,
+    /// `100 * (a - b) / (a + b)`, or `0.0` when `a + b` is `0.0` (no gain
+    /// and no loss over the window) instead of propagating `NaN`. Used by
+    /// [`super::cmo`] to turn rolling up/down sums into the Chande Momentum
+    /// Oscillator directly, without a separate "net minus total" node.
+    PercentOfDifference,
+}
+
+impl CombineOp {
+    fn apply(&self, a: f64, b: f64) -> f64 {
+        match self {
+            CombineOp::Add => a + b,
+            CombineOp::Sub => a - b,
+            CombineOp::RatioPercent => {
+                if b == 0.0 {
+                    0.0
+                } else {
+                    100.0 * a / b
+                }
+            }
+            CombineOp::DoubleMinus => 2.0 * a - b,
+            CombineOp::TripleMinus => 3.0 * a - 3.0 * b,
+            CombineOp::Average => (a + b) / 2.0,
+            CombineOp::Mul => a * b,
+            CombineOp::SafeRatio => {
+                if b == 0.0 {
+                    0.0
+                } else {
+                    a / b
+                }
+            }
+            CombineOp::SignedVolume => {
+                if a > 0.0 {
+                    b
+                } else if a < 0.0 {
+                    -b
+                } else {
+                    0.0
+                }
+            }
+            CombineOp::PercentOfTotal => {
+                let total = a + b;
+                if total == 0.0 {
+                    0.0
+                } else {
+                    100.0 * a / total
+                }
+            }
+            CombineOp::PercentOfDifference => {
+                let total = a + b;
+                if total == 0.0 {
+                    0.0
+                } else {
+                    100.0 * (a - b) / total
+                }
+            }
+        }
+    }
+}
+
+struct Node {
+    name: String,
+    input: Input,
+    exec: Box<dyn IndicatorExec>,
+    output: CircularColumn<f64>,
+    /// How many other live nodes read this one as a direct dependency;
+    /// [`IndicatorGraph::remove_indicator`] refuses to remove a node while
+    /// this is nonzero.
+    ref_count: usize,
+    /// Optional `(min, max)` applied to every value before it's written to
+    /// `output`, for bounded oscillators (RSI, Williams %R, Stochastic)
+    /// that can drift a hair outside their documented range from float
+    /// error. Off by default so genuine bugs still surface as
+    /// out-of-range values instead of being silently snapped away; opt in
+    /// via [`IndicatorGraph::set_output_clamp`].
+    clamp: Option<(f64, f64)>,
+}
+
+impl std::fmt::Debug for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Node").field("name", &self.name).finish()
+    }
+}
+
+/// Apply an optional `(min, max)` clamp to a freshly computed node output.
+/// `f64::clamp` leaves a `NaN` value (e.g. during warm-up) unchanged, since
+/// its bound comparisons are always false against `NaN`.
+fn clamp_output(value: f64, clamp: Option<(f64, f64)>) -> f64 {
+    match clamp {
+        Some((min, max)) => value.clamp(min, max),
+        None => value,
+    }
+}
+
+/// Default number of past bars kept per indicator, matching the window
+/// most DSL rules and stats need to look back over.
+const DEFAULT_HISTORY: usize = 512;
+
+#[derive(Debug)]
+pub struct IndicatorGraph {
+    /// Slots indexed by [`NodeId`]; `None` marks a removed node whose id is
+    /// awaiting reuse via `free`.
+    nodes: Vec<Option<Node>>,
+    name_index: HashMap<String, NodeId>,
+    /// Topological processing order, kept separate from slot index so a
+    /// freed slot can be reused by a brand new node without corrupting
+    /// dependency ordering: a reused low id could otherwise land before a
+    /// higher-id dependency in a plain `0..nodes.len()` scan.
+    order: Vec<NodeId>,
+    /// Freed slots available for reuse by the next `insert`, so a
+    /// long-running process that rotates indicators doesn't grow `nodes`
+    /// without bound.
+    free: Vec<NodeId>,
+    history: usize,
+}
+
+impl IndicatorGraph {
+    pub fn new() -> Self {
+        Self::with_history(DEFAULT_HISTORY)
+    }
+
+    pub fn with_history(history: usize) -> Self {
+        Self {
+            nodes: Vec::new(),
+            name_index: HashMap::new(),
+            order: Vec::new(),
+            free: Vec::new(),
+            history,
+        }
+    }
+
+    pub fn node_id(&self, name: &str) -> Option<NodeId> {
+        self.name_index.get(name).copied()
+    }
+
+    /// The internal name backing `id`, including the sub-node names a
+    /// multi-node spec (MACD, TSI, ...) generates for its internal nodes.
+    pub fn node_name(&self, id: NodeId) -> Option<&str> {
+        self.nodes.get(id)?.as_ref().map(|n| n.name.as_str())
+    }
+
+    /// Every live node's name, its [`IndicatorExec`]'s serialized state,
+    /// and its output history (oldest first), in construction order. See
+    /// [`crate::engine::HQuant::save_state`].
+    pub fn snapshot_nodes(&self) -> Vec<(String, Vec<u8>, Vec<f64>)> {
+        self.order
+            .iter()
+            .filter_map(|&id| {
+                self.nodes[id]
+                    .as_ref()
+                    .map(|n| (n.name.clone(), n.exec.serialize_state(), n.output.to_vec()))
+            })
+            .collect()
+    }
+
+    /// Restore a single named node's `exec` state and output history from
+    /// a snapshot produced by `snapshot_nodes`. The caller must have
+    /// already rebuilt this graph with the same specs (so `name` resolves
+    /// to a node built with the same parameters as when the snapshot was
+    /// taken) -- this only restores a node's state, not graph shape.
+    pub fn restore_node(&mut self, name: &str, exec_state: &[u8], output: &[f64]) -> Result<()> {
+        let id = self.node_id(name).ok_or_else(|| HQuantError::UnknownIndicator(name.to_string()))?;
+        let node = self.nodes[id].as_mut().unwrap();
+        node.exec.deserialize_state(exec_state)?;
+        for &v in output {
+            node.output.push(v);
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.name_index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.name_index.is_empty()
+    }
+
+    /// The direct dependencies an [`Input`] reads from.
+    fn dependencies(input: Input) -> Vec<NodeId> {
+        match input {
+            Input::Field(_) => Vec::new(),
+            Input::Node(dep) => vec![dep],
+            Input::Combine(a, b, _) => vec![a, b],
+        }
+    }
+
+    fn insert(&mut self, name: &str, input: Input, exec: Box<dyn IndicatorExec>) -> Result<NodeId> {
+        if self.name_index.contains_key(name) {
+            return Err(HQuantError::DuplicateIndicator(name.to_string()));
+        }
+        let node = Node {
+            name: name.to_string(),
+            input,
+            exec,
+            output: CircularColumn::new(self.history),
+            ref_count: 0,
+            clamp: None,
+        };
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.nodes[id] = Some(node);
+                id
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        self.name_index.insert(name.to_string(), id);
+        self.order.push(id);
+        for dep in Self::dependencies(input) {
+            if let Some(dep_node) = self.nodes[dep].as_mut() {
+                dep_node.ref_count += 1;
+            }
+        }
+        Ok(id)
+    }
+
+    /// Remove a node, refusing (returning `false`) if another live node
+    /// still lists it as a dependency. On success, frees the id for reuse
+    /// and drops its dependencies' ref counts, so a chain of removals can
+    /// unwind a whole no-longer-used indicator one node at a time.
+    pub fn remove_indicator(&mut self, id: NodeId) -> bool {
+        let Some(Some(node)) = self.nodes.get(id) else {
+            return false;
+        };
+        if node.ref_count > 0 {
+            return false;
+        }
+        let node = self.nodes[id].take().unwrap();
+        self.name_index.remove(&node.name);
+        self.order.retain(|&n| n != id);
+        self.free.push(id);
+        for dep in Self::dependencies(node.input) {
+            if let Some(dep_node) = self.nodes[dep].as_mut() {
+                dep_node.ref_count -= 1;
+            }
+        }
+        true
+    }
+
+    /// Add a node whose input is a raw price/volume field.
+    pub fn add_field_indicator(
+        &mut self,
+        name: &str,
+        field: Field,
+        exec: Box<dyn IndicatorExec>,
+    ) -> Result<NodeId> {
+        self.insert(name, Input::Field(field), exec)
+    }
+
+    /// Add a node chained off another node's output, e.g. an EMA of an EMA.
+    pub fn add_chained_indicator(
+        &mut self,
+        name: &str,
+        source: NodeId,
+        exec: Box<dyn IndicatorExec>,
+    ) -> Result<NodeId> {
+        self.insert(name, Input::Node(source), exec)
+    }
+
+    /// Add a node whose input is two upstream nodes combined via `op`,
+    /// e.g. the momentum/absolute-momentum ratio behind TSI.
+    pub fn add_combined_indicator(
+        &mut self,
+        name: &str,
+        a: NodeId,
+        b: NodeId,
+        op: CombineOp,
+        exec: Box<dyn IndicatorExec>,
+    ) -> Result<NodeId> {
+        self.insert(name, Input::Combine(a, b, op), exec)
+    }
+
+    /// Add a well-known indicator from a spec string such as `"EMA_12"`,
+    /// reading from `Close`. Returns the id of the (possibly multi-node)
+    /// indicator's primary output.
+    pub fn add_from_spec(&mut self, name: &str) -> Result<NodeId> {
+        let spec = IndicatorSpec::parse(name)
+            .ok_or_else(|| HQuantError::InvalidSpec(name.to_string()))?;
+        spec.build(self, name)
+    }
+
+    /// Swap an indicator's parameters (e.g. `SMA_20` for `SMA_50`) without
+    /// losing its place in the graph: removes `old_id`, builds `new_spec`
+    /// (registered under `new_spec` itself, the same convention
+    /// `add_from_spec` uses), then replays `history` through only the
+    /// freshly built node(s) so the replacement isn't cold on the very next
+    /// bar.
+    ///
+    /// `history` must be the same bar history the rest of the graph was
+    /// already built from, oldest first (see [`crate::kline::KlineBuffer::to_bars`]).
+    /// Fails (leaving the old node in place) if `old_id` doesn't exist, is
+    /// still depended on by another node, or `new_spec` doesn't parse.
+    ///
+    /// For a single-node spec (`SMA`, `EMA`, ...) the returned id is always
+    /// `old_id` itself, reused via the same free-slot recycling
+    /// [`IndicatorGraph::remove_indicator`] uses elsewhere — so a strategy
+    /// already holding `old_id` keeps working unchanged. A multi-node spec
+    /// (`HMA`, `BOLL`, ...) only reuses `old_id` for its first internal
+    /// sub-node, not necessarily its primary output; callers replacing a
+    /// multi-node indicator must re-point any strategy to the returned id
+    /// themselves, since this graph has no strategy registry to patch.
+    pub fn replace(&mut self, old_id: NodeId, new_spec: &str, history: &[Bar]) -> Result<NodeId> {
+        let old_name = self
+            .nodes
+            .get(old_id)
+            .and_then(|n| n.as_ref())
+            .map(|n| n.name.clone())
+            .ok_or_else(|| HQuantError::InvalidSpec(format!("no indicator with id {old_id}")))?;
+        let spec = IndicatorSpec::parse(new_spec).ok_or_else(|| HQuantError::InvalidSpec(new_spec.to_string()))?;
+        if !self.remove_indicator(old_id) {
+            return Err(HQuantError::IndicatorInUse(old_name));
+        }
+        let backfill_from = self.order.len();
+        let new_id = spec.build(self, new_spec)?;
+        for bar in history {
+            for k in backfill_from..self.order.len() {
+                let i = self.order[k];
+                let raw = self.raw_input(i, bar);
+                let node = self.nodes[i].as_mut().unwrap();
+                let value = node.exec.push(raw);
+                node.output.push(value);
+            }
+        }
+        Ok(new_id)
+    }
+
+    /// Push a fully closed bar through every live node, in topological
+    /// order. A dependency is always processed before whatever reads it, so
+    /// a single forward pass is enough to keep every output current.
+    pub fn push_bar(&mut self, bar: &Bar) {
+        for k in 0..self.order.len() {
+            let i = self.order[k];
+            let raw = self.raw_input(i, bar);
+            let node = self.nodes[i].as_mut().unwrap();
+            let value = clamp_output(node.exec.push(raw), node.clamp);
+            node.output.push(value);
+        }
+    }
+
+    /// Revise the still-open bar (e.g. a live ticking candle) without
+    /// advancing history.
+    pub fn update_last(&mut self, bar: &Bar) {
+        for k in 0..self.order.len() {
+            let i = self.order[k];
+            let raw = self.raw_input(i, bar);
+            let node = self.nodes[i].as_mut().unwrap();
+            let value = clamp_output(node.exec.update_last(raw), node.clamp);
+            node.output.update_last(value);
+        }
+    }
+
+    /// Clear every live node's accumulated state -- its [`IndicatorExec`]'s
+    /// accumulators (via [`IndicatorExec::reset`]) and its output history --
+    /// while keeping the graph's shape (nodes, names, dependency wiring)
+    /// exactly as built. See [`crate::engine::HQuant::reset`], the caller
+    /// that needs a whole graph reusable across symbols without re-parsing
+    /// every spec.
+    pub fn reset(&mut self) {
+        for node in self.nodes.iter_mut().flatten() {
+            node.exec.reset();
+            node.output.clear();
+        }
+    }
+
+    /// Clamp `id`'s output to `[min, max]` before it's written to its
+    /// output column, for a bounded oscillator that can drift a hair
+    /// outside its documented range from float error. Pass `None` to turn
+    /// clamping back off. Returns `false` if `id` doesn't name a live node.
+    pub fn set_output_clamp(&mut self, id: NodeId, range: Option<(f64, f64)>) -> bool {
+        let Some(node) = self.nodes.get_mut(id).and_then(Option::as_mut) else {
+            return false;
+        };
+        node.clamp = range;
+        true
+    }
+
+    /// Resolve node `i`'s scalar input for the bar currently being
+    /// processed. `push_bar`/`update_last` process nodes in topological
+    /// order, so a dependency's output for this bar has already been
+    /// written by the time we get here.
+    fn raw_input(&self, i: NodeId, bar: &Bar) -> f64 {
+        match self.nodes[i].as_ref().unwrap().input {
+            Input::Field(f) => f.value(bar),
+            Input::Node(dep) => self.output_of(dep),
+            Input::Combine(a, b, op) => op.apply(self.output_of(a), self.output_of(b)),
+        }
+    }
+
+    fn output_of(&self, id: NodeId) -> f64 {
+        self.nodes[id].as_ref().and_then(|n| n.output.get_from_end(0)).unwrap_or(f64::NAN)
+    }
+
+    /// Read a node's output counting back from the most recent bar: `0` is
+    /// the current bar, `1` the previous one, and so on.
+    pub fn get_from_end(&self, id: NodeId, n: usize) -> Option<f64> {
+        self.nodes.get(id)?.as_ref()?.output.get_from_end(n)
+    }
+
+    /// Read a node's most recent *finite* output, scanning back through
+    /// history past any `NaN` (e.g. a still-forming bar with a bad input,
+    /// or plain warm-up) for consumers that want last-valid-forward-fill
+    /// display continuity instead of a flickering `NaN`. `None` only if
+    /// the node has no history yet, or every stored value is `NaN`.
+    pub fn get_last_valid(&self, id: NodeId) -> Option<f64> {
+        let node = self.nodes.get(id)?.as_ref()?;
+        (0..node.output.len()).find_map(|n| node.output.get_from_end(n).filter(|v| v.is_finite()))
+    }
+
+    /// Whether `id` has seen enough bars for its own output to mean
+    /// anything, per its [`IndicatorExec::min_periods`] -- an alternative
+    /// to a caller scanning for `NaN` themselves, since most execs never
+    /// actually emit `NaN` (an [`Sma`] just averages a partial window).
+    /// `false` for an unknown id, the same as every other id-keyed getter
+    /// here. Only checks `id`'s own warm-up, not any upstream node it
+    /// reads from. A multi-node indicator whose primary output is a
+    /// pass-through combine node (`RSI`, `CMO`, `CMF`, `MFI`, `MACD`, ...)
+    /// has a `min_periods` of `1` there, so it reports ready after its
+    /// first bar whatever its period; `BOLL`'s primary output, the middle
+    /// `Sma`, does wait for its full window.
+    pub fn is_ready(&self, id: NodeId) -> bool {
+        match self.nodes.get(id).and_then(|n| n.as_ref()) {
+            Some(node) => node.output.len() >= node.exec.min_periods(),
+            None => false,
+        }
+    }
+
+    /// Zero-copy `(raw_slice, capacity, len, head)` view over a node's
+    /// full output history, for exporting to numpy/typed-array bindings.
+    pub fn raw_view(&self, id: NodeId) -> Option<(&[f64], usize, usize, usize)> {
+        Some(self.nodes.get(id)?.as_ref()?.output.raw_view())
+    }
+
+    /// A node's full output history, oldest first. Prefer
+    /// [`IndicatorGraph::raw_view`] for zero-copy access; this is a
+    /// convenience for callers that just want a `Vec`.
+    pub fn series(&self, id: NodeId) -> Option<Vec<f64>> {
+        Some(self.nodes.get(id)?.as_ref()?.output.to_vec())
+    }
+
+    /// Compute SMA for every period in `periods` over the same `bars` in
+    /// one graph, so a parameter sweep only walks `bars` once no matter how
+    /// many periods are requested, instead of building and driving a
+    /// separate graph per period. Duplicate periods are computed once.
+    ///
+    /// SMA and EMA are the two sweeps added here; a `sweep_rsi` would need
+    /// its own scratch-graph machinery (RSI is multi-node, unlike SMA/EMA's
+    /// single field-reading node) rather than reusing [`IndicatorGraph::sweep`]
+    /// as-is.
+    pub fn sweep_sma(field: Field, periods: &[usize], bars: &[Bar]) -> HashMap<usize, Vec<f64>> {
+        Self::sweep(field, periods, bars, |period| Box::new(Sma::new(period)))
+    }
+
+    /// The EMA counterpart of [`IndicatorGraph::sweep_sma`].
+    pub fn sweep_ema(field: Field, periods: &[usize], bars: &[Bar]) -> HashMap<usize, Vec<f64>> {
+        Self::sweep(field, periods, bars, |period| Box::new(Ema::new(period)))
+    }
+
+    /// Shared machinery behind [`IndicatorGraph::sweep_sma`]/[`IndicatorGraph::sweep_ema`]:
+    /// one node per distinct period, all reading `field` off the same
+    /// scratch graph, driven through `bars` with a single `push_bar` per
+    /// bar.
+    fn sweep(
+        field: Field,
+        periods: &[usize],
+        bars: &[Bar],
+        make_exec: impl Fn(usize) -> Box<dyn IndicatorExec>,
+    ) -> HashMap<usize, Vec<f64>> {
+        let mut graph = IndicatorGraph::with_history(bars.len());
+        let mut ids = HashMap::new();
+        for &period in periods {
+            if ids.contains_key(&period) {
+                continue;
+            }
+            let id = graph
+                .add_field_indicator(&format!("__sweep_{period}"), field, make_exec(period))
+                .expect("sweep node names are unique per period");
+            ids.insert(period, id);
+        }
+        for bar in bars {
+            graph.push_bar(bar);
+        }
+        ids.into_iter().map(|(period, id)| (period, graph.series(id).unwrap_or_default())).collect()
+    }
+}
+
+impl Default for IndicatorGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(close: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn removing_a_node_still_depended_on_is_refused() {
+        let mut graph = IndicatorGraph::new();
+        let sma = graph.add_from_spec("SMA_5").unwrap();
+        graph.add_chained_indicator("EMA_OF_SMA", sma, Box::new(Ema::new(3))).unwrap();
+        assert!(!graph.remove_indicator(sma));
+        assert!(graph.node_id("SMA_5").is_some());
+    }
+
+    #[test]
+    fn a_shared_sma_dependency_is_reference_counted_across_bollinger_bands() {
+        let mut graph = IndicatorGraph::new();
+        let boll = boll::build(&mut graph, "BOLL_5", Field::Close, 5, 2.0).unwrap();
+        for close in [10.0, 11.0, 9.0, 12.0, 8.0] {
+            graph.push_bar(&bar(close));
+        }
+
+        // Both bands still depend on the middle SMA node: removal refused.
+        assert!(!graph.remove_indicator(boll.mid));
+
+        assert!(graph.remove_indicator(boll.upper));
+        // Lower still depends on it.
+        assert!(!graph.remove_indicator(boll.mid));
+
+        assert!(graph.remove_indicator(boll.lower));
+        // Nothing depends on the SMA anymore: removable.
+        assert!(graph.remove_indicator(boll.mid));
+        assert!(graph.node_id("BOLL_5").is_none());
+
+        // The freed id is available again for an unrelated indicator.
+        let reused = graph.add_from_spec("SMA_20").unwrap();
+        assert_eq!(graph.get_from_end(reused, 0), None);
+        graph.push_bar(&bar(100.0));
+        assert_eq!(graph.get_from_end(reused, 0), Some(100.0));
+    }
+
+    #[test]
+    fn replace_swaps_the_period_and_backfills_over_history_without_leaking_the_old_node() {
+        let mut graph = IndicatorGraph::new();
+        let sma20 = graph.add_from_spec("SMA_20").unwrap();
+        let history: Vec<Bar> = (1..=30).map(|i| bar(i as f64)).collect();
+        for b in &history {
+            graph.push_bar(b);
+        }
+        let before_len = graph.len();
+
+        let sma50 = graph.replace(sma20, "SMA_50", &history).unwrap();
+
+        // A single-node spec reuses the freed slot: same id, not a leak.
+        assert_eq!(sma50, sma20);
+        assert_eq!(graph.len(), before_len);
+        assert!(graph.node_id("SMA_20").is_none());
+        assert_eq!(graph.node_id("SMA_50"), Some(sma50));
+
+        // Backfilled immediately: SMA(50) of the last min(50, 30) values.
+        let expected: f64 = history.iter().map(|b| b.close).sum::<f64>() / history.len() as f64;
+        assert!((graph.get_from_end(sma50, 0).unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn replace_is_refused_while_the_old_node_still_has_a_dependent() {
+        let mut graph = IndicatorGraph::new();
+        let sma = graph.add_from_spec("SMA_20").unwrap();
+        graph.add_chained_indicator("EMA_OF_SMA", sma, Box::new(Ema::new(3))).unwrap();
+        assert!(graph.replace(sma, "SMA_50", &[]).is_err());
+    }
+
+    #[test]
+    fn output_clamp_snaps_drift_to_the_boundary_but_is_off_by_default() {
+        let mut graph = IndicatorGraph::new();
+        let id = graph.add_field_indicator("RAW", Field::Close, Box::new(exec::Identity)).unwrap();
+
+        // No clamp set: a value outside [0, 100] passes through untouched.
+        graph.push_bar(&bar(105.0));
+        assert_eq!(graph.get_from_end(id, 0), Some(105.0));
+
+        assert!(graph.set_output_clamp(id, Some((0.0, 100.0))));
+        graph.push_bar(&bar(110.0));
+        assert_eq!(graph.get_from_end(id, 0), Some(100.0));
+        graph.push_bar(&bar(-5.0));
+        assert_eq!(graph.get_from_end(id, 0), Some(0.0));
+
+        // Turning it back off restores the raw drift.
+        assert!(graph.set_output_clamp(id, None));
+        graph.push_bar(&bar(-42.0));
+        assert_eq!(graph.get_from_end(id, 0), Some(-42.0));
+    }
+
+    /// `Sma` keeps its own `VecDeque` window sized to `period`, entirely
+    /// independent of a node's output `CircularColumn` (sized by
+    /// `IndicatorGraph::history`). So even when the graph's history
+    /// capacity is set right at `period + 1` and wraps on every push past
+    /// it, `Sma`'s rolling sum can't read a slot the output ring is about
+    /// to overwrite -- there's no shared storage between the two. This
+    /// test pins that down against a brute-force window recomputed from
+    /// scratch every bar.
+    #[test]
+    fn sma_matches_brute_force_when_graph_history_capacity_wraps_at_period_plus_one() {
+        let period = 5;
+        let mut graph = IndicatorGraph::with_history(period + 1);
+        let sma = graph.add_from_spec("SMA_5").unwrap();
+
+        let mut closes: Vec<f64> = Vec::new();
+        for i in 1..=(3 * (period + 1)) {
+            let close = i as f64;
+            graph.push_bar(&bar(close));
+            closes.push(close);
+
+            let window = &closes[closes.len().saturating_sub(period)..];
+            let expected = window.iter().sum::<f64>() / window.len() as f64;
+            assert!((graph.get_from_end(sma, 0).unwrap() - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn sweep_sma_matches_adding_each_period_one_at_a_time() {
+        let bars: Vec<Bar> = (1..=60).map(|i| bar(100.0 + i as f64)).collect();
+        let periods = [5, 10, 20, 20];
+
+        let swept = IndicatorGraph::sweep_sma(Field::Close, &periods, &bars);
+        assert_eq!(swept.len(), 3); // the duplicate 20 collapses to one entry
+
+        for &period in &periods {
+            let mut graph = IndicatorGraph::with_history(bars.len());
+            let id = graph.add_field_indicator("SMA", Field::Close, Box::new(Sma::new(period))).unwrap();
+            for b in &bars {
+                graph.push_bar(b);
+            }
+            assert_eq!(swept[&period], graph.series(id).unwrap());
+        }
+    }
+
+    #[test]
+    fn sweep_ema_matches_adding_each_period_one_at_a_time() {
+        let bars: Vec<Bar> = (1..=60).map(|i| bar(100.0 + i as f64)).collect();
+        let periods = [3, 12, 26];
+
+        let swept = IndicatorGraph::sweep_ema(Field::Close, &periods, &bars);
+
+        for &period in &periods {
+            let mut graph = IndicatorGraph::with_history(bars.len());
+            let id = graph.add_field_indicator("EMA", Field::Close, Box::new(Ema::new(period))).unwrap();
+            for b in &bars {
+                graph.push_bar(b);
+            }
+            assert_eq!(swept[&period], graph.series(id).unwrap());
+        }
+    }
+
+    // A single-node SMA(14), whose own `min_periods` is its window. A
+    // multi-node RSI(14) doesn't show this: its primary output is a
+    // pass-through combine node with a `min_periods` of 1 (see
+    // `IndicatorGraph::is_ready`).
+    #[test]
+    fn a_fresh_sma14_is_not_ready_until_14_bars_have_been_pushed() {
+        let mut graph = IndicatorGraph::new();
+        let sma = graph.add_from_spec("SMA_14").unwrap();
+
+        for i in 0..13 {
+            graph.push_bar(&bar(100.0 + i as f64));
+            assert!(!graph.is_ready(sma), "ready too early at bar {}", i + 1);
+        }
+        graph.push_bar(&bar(113.0));
+        assert!(graph.is_ready(sma));
+    }
+
+    #[test]
+    fn is_ready_is_false_for_an_unknown_id() {
+        let graph = IndicatorGraph::new();
+        assert!(!graph.is_ready(0));
+    }
+}
@@ -0,0 +1,87 @@
+//! On-Balance Volume: a running total of volume, added on an up close and
+//! subtracted on a down close, unchanged on a flat close.
+//!
+//! The only cross-bar lag OBV needs -- `close_t` vs `close_{t-1}` -- is
+//! already fully encapsulated inside [`super::exec::Momentum`] as a single
+//! field node, so unlike ADX (see [`super::adx`]) this fits the graph's
+//! one-field-per-node contract directly instead of needing a standalone
+//! transform: the momentum node's sign gates a volume node via
+//! [`CombineOp::SignedVolume`], and the result feeds a running
+//! [`super::exec::CumulativeSum`].
+
+use super::exec::{CumulativeSum, Identity, Momentum};
+use super::{CombineOp, IndicatorGraph, NodeId};
+use crate::error::Result;
+use crate::kline::Field;
+
+/// Build the OBV DAG chain under `base_name`, registering the intermediate
+/// momentum/volume/signed-volume nodes with `base_name__`-prefixed internal
+/// names the same way [`super::tsi::build`] does. Returns the OBV node id.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str) -> Result<NodeId> {
+    let mom = graph.add_field_indicator(&format!("{base_name}__mom"), Field::Close, Box::new(Momentum::new()))?;
+    let volume = graph.add_field_indicator(&format!("{base_name}__volume"), Field::Volume, Box::new(Identity))?;
+    let signed_volume = graph.add_combined_indicator(
+        &format!("{base_name}__signed_volume"),
+        mom,
+        volume,
+        CombineOp::SignedVolume,
+        Box::new(Identity),
+    )?;
+    graph.add_chained_indicator(base_name, signed_volume, Box::new(CumulativeSum::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(close: f64, volume: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume }
+    }
+
+    #[test]
+    fn accumulates_signed_volume_on_each_close_to_close_move() {
+        let mut graph = IndicatorGraph::new();
+        let obv = build(&mut graph, "OBV").unwrap();
+
+        // First bar has no prior close, so it contributes 0.0.
+        graph.push_bar(&bar(10.0, 100.0));
+        assert_eq!(graph.get_from_end(obv, 0).unwrap(), 0.0);
+
+        // Up close: +200.
+        graph.push_bar(&bar(11.0, 200.0));
+        assert_eq!(graph.get_from_end(obv, 0).unwrap(), 200.0);
+
+        // Down close: -50.
+        graph.push_bar(&bar(9.0, 50.0));
+        assert_eq!(graph.get_from_end(obv, 0).unwrap(), 150.0);
+
+        // Flat close: unchanged.
+        graph.push_bar(&bar(9.0, 999.0));
+        assert_eq!(graph.get_from_end(obv, 0).unwrap(), 150.0);
+    }
+
+    #[test]
+    fn update_last_reproduces_a_fresh_push() {
+        let mut a = IndicatorGraph::new();
+        let obv_a = build(&mut a, "OBV").unwrap();
+        let mut b = IndicatorGraph::new();
+        let obv_b = build(&mut b, "OBV").unwrap();
+
+        a.push_bar(&bar(10.0, 100.0));
+        b.push_bar(&bar(10.0, 100.0));
+        a.push_bar(&bar(11.0, 100.0));
+        b.push_bar(&bar(11.0, 100.0));
+
+        // `a` opens the new bar with a placeholder, then revises it in
+        // place as the candle ticks toward its real close.
+        a.push_bar(&bar(12.0, 50.0));
+        a.update_last(&bar(9.0, 300.0));
+        // `b` sees the real close directly.
+        b.push_bar(&bar(9.0, 300.0));
+
+        let va = a.get_from_end(obv_a, 0).unwrap();
+        let vb = b.get_from_end(obv_b, 0).unwrap();
+        assert!((va - vb).abs() < 1e-9, "expected {va} to match fresh push {vb}");
+    }
+}
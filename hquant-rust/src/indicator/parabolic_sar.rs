@@ -0,0 +1,200 @@
+//! Parabolic SAR (stop-and-reverse): a trailing stop that accelerates
+//! toward price as a trend extends, flipping to the opposite side once
+//! price crosses it.
+//!
+//! ```text
+//! sar[t] = sar[t-1] + af[t-1] * (ep[t-1] - sar[t-1])
+//! ```
+//!
+//! clamped so an uptrend's SAR never rises above the prior bar's low (a
+//! downtrend's never falls below the prior bar's high), and reversing
+//! outright -- SAR jumps to the old extreme point, `ep` resets to the
+//! current bar's opposite extreme, and `af` resets to `af_start` -- the
+//! moment price crosses it. Textbook Wilder's SAR clamps against the prior
+//! *two* bars' extremes; this only keeps the immediately preceding bar
+//! (like [`super::adx::Adx`], which only needs one bar of joint high/low/close
+//! state for its own true-range recurrence), a simplification worth
+//! knowing about if this is ever checked against a reference implementation
+//! bar-for-bar.
+//!
+//! Like [`super::adx::Adx`] and [`super::kdj::Kdj`], SAR needs a bar's high
+//! and low jointly, more than [`super::IndicatorExec`]'s single-scalar
+//! contract can express, so it's implemented as a standalone transform in
+//! the same style rather than as an `IndicatorGraph`-registered node.
+
+use crate::kline::Bar;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Up,
+    Down,
+}
+
+/// One bar's SAR output: the stop level itself, plus the trend it belongs
+/// to (the `extra` component the request describes).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SarOutput {
+    pub sar: f64,
+    pub trend: Trend,
+}
+
+/// Everything needed to resume the recurrence from a given bar: the SAR
+/// level and trend `SarOutput` already carries, plus the extreme point,
+/// acceleration factor, and the bar itself (for the next bar's clamp).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SarState {
+    sar: f64,
+    ep: f64,
+    af: f64,
+    trend: Trend,
+    bar: Bar,
+    /// Whether this is the very first bar's placeholder state, which has
+    /// no real trend behind it yet (a single bar alone can't say which way
+    /// price is moving). The *next* bar resolves it into a real initial
+    /// trend by comparing against this one, rather than the general
+    /// recurrence -- so the placeholder's arbitrary `Trend::Up` never
+    /// counts as a real flip when it's superseded.
+    is_seed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParabolicSar {
+    af_start: f64,
+    af_step: f64,
+    af_max: f64,
+    /// State as of the end of the last fully closed bar.
+    committed: Option<SarState>,
+    /// State for the bar currently being built, if any.
+    current: Option<SarState>,
+}
+
+impl ParabolicSar {
+    pub fn new(af_start: f64, af_step: f64, af_max: f64) -> Self {
+        assert!(af_start > 0.0 && af_step > 0.0 && af_max >= af_start, "invalid Parabolic SAR acceleration factors");
+        Self { af_start, af_step, af_max, committed: None, current: None }
+    }
+
+    fn compute(&self, bar: &Bar) -> SarState {
+        let Some(prev) = self.committed else {
+            // No prior bar at all; place a seed with an arbitrary trend
+            // the next bar will resolve for real.
+            return SarState { sar: bar.low, ep: bar.high, af: self.af_start, trend: Trend::Up, bar: *bar, is_seed: true };
+        };
+
+        if prev.is_seed {
+            // Resolve the real initial trend from how this bar moved
+            // against the seed, rather than trusting the seed's guess.
+            return if bar.high > prev.bar.high {
+                SarState { sar: prev.bar.low, ep: bar.high, af: self.af_start, trend: Trend::Up, bar: *bar, is_seed: false }
+            } else {
+                SarState { sar: prev.bar.high, ep: bar.low, af: self.af_start, trend: Trend::Down, bar: *bar, is_seed: false }
+            };
+        }
+
+        let raw_sar = prev.sar + prev.af * (prev.ep - prev.sar);
+
+        match prev.trend {
+            Trend::Up => {
+                let sar = raw_sar.min(prev.bar.low);
+                if bar.low < sar {
+                    SarState { sar: prev.ep, ep: bar.low, af: self.af_start, trend: Trend::Down, bar: *bar, is_seed: false }
+                } else if bar.high > prev.ep {
+                    SarState {
+                        sar,
+                        ep: bar.high,
+                        af: (prev.af + self.af_step).min(self.af_max),
+                        trend: Trend::Up,
+                        bar: *bar,
+                        is_seed: false,
+                    }
+                } else {
+                    SarState { sar, ep: prev.ep, af: prev.af, trend: Trend::Up, bar: *bar, is_seed: false }
+                }
+            }
+            Trend::Down => {
+                let sar = raw_sar.max(prev.bar.high);
+                if bar.high > sar {
+                    SarState { sar: prev.ep, ep: bar.high, af: self.af_start, trend: Trend::Up, bar: *bar, is_seed: false }
+                } else if bar.low < prev.ep {
+                    SarState {
+                        sar,
+                        ep: bar.low,
+                        af: (prev.af + self.af_step).min(self.af_max),
+                        trend: Trend::Down,
+                        bar: *bar,
+                        is_seed: false,
+                    }
+                } else {
+                    SarState { sar, ep: prev.ep, af: prev.af, trend: Trend::Down, bar: *bar, is_seed: false }
+                }
+            }
+        }
+    }
+
+    /// Commit the previous bar's state permanently, then compute SAR for
+    /// `bar`.
+    pub fn push(&mut self, bar: &Bar) -> SarOutput {
+        if let Some(prev) = self.current.take() {
+            self.committed = Some(prev);
+        }
+        let state = self.compute(bar);
+        self.current = Some(state);
+        SarOutput { sar: state.sar, trend: state.trend }
+    }
+
+    /// Revise the current (not yet committed) bar's SAR in place, against
+    /// the same committed previous state `push` last used.
+    pub fn update_last(&mut self, bar: &Bar) -> SarOutput {
+        let state = self.compute(bar);
+        self.current = Some(state);
+        SarOutput { sar: state.sar, trend: state.trend }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(high: f64, low: f64) -> Bar {
+        Bar { ts: 0, open: (high + low) / 2.0, high, low, close: (high + low) / 2.0, volume: 1.0 }
+    }
+
+    #[test]
+    fn a_v_shaped_price_path_flips_exactly_once() {
+        let mut sar = ParabolicSar::new(0.02, 0.02, 0.2);
+
+        // Downtrend into the V, then an uptrend back out of it.
+        let down: Vec<Bar> = (0..15).map(|i| bar(100.0 - i as f64, 90.0 - i as f64)).collect();
+        let up: Vec<Bar> = (0..15).map(|i| bar(86.0 + i as f64 * 2.0, 76.0 + i as f64 * 2.0)).collect();
+
+        let outputs: Vec<SarOutput> = down.iter().chain(up.iter()).map(|b| sar.push(b)).collect();
+
+        // The very first bar's trend is an arbitrary seed with no real
+        // price action behind it yet -- the second bar resolving it into
+        // the real initial (down)trend isn't a "flip", so count changes
+        // from there on.
+        let flips = outputs[1..].windows(2).filter(|w| w[0].trend != w[1].trend).count();
+        assert_eq!(flips, 1);
+    }
+
+    #[test]
+    fn update_last_is_idempotent_and_ignores_a_since_discarded_live_bar() {
+        let mut sar = ParabolicSar::new(0.02, 0.02, 0.2);
+        sar.push(&bar(101.0, 99.0));
+        // A live bar that gets revised away -- its EP/AF bump must not leak
+        // into `update_last`, which always recomputes from the same
+        // committed previous bar `push` last used.
+        sar.push(&bar(103.0, 101.0));
+        let revised = sar.update_last(&bar(104.0, 102.0));
+        let revised_again = sar.update_last(&bar(104.0, 102.0));
+        assert_eq!(revised, revised_again);
+    }
+
+    #[test]
+    fn first_bar_starts_in_an_uptrend_pinned_to_its_own_low() {
+        let mut sar = ParabolicSar::new(0.02, 0.02, 0.2);
+        let out = sar.push(&bar(110.0, 100.0));
+        assert_eq!(out.sar, 100.0);
+        assert_eq!(out.trend, Trend::Up);
+    }
+}
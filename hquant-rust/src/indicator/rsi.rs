@@ -0,0 +1,146 @@
+//! Relative Strength Index: `100 - 100 / (1 + RS)`, `RS = avg_gain /
+//! avg_loss` over a rolling window of close-to-close moves.
+//!
+//! The only cross-bar lag RSI needs -- `close_t` vs `close_{t-1}` -- is
+//! already fully encapsulated inside [`super::exec::Momentum`] as a single
+//! field node, so like [`super::obv`] this fits the graph's
+//! one-field-per-node contract directly: the momentum node's positive and
+//! negative parts (see [`super::exec::PositivePart`]/[`super::exec::NegativePart`])
+//! feed whichever smoothing the caller picked, and `100 * avg_gain /
+//! (avg_gain + avg_loss)` (algebraically `100 - 100 / (1 + RS)`, without
+//! dividing by average loss directly) is [`CombineOp::PercentOfTotal`].
+
+use super::exec::{Ema, NegativePart, PositivePart, Sma};
+use super::{CombineOp, IndicatorExec, IndicatorGraph, NodeId};
+use crate::error::Result;
+use crate::kline::Field;
+
+/// How RSI's average gain/loss are smoothed. Different platforms disagree
+/// here -- TradingView's default `rma` is [`RsiSmoothing::Wilder`], but a
+/// plain trailing average ([`RsiSmoothing::Sma`]) or a standard EMA
+/// ([`RsiSmoothing::Ema`]) both show up too, and each gives a different RSI
+/// value on the same input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RsiSmoothing {
+    /// Wilder's original smoothing: an EMA with `alpha = 1 / period`
+    /// instead of the usual `2 / (period + 1)`. The default, matching the
+    /// convention most charting packages use for a bare `RSI_14`.
+    Wilder,
+    /// A plain trailing average recomputed over the last `period` gains
+    /// and losses, with no recursive smoothing.
+    Sma,
+    /// A standard EMA, `alpha = 2 / (period + 1)`.
+    Ema,
+}
+
+fn smoothing_exec(smoothing: RsiSmoothing, period: usize) -> Box<dyn IndicatorExec> {
+    match smoothing {
+        RsiSmoothing::Wilder => Box::new(Ema::with_alpha(1.0 / period as f64)),
+        RsiSmoothing::Sma => Box::new(Sma::new(period)),
+        RsiSmoothing::Ema => Box::new(Ema::new(period)),
+    }
+}
+
+/// Build the RSI DAG chain under `base_name`, registering the intermediate
+/// momentum/gain/loss/average nodes with `base_name__`-prefixed internal
+/// names the same way [`super::tsi::build`] does. Returns the RSI node id.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str, period: usize, smoothing: RsiSmoothing) -> Result<NodeId> {
+    assert!(period > 0, "RSI period must be > 0");
+
+    let mom = graph.add_field_indicator(&format!("{base_name}__mom"), Field::Close, Box::new(super::exec::Momentum::new()))?;
+    let gain = graph.add_chained_indicator(&format!("{base_name}__gain"), mom, Box::new(PositivePart))?;
+    let loss = graph.add_chained_indicator(&format!("{base_name}__loss"), mom, Box::new(NegativePart))?;
+    let avg_gain = graph.add_chained_indicator(&format!("{base_name}__avg_gain"), gain, smoothing_exec(smoothing, period))?;
+    let avg_loss = graph.add_chained_indicator(&format!("{base_name}__avg_loss"), loss, smoothing_exec(smoothing, period))?;
+
+    graph.add_combined_indicator(base_name, avg_gain, avg_loss, CombineOp::PercentOfTotal, Box::new(super::exec::Identity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(close: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn rsi_is_100_when_every_move_is_a_gain() {
+        let mut graph = IndicatorGraph::new();
+        let rsi = build(&mut graph, "RSI_5", 5, RsiSmoothing::Wilder).unwrap();
+
+        let mut price = 100.0;
+        for _ in 0..10 {
+            graph.push_bar(&bar(price));
+            price += 1.0;
+        }
+
+        assert!((graph.get_from_end(rsi, 0).unwrap() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rsi_is_0_when_every_move_is_a_loss() {
+        let mut graph = IndicatorGraph::new();
+        let rsi = build(&mut graph, "RSI_5", 5, RsiSmoothing::Wilder).unwrap();
+
+        let mut price = 100.0;
+        for _ in 0..10 {
+            graph.push_bar(&bar(price));
+            price -= 1.0;
+        }
+
+        assert!((graph.get_from_end(rsi, 0).unwrap() - 0.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wilder_sma_and_ema_smoothing_disagree_on_the_same_input() {
+        let prices = [100.0, 102.0, 101.0, 103.0, 106.0, 104.0, 107.0, 105.0, 108.0, 110.0];
+
+        let mut wilder_val = None;
+        let mut sma_val = None;
+        let mut ema_val = None;
+        for (smoothing, slot) in [
+            (RsiSmoothing::Wilder, &mut wilder_val),
+            (RsiSmoothing::Sma, &mut sma_val),
+            (RsiSmoothing::Ema, &mut ema_val),
+        ] {
+            let mut graph = IndicatorGraph::new();
+            let rsi = build(&mut graph, "RSI_5", 5, smoothing).unwrap();
+            for &p in &prices {
+                graph.push_bar(&bar(p));
+            }
+            *slot = graph.get_from_end(rsi, 0);
+        }
+
+        let (wilder_val, sma_val, ema_val) = (wilder_val.unwrap(), sma_val.unwrap(), ema_val.unwrap());
+        assert!((wilder_val - sma_val).abs() > 1e-6, "expected Wilder and SMA smoothing to diverge, both gave {wilder_val}");
+        assert!((wilder_val - ema_val).abs() > 1e-6, "expected Wilder and EMA smoothing to diverge, both gave {wilder_val}");
+    }
+
+    #[test]
+    fn update_last_reproduces_a_fresh_push() {
+        let mut a = IndicatorGraph::new();
+        let rsi_a = build(&mut a, "RSI_5", 5, RsiSmoothing::Sma).unwrap();
+        let mut b = IndicatorGraph::new();
+        let rsi_b = build(&mut b, "RSI_5", 5, RsiSmoothing::Sma).unwrap();
+
+        let prices = [100.0, 101.0, 99.0, 105.0, 110.0, 108.0];
+        for &p in &prices[..prices.len() - 1] {
+            a.push_bar(&bar(p));
+            b.push_bar(&bar(p));
+        }
+        let last = *prices.last().unwrap();
+
+        // `a` opens the new bar with a placeholder, then revises it in
+        // place as the candle ticks toward its real close.
+        a.push_bar(&bar(last - 1.0));
+        a.update_last(&bar(last));
+        // `b` sees the real close directly.
+        b.push_bar(&bar(last));
+
+        let va = a.get_from_end(rsi_a, 0).unwrap();
+        let vb = b.get_from_end(rsi_b, 0).unwrap();
+        assert!((va - vb).abs() < 1e-9, "expected {va} to match fresh push {vb}");
+    }
+}
@@ -0,0 +1,451 @@
+//! Parsing of indicator spec strings, as used by the DSL and by callers
+//! adding indicators by name. Two shapes are supported: `KIND_period`
+//! (e.g. `"EMA_12"`) for single-parameter indicators, and `KIND(a,b,...)`
+//! (e.g. `"TSI(25,13)"`) for ones needing more than one.
+
+use super::exec::{Ema, Kama, Sma, Wma, Zscore};
+use super::{adl, boll, cmf, cmo, dema, donchian, hma, macd, mfi, obv, rsi, tema, trix, tsi};
+use super::{IndicatorGraph, NodeId, RsiSmoothing};
+use crate::error::{HQuantError, Result};
+use crate::kline::Field;
+
+/// Default band width (in standard deviations) for the `BOLL_n` shorthand,
+/// which has no room for a third argument.
+const DEFAULT_BOLL_NUM_STD: f64 = 2.0;
+
+/// Default fast/slow EMA periods for the `KAMA_n` shorthand, which has no
+/// room for the other two arguments -- Kaufman's own originally published
+/// constants.
+const DEFAULT_KAMA_FAST_PERIOD: usize = 2;
+const DEFAULT_KAMA_SLOW_PERIOD: usize = 30;
+
+fn parse_smoothing(name: &str) -> Option<RsiSmoothing> {
+    match name.to_ascii_uppercase().as_str() {
+        "WILDER" => Some(RsiSmoothing::Wilder),
+        "SMA" => Some(RsiSmoothing::Sma),
+        "EMA" => Some(RsiSmoothing::Ema),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndicatorSpec {
+    Sma { field: Field, period: usize },
+    Ema { field: Field, period: usize },
+    Tsi { r: usize, s: usize },
+    Wma { field: Field, period: usize },
+    Hma { field: Field, period: usize },
+    Dema { field: Field, period: usize },
+    Tema { field: Field, period: usize },
+    Boll { field: Field, period: usize, num_std: f64 },
+    BollPercentB { field: Field, period: usize, num_std: f64 },
+    BollBandwidth { field: Field, period: usize, num_std: f64 },
+    Donchian { period: usize },
+    Macd { fast_period: usize, slow_period: usize, signal_period: usize },
+    /// On-Balance Volume. Takes no period -- it's a running total over the
+    /// whole history, like [`IndicatorSpec::Adl`] -- so it parses from the
+    /// bare keyword `"OBV"` rather than the usual `KIND_period` shape.
+    Obv,
+    /// Accumulation/Distribution Line. Parses from the bare keyword `"ADL"`
+    /// for the same reason as [`IndicatorSpec::Obv`].
+    Adl,
+    Cmf { period: usize },
+    Mfi { period: usize },
+    Rsi { period: usize, smoothing: RsiSmoothing },
+    Kama { period: usize, fast_period: usize, slow_period: usize },
+    Cmo { period: usize },
+    Trix { field: Field, period: usize },
+    /// How many standard deviations `field` sits from its own rolling mean
+    /// -- `ZSCORE(spread, 20)` on a [`crate::spread::SpreadBuilder`]'s
+    /// synthetic close is the main use case, but it works over any field.
+    Zscore { field: Field, period: usize },
+}
+
+impl IndicatorSpec {
+    pub fn parse(spec: &str) -> Option<Self> {
+        match spec.to_ascii_uppercase().as_str() {
+            "OBV" => return Some(IndicatorSpec::Obv),
+            "ADL" => return Some(IndicatorSpec::Adl),
+            _ => {}
+        }
+        if let Some((kind, args)) = spec.split_once('(') {
+            let args = args.strip_suffix(')')?;
+            let kind = kind.to_ascii_uppercase();
+            if kind == "SMA" || kind == "EMA" || kind == "WMA" || kind == "HMA" || kind == "DEMA" || kind == "TEMA" || kind == "TRIX" || kind == "ZSCORE" {
+                let (field, period) = args.split_once(',')?;
+                let field = Field::from_name(field.trim())?;
+                let period: usize = period.trim().parse().ok()?;
+                return Some(match kind.as_str() {
+                    "SMA" => IndicatorSpec::Sma { field, period },
+                    "EMA" => IndicatorSpec::Ema { field, period },
+                    "WMA" => IndicatorSpec::Wma { field, period },
+                    "HMA" => IndicatorSpec::Hma { field, period },
+                    "DEMA" => IndicatorSpec::Dema { field, period },
+                    "TEMA" => IndicatorSpec::Tema { field, period },
+                    "ZSCORE" => IndicatorSpec::Zscore { field, period },
+                    _ => IndicatorSpec::Trix { field, period },
+                });
+            }
+            if kind == "BOLL" || kind == "BOLLPB" || kind == "BOLLBW" {
+                let mut parts = args.split(',');
+                let field = Field::from_name(parts.next()?.trim())?;
+                let period: usize = parts.next()?.trim().parse().ok()?;
+                let num_std: f64 = match parts.next() {
+                    Some(n) => n.trim().parse().ok()?,
+                    None => DEFAULT_BOLL_NUM_STD,
+                };
+                return Some(match kind.as_str() {
+                    "BOLL" => IndicatorSpec::Boll { field, period, num_std },
+                    "BOLLPB" => IndicatorSpec::BollPercentB { field, period, num_std },
+                    _ => IndicatorSpec::BollBandwidth { field, period, num_std },
+                });
+            }
+            if kind == "RSI" {
+                let mut parts = args.split(',');
+                let period: usize = parts.next()?.trim().parse().ok()?;
+                let smoothing = match parts.next() {
+                    Some(s) => parse_smoothing(s.trim())?,
+                    None => RsiSmoothing::Wilder,
+                };
+                return Some(IndicatorSpec::Rsi { period, smoothing });
+            }
+            let parts: Vec<usize> = args
+                .split(',')
+                .map(|p| p.trim().parse().ok())
+                .collect::<Option<_>>()?;
+            return match (kind.as_str(), parts.as_slice()) {
+                ("TSI", [r, s]) => Some(IndicatorSpec::Tsi { r: *r, s: *s }),
+                ("MACD", [fast_period, slow_period, signal_period]) => {
+                    Some(IndicatorSpec::Macd { fast_period: *fast_period, slow_period: *slow_period, signal_period: *signal_period })
+                }
+                ("CMF", [period]) => Some(IndicatorSpec::Cmf { period: *period }),
+                ("MFI", [period]) => Some(IndicatorSpec::Mfi { period: *period }),
+                ("CMO", [period]) => Some(IndicatorSpec::Cmo { period: *period }),
+                ("KAMA", [period, fast_period, slow_period]) => Some(IndicatorSpec::Kama {
+                    period: *period,
+                    fast_period: *fast_period,
+                    slow_period: *slow_period,
+                }),
+                _ => None,
+            };
+        }
+        Self::parse_underscore(spec)
+    }
+
+    /// Parse specs of the form `KIND_period`, e.g. `"EMA_12"` or `"SMA_20"`.
+    /// Defaults to `Close` for the ones that take a field, matching the
+    /// function-call syntax's most common usage.
+    fn parse_underscore(spec: &str) -> Option<Self> {
+        let (kind, period) = spec.rsplit_once('_')?;
+        let period: usize = period.parse().ok()?;
+        match kind.to_ascii_uppercase().as_str() {
+            "SMA" => Some(IndicatorSpec::Sma { field: Field::Close, period }),
+            "EMA" => Some(IndicatorSpec::Ema { field: Field::Close, period }),
+            "WMA" => Some(IndicatorSpec::Wma { field: Field::Close, period }),
+            "HMA" => Some(IndicatorSpec::Hma { field: Field::Close, period }),
+            "DEMA" => Some(IndicatorSpec::Dema { field: Field::Close, period }),
+            "TEMA" => Some(IndicatorSpec::Tema { field: Field::Close, period }),
+            "TRIX" => Some(IndicatorSpec::Trix { field: Field::Close, period }),
+            "ZSCORE" => Some(IndicatorSpec::Zscore { field: Field::Close, period }),
+            "BOLL" => Some(IndicatorSpec::Boll { field: Field::Close, period, num_std: DEFAULT_BOLL_NUM_STD }),
+            "BOLLPB" => Some(IndicatorSpec::BollPercentB { field: Field::Close, period, num_std: DEFAULT_BOLL_NUM_STD }),
+            "BOLLBW" => Some(IndicatorSpec::BollBandwidth { field: Field::Close, period, num_std: DEFAULT_BOLL_NUM_STD }),
+            "DONCHIAN" => Some(IndicatorSpec::Donchian { period }),
+            "CMF" => Some(IndicatorSpec::Cmf { period }),
+            "MFI" => Some(IndicatorSpec::Mfi { period }),
+            "CMO" => Some(IndicatorSpec::Cmo { period }),
+            "RSI" => Some(IndicatorSpec::Rsi { period, smoothing: RsiSmoothing::Wilder }),
+            "KAMA" => Some(IndicatorSpec::Kama {
+                period,
+                fast_period: DEFAULT_KAMA_FAST_PERIOD,
+                slow_period: DEFAULT_KAMA_SLOW_PERIOD,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Build this spec's node(s) and return the primary output's id. For
+    /// multi-node indicators, other outputs are still reachable by name:
+    /// `TSI(25,13)_signal`-style suffixes for TSI, `BOLL_20_upper`/`_lower` for
+    /// Bollinger Bands, `DONCHIAN_20_upper`/`_lower` for the Donchian
+    /// Channel (the middle band is the primary output in both cases), and
+    /// `MACD(12,26,9)_signal`/`_histogram` for MACD (the MACD line is the
+    /// primary output). `OBV`/`ADL`/`CMF_20`/`RSI_14`/`CMO_14`/`TRIX_10`/
+    /// `ZSCORE_20`/`MFI_14` have no secondary outputs.
+    pub fn build(self, graph: &mut IndicatorGraph, name: &str) -> Result<NodeId> {
+        match self {
+            IndicatorSpec::Sma { field, period } => graph.add_field_indicator(name, field, Box::new(Sma::new(period))),
+            IndicatorSpec::Ema { field, period } => graph.add_field_indicator(name, field, Box::new(Ema::new(period))),
+            IndicatorSpec::Tsi { r, s } => {
+                if r == 0 || s == 0 {
+                    return Err(HQuantError::InvalidSpec(name.to_string()));
+                }
+                tsi::build(graph, name, r, s)
+            }
+            IndicatorSpec::Wma { field, period } => graph.add_field_indicator(name, field, Box::new(Wma::new(period))),
+            IndicatorSpec::Hma { field, period } => hma::build(graph, name, field, period),
+            IndicatorSpec::Dema { field, period } => dema::build(graph, name, field, period),
+            IndicatorSpec::Tema { field, period } => tema::build(graph, name, field, period),
+            IndicatorSpec::Boll { field, period, num_std } => Ok(boll::build(graph, name, field, period, num_std)?.mid),
+            IndicatorSpec::BollPercentB { field, period, num_std } => boll::build_percent_b(graph, name, field, period, num_std),
+            IndicatorSpec::BollBandwidth { field, period, num_std } => boll::build_bandwidth(graph, name, field, period, num_std),
+            IndicatorSpec::Donchian { period } => Ok(donchian::build(graph, name, period)?.mid),
+            IndicatorSpec::Macd { fast_period, slow_period, signal_period } => {
+                Ok(macd::build(graph, name, fast_period, slow_period, signal_period)?.macd)
+            }
+            IndicatorSpec::Obv => obv::build(graph, name),
+            IndicatorSpec::Adl => adl::build(graph, name),
+            IndicatorSpec::Cmf { period } => cmf::build(graph, name, period),
+            IndicatorSpec::Mfi { period } => mfi::build(graph, name, period),
+            IndicatorSpec::Rsi { period, smoothing } => rsi::build(graph, name, period, smoothing),
+            IndicatorSpec::Kama { period, fast_period, slow_period } => {
+                if period == 0 || fast_period == 0 || slow_period == 0 {
+                    return Err(HQuantError::InvalidSpec(name.to_string()));
+                }
+                graph.add_field_indicator(name, Field::Close, Box::new(Kama::new(period, fast_period, slow_period)))
+            }
+            IndicatorSpec::Cmo { period } => {
+                if period == 0 {
+                    return Err(HQuantError::InvalidSpec(name.to_string()));
+                }
+                cmo::build(graph, name, period)
+            }
+            IndicatorSpec::Trix { field, period } => trix::build(graph, name, field, period),
+            IndicatorSpec::Zscore { field, period } => graph.add_field_indicator(name, field, Box::new(Zscore::new(period))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_kinds() {
+        assert_eq!(IndicatorSpec::parse("EMA_12"), Some(IndicatorSpec::Ema { field: Field::Close, period: 12 }));
+        assert_eq!(IndicatorSpec::parse("SMA_20"), Some(IndicatorSpec::Sma { field: Field::Close, period: 20 }));
+        assert_eq!(IndicatorSpec::parse("TSI(25,13)"), Some(IndicatorSpec::Tsi { r: 25, s: 13 }));
+        assert_eq!(
+            IndicatorSpec::parse("RSI_14"),
+            Some(IndicatorSpec::Rsi { period: 14, smoothing: RsiSmoothing::Wilder })
+        );
+        assert_eq!(
+            IndicatorSpec::parse("RSI(14,sma)"),
+            Some(IndicatorSpec::Rsi { period: 14, smoothing: RsiSmoothing::Sma })
+        );
+        assert_eq!(
+            IndicatorSpec::parse("RSI(14,ema)"),
+            Some(IndicatorSpec::Rsi { period: 14, smoothing: RsiSmoothing::Ema })
+        );
+        assert_eq!(IndicatorSpec::parse("garbage"), None);
+        assert_eq!(IndicatorSpec::parse("WMA_10"), Some(IndicatorSpec::Wma { field: Field::Close, period: 10 }));
+        assert_eq!(
+            IndicatorSpec::parse("WMA(close, 10)"),
+            Some(IndicatorSpec::Wma { field: Field::Close, period: 10 })
+        );
+        assert_eq!(IndicatorSpec::parse("HMA_16"), Some(IndicatorSpec::Hma { field: Field::Close, period: 16 }));
+        assert_eq!(IndicatorSpec::parse("DEMA_10"), Some(IndicatorSpec::Dema { field: Field::Close, period: 10 }));
+        assert_eq!(
+            IndicatorSpec::parse("DEMA(close, 10)"),
+            Some(IndicatorSpec::Dema { field: Field::Close, period: 10 })
+        );
+        assert_eq!(IndicatorSpec::parse("TEMA_10"), Some(IndicatorSpec::Tema { field: Field::Close, period: 10 }));
+        assert_eq!(
+            IndicatorSpec::parse("TEMA(close, 10)"),
+            Some(IndicatorSpec::Tema { field: Field::Close, period: 10 })
+        );
+        assert_eq!(
+            IndicatorSpec::parse("BOLL_20"),
+            Some(IndicatorSpec::Boll { field: Field::Close, period: 20, num_std: DEFAULT_BOLL_NUM_STD })
+        );
+        assert_eq!(
+            IndicatorSpec::parse("BOLL(close, 20, 2.5)"),
+            Some(IndicatorSpec::Boll { field: Field::Close, period: 20, num_std: 2.5 })
+        );
+        assert_eq!(
+            IndicatorSpec::parse("BOLLPB_20"),
+            Some(IndicatorSpec::BollPercentB { field: Field::Close, period: 20, num_std: DEFAULT_BOLL_NUM_STD })
+        );
+        assert_eq!(
+            IndicatorSpec::parse("BOLLPB(close, 20, 2.5)"),
+            Some(IndicatorSpec::BollPercentB { field: Field::Close, period: 20, num_std: 2.5 })
+        );
+        assert_eq!(
+            IndicatorSpec::parse("BOLLBW_20"),
+            Some(IndicatorSpec::BollBandwidth { field: Field::Close, period: 20, num_std: DEFAULT_BOLL_NUM_STD })
+        );
+        assert_eq!(IndicatorSpec::parse("DONCHIAN_20"), Some(IndicatorSpec::Donchian { period: 20 }));
+        assert_eq!(
+            IndicatorSpec::parse("MACD(12,26,9)"),
+            Some(IndicatorSpec::Macd { fast_period: 12, slow_period: 26, signal_period: 9 })
+        );
+        assert_eq!(IndicatorSpec::parse("OBV"), Some(IndicatorSpec::Obv));
+        assert_eq!(IndicatorSpec::parse("obv"), Some(IndicatorSpec::Obv));
+        assert_eq!(IndicatorSpec::parse("ADL"), Some(IndicatorSpec::Adl));
+        assert_eq!(IndicatorSpec::parse("CMF_20"), Some(IndicatorSpec::Cmf { period: 20 }));
+        assert_eq!(IndicatorSpec::parse("CMF(20)"), Some(IndicatorSpec::Cmf { period: 20 }));
+        assert_eq!(IndicatorSpec::parse("MFI_14"), Some(IndicatorSpec::Mfi { period: 14 }));
+        assert_eq!(IndicatorSpec::parse("MFI(14)"), Some(IndicatorSpec::Mfi { period: 14 }));
+        assert_eq!(
+            IndicatorSpec::parse("KAMA_10"),
+            Some(IndicatorSpec::Kama { period: 10, fast_period: DEFAULT_KAMA_FAST_PERIOD, slow_period: DEFAULT_KAMA_SLOW_PERIOD })
+        );
+        assert_eq!(
+            IndicatorSpec::parse("KAMA(10,2,30)"),
+            Some(IndicatorSpec::Kama { period: 10, fast_period: 2, slow_period: 30 })
+        );
+        assert_eq!(IndicatorSpec::parse("CMO_14"), Some(IndicatorSpec::Cmo { period: 14 }));
+        assert_eq!(IndicatorSpec::parse("CMO(14)"), Some(IndicatorSpec::Cmo { period: 14 }));
+        assert_eq!(IndicatorSpec::parse("TRIX_10"), Some(IndicatorSpec::Trix { field: Field::Close, period: 10 }));
+        assert_eq!(
+            IndicatorSpec::parse("TRIX(close, 10)"),
+            Some(IndicatorSpec::Trix { field: Field::Close, period: 10 })
+        );
+        assert_eq!(IndicatorSpec::parse("ZSCORE_20"), Some(IndicatorSpec::Zscore { field: Field::Close, period: 20 }));
+        assert_eq!(
+            IndicatorSpec::parse("ZSCORE(close, 20)"),
+            Some(IndicatorSpec::Zscore { field: Field::Close, period: 20 })
+        );
+        assert_eq!(
+            IndicatorSpec::parse("SMA(typical, 20)"),
+            Some(IndicatorSpec::Sma { field: Field::Typical, period: 20 })
+        );
+        assert_eq!(
+            IndicatorSpec::parse("EMA(median, 10)"),
+            Some(IndicatorSpec::Ema { field: Field::Median, period: 10 })
+        );
+        assert_eq!(
+            IndicatorSpec::parse("SMA(hlc3, 20)"),
+            Some(IndicatorSpec::Sma { field: Field::Typical, period: 20 })
+        );
+    }
+
+    #[test]
+    fn sma_over_typical_price_matches_the_hand_computed_average() {
+        use crate::kline::Bar;
+
+        let mut graph = IndicatorGraph::new();
+        let id = graph.add_from_spec("SMA(typical, 2)").unwrap();
+
+        graph.push_bar(&Bar { ts: 0, open: 0.0, high: 12.0, low: 8.0, close: 10.0, volume: 1.0 }); // typical 10
+        graph.push_bar(&Bar { ts: 1, open: 0.0, high: 22.0, low: 18.0, close: 20.0, volume: 1.0 }); // typical 20
+
+        // SMA_2 over (10, 20) is 15.
+        assert!((graph.get_from_end(id, 0).unwrap() - 15.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn builds_kama_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let id = graph.add_from_spec("KAMA_10").unwrap();
+        assert_eq!(graph.node_id("KAMA_10"), Some(id));
+    }
+
+    #[test]
+    fn builds_hma_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let id = graph.add_from_spec("HMA_16").unwrap();
+        assert_eq!(graph.node_id("HMA_16"), Some(id));
+    }
+
+    #[test]
+    fn builds_dema_and_tema_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let dema = graph.add_from_spec("DEMA_10").unwrap();
+        assert_eq!(graph.node_id("DEMA_10"), Some(dema));
+        let tema = graph.add_from_spec("TEMA_10").unwrap();
+        assert_eq!(graph.node_id("TEMA_10"), Some(tema));
+    }
+
+    #[test]
+    fn builds_boll_with_upper_and_lower_bands_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let mid = graph.add_from_spec("BOLL_20").unwrap();
+        assert_eq!(graph.node_id("BOLL_20"), Some(mid));
+        assert!(graph.node_id("BOLL_20_upper").is_some());
+        assert!(graph.node_id("BOLL_20_lower").is_some());
+    }
+
+    #[test]
+    fn builds_boll_percent_b_and_bandwidth_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let percent_b = graph.add_from_spec("BOLLPB_20").unwrap();
+        assert_eq!(graph.node_id("BOLLPB_20"), Some(percent_b));
+        let bandwidth = graph.add_from_spec("BOLLBW_20").unwrap();
+        assert_eq!(graph.node_id("BOLLBW_20"), Some(bandwidth));
+    }
+
+    #[test]
+    fn builds_donchian_with_upper_and_lower_bands_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let mid = graph.add_from_spec("DONCHIAN_20").unwrap();
+        assert_eq!(graph.node_id("DONCHIAN_20"), Some(mid));
+        assert!(graph.node_id("DONCHIAN_20_upper").is_some());
+        assert!(graph.node_id("DONCHIAN_20_lower").is_some());
+    }
+
+    #[test]
+    fn builds_macd_with_signal_and_histogram_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let macd = graph.add_from_spec("MACD(12,26,9)").unwrap();
+        assert_eq!(graph.node_id("MACD(12,26,9)"), Some(macd));
+        assert!(graph.node_id("MACD(12,26,9)_signal").is_some());
+        assert!(graph.node_id("MACD(12,26,9)_histogram").is_some());
+    }
+
+    #[test]
+    fn builds_tsi_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let id = graph.add_from_spec("TSI(25,13)").unwrap();
+        assert_eq!(graph.node_id("TSI(25,13)"), Some(id));
+        assert_eq!(graph.node_id("TSI(25,13)_signal"), Some(id + 1));
+    }
+
+    #[test]
+    fn builds_obv_and_adl_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let obv = graph.add_from_spec("OBV").unwrap();
+        assert_eq!(graph.node_id("OBV"), Some(obv));
+        let adl = graph.add_from_spec("ADL").unwrap();
+        assert_eq!(graph.node_id("ADL"), Some(adl));
+    }
+
+    #[test]
+    fn builds_cmf_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let id = graph.add_from_spec("CMF_20").unwrap();
+        assert_eq!(graph.node_id("CMF_20"), Some(id));
+    }
+
+    #[test]
+    fn builds_rsi_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let id = graph.add_from_spec("RSI_14").unwrap();
+        assert_eq!(graph.node_id("RSI_14"), Some(id));
+        let id = graph.add_from_spec("RSI(14,sma)").unwrap();
+        assert_eq!(graph.node_id("RSI(14,sma)"), Some(id));
+    }
+
+    #[test]
+    fn builds_cmo_and_trix_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let cmo = graph.add_from_spec("CMO_14").unwrap();
+        assert_eq!(graph.node_id("CMO_14"), Some(cmo));
+        let trix = graph.add_from_spec("TRIX_10").unwrap();
+        assert_eq!(graph.node_id("TRIX_10"), Some(trix));
+    }
+
+    #[test]
+    fn builds_zscore_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let id = graph.add_from_spec("ZSCORE_20").unwrap();
+        assert_eq!(graph.node_id("ZSCORE_20"), Some(id));
+    }
+
+    #[test]
+    fn builds_mfi_reachable_by_name() {
+        let mut graph = IndicatorGraph::new();
+        let id = graph.add_from_spec("MFI_14").unwrap();
+        assert_eq!(graph.node_id("MFI_14"), Some(id));
+    }
+}
@@ -0,0 +1,67 @@
+//! Triple Exponential Moving Average: `3*EMA - 3*EMA(EMA) + EMA(EMA(EMA))`,
+//! one nested `Ema` deeper than [`super::dema`] for even less lag. Built as
+//! `3*(ema1 - ema2)` via [`CombineOp::TripleMinus`], then `+ ema3` via a
+//! plain `Add`, rather than a single three-input combine node -- this
+//! graph only ever folds two upstream outputs into one, so a three-term sum
+//! chains two binary combines instead.
+
+use super::exec::{Ema, Identity};
+use super::{CombineOp, IndicatorGraph, NodeId};
+use crate::error::{HQuantError, Result};
+use crate::kline::Field;
+
+/// Build the TEMA DAG chain under `base_name`, reading `field` from the bar.
+/// Sub-indicators are registered under `base_name__`-prefixed internal
+/// names, the same convention [`super::hma::build`] uses. Returns the TEMA
+/// node id.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str, field: Field, period: usize) -> Result<NodeId> {
+    if period == 0 {
+        return Err(HQuantError::InvalidSpec(base_name.to_string()));
+    }
+    let ema1 = graph.add_field_indicator(&format!("{base_name}__ema1"), field, Box::new(Ema::new(period)))?;
+    let ema2 = graph.add_chained_indicator(&format!("{base_name}__ema2"), ema1, Box::new(Ema::new(period)))?;
+    let ema3 = graph.add_chained_indicator(&format!("{base_name}__ema3"), ema2, Box::new(Ema::new(period)))?;
+    let triple = graph.add_combined_indicator(&format!("{base_name}__triple"), ema1, ema2, CombineOp::TripleMinus, Box::new(Identity))?;
+    graph.add_combined_indicator(base_name, triple, ema3, CombineOp::Add, Box::new(Identity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicator::exec::Ema as EmaExec;
+    use crate::kline::Bar;
+
+    fn bar(close: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn tema_reacts_faster_than_dema_and_a_plain_ema_to_a_step_input() {
+        let mut graph = IndicatorGraph::new();
+        let tema = build(&mut graph, "TEMA_10", Field::Close, 10).unwrap();
+        let dema = super::super::dema::build(&mut graph, "DEMA_10", Field::Close, 10).unwrap();
+        let plain_ema = graph.add_field_indicator("EMA_10", Field::Close, Box::new(EmaExec::new(10))).unwrap();
+
+        for _ in 0..20 {
+            graph.push_bar(&bar(100.0));
+        }
+        for _ in 0..3 {
+            graph.push_bar(&bar(120.0));
+        }
+
+        let tema_value = graph.get_from_end(tema, 0).unwrap();
+        let dema_value = graph.get_from_end(dema, 0).unwrap();
+        let ema_value = graph.get_from_end(plain_ema, 0).unwrap();
+        assert!(
+            (tema_value - 120.0).abs() < (dema_value - 120.0).abs(),
+            "TEMA ({tema_value}) should sit closer to the new level than DEMA ({dema_value}) after a step"
+        );
+        assert!((dema_value - 120.0).abs() < (ema_value - 120.0).abs());
+    }
+
+    #[test]
+    fn rejects_a_zero_period() {
+        let mut graph = IndicatorGraph::new();
+        assert!(build(&mut graph, "TEMA_0", Field::Close, 0).is_err());
+    }
+}
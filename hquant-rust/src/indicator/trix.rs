@@ -0,0 +1,88 @@
+//! TRIX: the percent rate of change of a triple-smoothed EMA, one bar to
+//! the next.
+//!
+//! The triple smoothing itself is [`super::tema`]'s own `ema1`/`ema2`/`ema3`
+//! chain -- TEMA folds those three back together with [`CombineOp`]s to
+//! cancel lag, TRIX instead takes [`super::exec::PercentChange`] of just the
+//! innermost one, `ema3`.
+
+use super::exec::{Ema, PercentChange};
+use super::{IndicatorGraph, NodeId};
+use crate::error::{HQuantError, Result};
+use crate::kline::Field;
+
+/// Build the TRIX DAG chain under `base_name`, reading `field` from the bar.
+/// Sub-indicators are registered under `base_name__`-prefixed internal
+/// names, the same convention [`super::tema::build`] uses. Returns the TRIX
+/// node id.
+pub fn build(graph: &mut IndicatorGraph, base_name: &str, field: Field, period: usize) -> Result<NodeId> {
+    if period == 0 {
+        return Err(HQuantError::InvalidSpec(base_name.to_string()));
+    }
+    let ema1 = graph.add_field_indicator(&format!("{base_name}__ema1"), field, Box::new(Ema::new(period)))?;
+    let ema2 = graph.add_chained_indicator(&format!("{base_name}__ema2"), ema1, Box::new(Ema::new(period)))?;
+    let ema3 = graph.add_chained_indicator(&format!("{base_name}__ema3"), ema2, Box::new(Ema::new(period)))?;
+    graph.add_chained_indicator(base_name, ema3, Box::new(PercentChange::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(close: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn trix_crosses_zero_after_a_trend_reversal() {
+        let mut graph = IndicatorGraph::new();
+        let trix = build(&mut graph, "TRIX_5", Field::Close, 5).unwrap();
+
+        // A steady rise gives every EMA in the chain a positive slope, so
+        // TRIX (their rate of change) is positive.
+        let mut price = 100.0;
+        for _ in 0..20 {
+            graph.push_bar(&bar(price));
+            price += 2.0;
+        }
+        assert!(graph.get_from_end(trix, 0).unwrap() > 0.0, "expected positive TRIX during the run-up");
+
+        // A long, sharp fall eventually drags the triple-smoothed EMA's own
+        // slope negative too.
+        for _ in 0..20 {
+            graph.push_bar(&bar(price));
+            price -= 4.0;
+        }
+        assert!(graph.get_from_end(trix, 0).unwrap() < 0.0, "expected negative TRIX after the reversal");
+    }
+
+    #[test]
+    fn rejects_a_zero_period() {
+        let mut graph = IndicatorGraph::new();
+        assert!(build(&mut graph, "TRIX_0", Field::Close, 0).is_err());
+    }
+
+    #[test]
+    fn update_last_reproduces_a_fresh_push() {
+        let mut a = IndicatorGraph::new();
+        let trix_a = build(&mut a, "TRIX_5", Field::Close, 5).unwrap();
+        let mut b = IndicatorGraph::new();
+        let trix_b = build(&mut b, "TRIX_5", Field::Close, 5).unwrap();
+
+        let prices = [100.0, 101.0, 99.0, 105.0, 110.0, 108.0];
+        for &p in &prices[..prices.len() - 1] {
+            a.push_bar(&bar(p));
+            b.push_bar(&bar(p));
+        }
+        let last = *prices.last().unwrap();
+
+        a.push_bar(&bar(last - 1.0));
+        a.update_last(&bar(last));
+        b.push_bar(&bar(last));
+
+        let va = a.get_from_end(trix_a, 0).unwrap();
+        let vb = b.get_from_end(trix_b, 0).unwrap();
+        assert!((va - vb).abs() < 1e-9, "expected {va} to match fresh push {vb}");
+    }
+}
@@ -0,0 +1,99 @@
+//! True Strength Index: a double-smoothed momentum oscillator,
+//! `100 * EMA(EMA(mom, r), s) / EMA(EMA(|mom|, r), s)`.
+
+use super::exec::{AbsValue, Ema, Identity, Momentum};
+use super::{CombineOp, IndicatorGraph, NodeId};
+use crate::error::Result;
+use crate::kline::Field;
+
+/// EMA period used for the signal line plotted alongside TSI. Not exposed
+/// as a DSL parameter since `TSI(r, s)` only carries the two smoothing
+/// periods; matches the common default used by most charting packages.
+const SIGNAL_PERIOD: usize = 7;
+
+/// Build the TSI DAG chain under `base_name`, registering every
+/// intermediate node with a `base_name__`-prefixed internal name so it
+/// doesn't collide with user-visible indicators. Returns the TSI node id;
+/// the signal line is registered as `"{base_name}_signal"` and can be
+/// looked up with [`IndicatorGraph::node_id`].
+pub fn build(graph: &mut IndicatorGraph, base_name: &str, r: usize, s: usize) -> Result<NodeId> {
+    let mom = graph.add_field_indicator(&format!("{base_name}__mom"), Field::Close, Box::new(Momentum::new()))?;
+    let abs_mom = graph.add_chained_indicator(&format!("{base_name}__abs_mom"), mom, Box::new(AbsValue))?;
+
+    let mom_r = graph.add_chained_indicator(&format!("{base_name}__mom_ema_r"), mom, Box::new(Ema::new(r)))?;
+    let mom_rs = graph.add_chained_indicator(&format!("{base_name}__mom_ema_rs"), mom_r, Box::new(Ema::new(s)))?;
+
+    let abs_r = graph.add_chained_indicator(&format!("{base_name}__abs_ema_r"), abs_mom, Box::new(Ema::new(r)))?;
+    let abs_rs = graph.add_chained_indicator(&format!("{base_name}__abs_ema_rs"), abs_r, Box::new(Ema::new(s)))?;
+
+    let tsi = graph.add_combined_indicator(base_name, mom_rs, abs_rs, CombineOp::RatioPercent, Box::new(Identity))?;
+    graph.add_chained_indicator(&format!("{base_name}_signal"), tsi, Box::new(Ema::new(SIGNAL_PERIOD)))?;
+
+    Ok(tsi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kline::Bar;
+
+    fn bar(close: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn tsi_is_positive_and_bounded_in_an_uptrend() {
+        let mut graph = IndicatorGraph::new();
+        let tsi = build(&mut graph, "TSI_25_13", 25, 13).unwrap();
+        let signal = graph.node_id("TSI_25_13_signal").unwrap();
+
+        let mut price = 100.0;
+        for _ in 0..200 {
+            graph.push_bar(&bar(price));
+            price += 1.0;
+        }
+
+        let value = graph.get_from_end(tsi, 0).unwrap();
+        assert!(value > 0.0, "TSI should be positive in a steady uptrend, got {value}");
+        assert!((-100.0..=100.0).contains(&value), "TSI should stay within +/-100, got {value}");
+        assert!(graph.get_from_end(signal, 0).unwrap().is_finite());
+    }
+
+    #[test]
+    fn update_last_reproduces_a_fresh_push() {
+        let mut a = IndicatorGraph::new();
+        let tsi_a = build(&mut a, "TSI_25_13", 25, 13).unwrap();
+        let mut b = IndicatorGraph::new();
+        let tsi_b = build(&mut b, "TSI_25_13", 25, 13).unwrap();
+
+        let prices = [100.0, 101.0, 99.0, 105.0, 110.0, 108.0];
+        for &p in &prices[..prices.len() - 1] {
+            a.push_bar(&bar(p));
+            b.push_bar(&bar(p));
+        }
+        let last = *prices.last().unwrap();
+
+        // `a` opens the new bar with a placeholder, then revises it in
+        // place as the candle ticks toward its real close.
+        a.push_bar(&bar(last - 1.0));
+        a.update_last(&bar(last));
+        // `b` sees the real close directly.
+        b.push_bar(&bar(last));
+
+        let va = a.get_from_end(tsi_a, 0).unwrap();
+        let vb = b.get_from_end(tsi_b, 0).unwrap();
+        assert!((va - vb).abs() < 1e-9, "expected {va} to match fresh push {vb}");
+    }
+
+    #[test]
+    fn a_flat_price_series_reports_zero_rather_than_nan() {
+        let mut graph = IndicatorGraph::new();
+        let tsi = build(&mut graph, "TSI_25_13", 25, 13).unwrap();
+
+        for _ in 0..60 {
+            graph.push_bar(&bar(100.0));
+        }
+
+        assert_eq!(graph.get_from_end(tsi, 0).unwrap(), 0.0);
+    }
+}
@@ -0,0 +1,153 @@
+//! Volume-Weighted Average Price, resetting its cumulative sums at each
+//! session boundary.
+//!
+//! Like [`super::Adx`], VWAP needs a bar's high, low, close *and* volume
+//! jointly -- more than [`super::IndicatorExec`]'s single-scalar-per-node
+//! contract can express -- plus the bar's timestamp, to know when a
+//! session has rolled over. It's implemented as a standalone transform in
+//! the style of [`crate::heikin_ashi::HeikinAshi`] (consuming `&Bar`
+//! directly) rather than as an `IndicatorGraph`-registered node, so it
+//! isn't reachable via `IndicatorGraph::add_from_spec`/[`super::IndicatorSpec`]
+//! or the DSL yet -- a caller drives it directly off the same bar stream,
+//! the way [`crate::aggregator::Aggregator`] is driven.
+//!
+//! Session boundaries are detected the same way [`crate::aggregator::Aggregator`]
+//! buckets bars: floor `bar.ts` (Unix milliseconds) to a `session_ms`-wide
+//! window, shifted by `session_offset_ms` for venues whose session doesn't
+//! reset on a UTC-epoch-aligned boundary.
+
+use crate::kline::Bar;
+
+#[derive(Debug, Clone)]
+pub struct SessionVwap {
+    session_ms: i64,
+    session_offset_ms: i64,
+    /// Bucket key of the session currently being accumulated. `None`
+    /// before the first bar.
+    session_key: Option<i64>,
+    cum_pv: f64,
+    cum_volume: f64,
+    /// This session's `(price*volume, volume)` contribution from the most
+    /// recently pushed bar, so `update_last` can subtract it back out
+    /// before re-adding the revised bar's contribution.
+    pending: Option<(f64, f64)>,
+}
+
+impl SessionVwap {
+    /// Resets once every 24h, on the UTC-epoch-aligned boundary (crypto's
+    /// usual UTC midnight daily session).
+    pub fn new() -> Self {
+        Self::with_session_offset(86_400_000, 0)
+    }
+
+    /// Like `new`, but resets `session_offset_ms` after each
+    /// epoch-aligned `session_ms` boundary rather than exactly on it --
+    /// e.g. a venue whose session opens at 08:00 rather than UTC
+    /// midnight.
+    pub fn with_session_offset(session_ms: i64, session_offset_ms: i64) -> Self {
+        assert!(session_ms > 0, "SessionVwap session_ms must be > 0");
+        Self { session_ms, session_offset_ms, session_key: None, cum_pv: 0.0, cum_volume: 0.0, pending: None }
+    }
+
+    fn session_key_for(&self, ts: i64) -> i64 {
+        let shifted = ts - self.session_offset_ms;
+        shifted.div_euclid(self.session_ms)
+    }
+
+    fn typical_price(bar: &Bar) -> f64 {
+        (bar.high + bar.low + bar.close) / 3.0
+    }
+
+    fn value(&self) -> f64 {
+        if self.cum_volume == 0.0 {
+            f64::NAN
+        } else {
+            self.cum_pv / self.cum_volume
+        }
+    }
+
+    /// Accumulate `bar` into the current session, resetting first if `bar`
+    /// belongs to a new one.
+    pub fn push(&mut self, bar: &Bar) -> f64 {
+        let key = self.session_key_for(bar.ts);
+        if self.session_key != Some(key) {
+            self.session_key = Some(key);
+            self.cum_pv = 0.0;
+            self.cum_volume = 0.0;
+        }
+        let pv = Self::typical_price(bar) * bar.volume;
+        self.cum_pv += pv;
+        self.cum_volume += bar.volume;
+        self.pending = Some((pv, bar.volume));
+        self.value()
+    }
+
+    /// Revise the most recently pushed bar's contribution in place,
+    /// without treating the revision itself as a new bar. A revision that
+    /// moves `bar.ts` across a session boundary isn't supported -- the
+    /// session is keyed by whichever timestamp `push` last saw, so this
+    /// still subtracts against that session.
+    pub fn update_last(&mut self, bar: &Bar) -> f64 {
+        if let Some((pv, volume)) = self.pending.take() {
+            self.cum_pv -= pv;
+            self.cum_volume -= volume;
+        }
+        let pv = Self::typical_price(bar) * bar.volume;
+        self.cum_pv += pv;
+        self.cum_volume += bar.volume;
+        self.pending = Some((pv, bar.volume));
+        self.value()
+    }
+}
+
+impl Default for SessionVwap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(ts: i64, price: f64, volume: f64) -> Bar {
+        Bar { ts, open: price, high: price, low: price, close: price, volume }
+    }
+
+    #[test]
+    fn accumulates_a_volume_weighted_average_within_one_session() {
+        let mut vwap = SessionVwap::new();
+        vwap.push(&bar(0, 100.0, 1.0));
+        let value = vwap.push(&bar(60_000, 200.0, 3.0));
+        // (100*1 + 200*3) / (1 + 3) = 175
+        assert_eq!(value, 175.0);
+    }
+
+    #[test]
+    fn crossing_a_day_boundary_resets_the_accumulated_sums() {
+        let mut vwap = SessionVwap::new();
+        vwap.push(&bar(0, 100.0, 1.0));
+        let still_day_one = vwap.push(&bar(86_399_000, 300.0, 1.0));
+        assert_eq!(still_day_one, 200.0);
+
+        let day_two = vwap.push(&bar(86_400_000, 50.0, 1.0));
+        assert_eq!(day_two, 50.0, "a new day should reset cum_pv/cum_volume rather than keep averaging in day one");
+    }
+
+    #[test]
+    fn update_last_revises_the_current_bar_without_double_counting() {
+        let mut vwap = SessionVwap::new();
+        vwap.push(&bar(0, 100.0, 1.0));
+        vwap.push(&bar(60_000, 200.0, 1.0));
+        let revised = vwap.update_last(&bar(60_000, 400.0, 1.0));
+        // (100*1 + 400*1) / (1 + 1) = 250, not the 300 double-counting the
+        // stale 200 contribution would give.
+        assert_eq!(revised, 250.0);
+    }
+
+    #[test]
+    fn value_is_nan_before_any_volume_has_accumulated() {
+        let vwap = SessionVwap::new();
+        assert!(vwap.value().is_nan());
+    }
+}
@@ -0,0 +1,143 @@
+//! Volume-Weighted Moving Average over a fixed rolling window:
+//! `sum(close*volume) / sum(volume)`.
+//!
+//! Like [`super::SessionVwap`], this needs a bar's close *and* volume
+//! jointly -- more than [`super::IndicatorExec`]'s single-scalar-per-node
+//! contract can express -- so it's implemented the same way: a standalone
+//! transform consuming `&Bar` directly rather than an `IndicatorGraph`
+//! node, not reachable via [`super::IndicatorSpec`]/the DSL yet.
+
+use crate::kline::Bar;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub struct Vwma {
+    period: usize,
+    /// `(close*volume, volume)` per bar in the window, oldest first.
+    window: VecDeque<(f64, f64)>,
+    cum_pv: f64,
+    cum_volume: f64,
+    /// The `(pv, volume)` pair added or overwritten by the most recent
+    /// `update_last`, so a further revision undoes it before applying the
+    /// next one -- same pattern as [`super::exec::Sma`]'s `pending`.
+    pending: Option<(f64, f64)>,
+}
+
+impl Vwma {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "VWMA period must be > 0");
+        Self {
+            period,
+            window: VecDeque::with_capacity(period),
+            cum_pv: 0.0,
+            cum_volume: 0.0,
+            pending: None,
+        }
+    }
+
+    /// `NaN` once the window's total volume is zero (e.g. only zero-volume
+    /// bars seen so far) rather than dividing by zero, the same convention
+    /// [`super::SessionVwap::value`] uses.
+    fn value(&self) -> f64 {
+        if self.cum_volume == 0.0 {
+            f64::NAN
+        } else {
+            self.cum_pv / self.cum_volume
+        }
+    }
+
+    pub fn push(&mut self, bar: &Bar) -> f64 {
+        self.pending = None;
+        let pv = bar.close * bar.volume;
+        self.window.push_back((pv, bar.volume));
+        self.cum_pv += pv;
+        self.cum_volume += bar.volume;
+        if self.window.len() > self.period {
+            let (old_pv, old_volume) = self.window.pop_front().unwrap();
+            self.cum_pv -= old_pv;
+            self.cum_volume -= old_volume;
+        }
+        self.value()
+    }
+
+    /// Revise the most recently pushed bar's contribution in place, without
+    /// shifting the window.
+    pub fn update_last(&mut self, bar: &Bar) -> f64 {
+        let pv = bar.close * bar.volume;
+        if let Some((old_pv, old_volume)) = self.pending.take() {
+            self.cum_pv -= old_pv;
+            self.cum_volume -= old_volume;
+            *self.window.back_mut().unwrap() = (pv, bar.volume);
+        } else if let Some(last) = self.window.back_mut() {
+            self.cum_pv -= last.0;
+            self.cum_volume -= last.1;
+            *last = (pv, bar.volume);
+        } else {
+            self.window.push_back((pv, bar.volume));
+        }
+        self.cum_pv += pv;
+        self.cum_volume += bar.volume;
+        self.pending = Some((pv, bar.volume));
+        self.value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(price: f64, volume: f64) -> Bar {
+        Bar { ts: 0, open: price, high: price, low: price, close: price, volume }
+    }
+
+    #[test]
+    fn weights_the_window_by_volume_not_a_plain_average() {
+        let mut vwma = Vwma::new(3);
+        vwma.push(&bar(100.0, 1.0));
+        vwma.push(&bar(200.0, 1.0));
+        let value = vwma.push(&bar(300.0, 1.0));
+        // Equal volumes: matches a plain SMA.
+        assert_eq!(value, 200.0);
+    }
+
+    #[test]
+    fn a_volume_spike_on_one_bar_pulls_vwma_toward_that_bars_price() {
+        let mut vwma = Vwma::new(3);
+        vwma.push(&bar(100.0, 1.0));
+        vwma.push(&bar(300.0, 100.0));
+        let value = vwma.push(&bar(100.0, 1.0));
+        // (100*1 + 300*100 + 100*1) / (1 + 100 + 1) is pulled hard toward
+        // 300 by the volume spike, far above the plain average of 166.7.
+        let expected = (100.0 + 300.0 * 100.0 + 100.0) / 102.0;
+        assert!((value - expected).abs() < 1e-9);
+        assert!(value > 250.0, "a 100x volume spike at 300 should pull VWMA well above the unweighted average, got {value}");
+    }
+
+    #[test]
+    fn window_evicts_the_oldest_bars_contribution() {
+        let mut vwma = Vwma::new(2);
+        vwma.push(&bar(100.0, 1.0));
+        vwma.push(&bar(200.0, 1.0));
+        let value = vwma.push(&bar(300.0, 1.0));
+        // The 100.0 bar has fallen out of the 2-wide window.
+        assert_eq!(value, 250.0);
+    }
+
+    #[test]
+    fn update_last_revises_the_current_bar_without_shifting_the_window() {
+        let mut vwma = Vwma::new(3);
+        vwma.push(&bar(100.0, 1.0));
+        vwma.push(&bar(200.0, 1.0));
+        let revised = vwma.update_last(&bar(400.0, 1.0));
+        // (100*1 + 400*1) / (1 + 1) = 250, not double-counting the stale
+        // 200.0 contribution.
+        assert_eq!(revised, 250.0);
+    }
+
+    #[test]
+    fn value_is_nan_when_total_volume_is_zero() {
+        let mut vwma = Vwma::new(3);
+        let value = vwma.push(&bar(100.0, 0.0));
+        assert!(value.is_nan());
+    }
+}
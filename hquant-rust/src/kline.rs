@@ -0,0 +1,620 @@
+//! Bar (kline/candle) representation shared by the aggregator, indicator
+//! graph and strategy evaluator.
+
+/// A single OHLCV bar. `ts` is a millisecond unix timestamp marking the
+/// bar's open time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bar {
+    pub ts: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+/// Price fields a strategy or indicator can reference directly, without
+/// going through a computed indicator node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Open,
+    High,
+    Low,
+    Close,
+    Volume,
+    /// `(high + low + close) / 3`, a.k.a. `HLC3` -- the average price a
+    /// typical trade during the bar cleared at, smoother than `Close`
+    /// alone. Not a stored column of its own; [`Field::value`] derives it
+    /// from the same bar's `high`/`low`/`close` on every read.
+    Typical,
+    /// `(high + low) / 2` -- the midpoint of the bar's range, ignoring
+    /// where it opened or closed. Derived the same way as `Typical`.
+    Median,
+}
+
+impl Field {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "OPEN" => Some(Field::Open),
+            "HIGH" => Some(Field::High),
+            "LOW" => Some(Field::Low),
+            "CLOSE" => Some(Field::Close),
+            "VOLUME" => Some(Field::Volume),
+            "TYPICAL" | "HLC3" => Some(Field::Typical),
+            "MEDIAN" => Some(Field::Median),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self, bar: &Bar) -> f64 {
+        match self {
+            Field::Open => bar.open,
+            Field::High => bar.high,
+            Field::Low => bar.low,
+            Field::Close => bar.close,
+            Field::Volume => bar.volume,
+            Field::Typical => (bar.high + bar.low + bar.close) / 3.0,
+            Field::Median => (bar.high + bar.low) / 2.0,
+        }
+    }
+}
+
+/// Rolling per-field bar history, one [`CircularColumn`] per OHLCV field.
+/// This is the backing store [`crate::engine::HQuant`] hands out zero-copy
+/// views over (`close_column` and friends).
+#[derive(Debug)]
+pub struct KlineBuffer {
+    ts: crate::common::CircularColumn<i64>,
+    open: crate::common::CircularColumn<f64>,
+    high: crate::common::CircularColumn<f64>,
+    low: crate::common::CircularColumn<f64>,
+    close: crate::common::CircularColumn<f64>,
+    volume: crate::common::CircularColumn<f64>,
+}
+
+impl KlineBuffer {
+    pub fn new(capacity: usize) -> Self {
+        use crate::common::CircularColumn;
+        Self {
+            ts: CircularColumn::new(capacity),
+            open: CircularColumn::new(capacity),
+            high: CircularColumn::new(capacity),
+            low: CircularColumn::new(capacity),
+            close: CircularColumn::new(capacity),
+            volume: CircularColumn::new(capacity),
+        }
+    }
+
+    pub fn push(&mut self, bar: &Bar) {
+        self.ts.push(bar.ts);
+        self.open.push(bar.open);
+        self.high.push(bar.high);
+        self.low.push(bar.low);
+        self.close.push(bar.close);
+        self.volume.push(bar.volume);
+    }
+
+    pub fn update_last(&mut self, bar: &Bar) {
+        self.ts.update_last(bar.ts);
+        self.open.update_last(bar.open);
+        self.high.update_last(bar.high);
+        self.low.update_last(bar.low);
+        self.close.update_last(bar.close);
+        self.volume.update_last(bar.volume);
+    }
+
+    pub fn close_column(&self) -> &crate::common::CircularColumn<f64> {
+        &self.close
+    }
+
+    /// A stored raw OHLCV column. `field` must be one of
+    /// [`Field::Open`]/[`Field::High`]/[`Field::Low`]/[`Field::Close`]/
+    /// [`Field::Volume`] -- a derived field like [`Field::Typical`]/
+    /// [`Field::Median`] has no column of its own (see [`Field::value`]),
+    /// so there's nothing this method could return for one.
+    pub fn field_column(&self, field: Field) -> &crate::common::CircularColumn<f64> {
+        match field {
+            Field::Open => &self.open,
+            Field::High => &self.high,
+            Field::Low => &self.low,
+            Field::Close => &self.close,
+            Field::Volume => &self.volume,
+            Field::Typical | Field::Median => {
+                unreachable!("field_column only supports raw OHLCV fields, not a derived one")
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.close.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.close.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.close.capacity()
+    }
+
+    /// Drop every stored bar, keeping capacity unchanged. See
+    /// [`crate::engine::HQuant::reset`].
+    pub fn clear(&mut self) {
+        self.ts.clear();
+        self.open.clear();
+        self.high.clear();
+        self.low.clear();
+        self.close.clear();
+        self.volume.clear();
+    }
+
+    /// The bar at chronological `index` (`0` = oldest currently retained
+    /// bar, matching [`KlineBuffer::to_bars`]'s ordering), or `None` if out
+    /// of range.
+    pub fn get(&self, index: usize) -> Option<Bar> {
+        Some(Bar {
+            ts: self.ts.get(index)?,
+            open: self.open.get(index)?,
+            high: self.high.get(index)?,
+            low: self.low.get(index)?,
+            close: self.close.get(index)?,
+            volume: self.volume.get(index)?,
+        })
+    }
+
+    /// Binary search the timestamp column for the bar at exactly `ts`,
+    /// assuming timestamps are monotonically increasing (as pushed bars
+    /// always are). `None` if no currently retained bar has that exact
+    /// timestamp.
+    pub fn index_of_timestamp(&self, ts: i64) -> Option<usize> {
+        self.ts.to_vec().binary_search(&ts).ok()
+    }
+
+    /// The bar at exactly timestamp `ts`, via [`KlineBuffer::index_of_timestamp`].
+    pub fn get_by_timestamp(&self, ts: i64) -> Option<Bar> {
+        self.get(self.index_of_timestamp(ts)?)
+    }
+
+    /// Overwrite the bar at chronological `index` in place, for correcting
+    /// a historical bar (e.g. a late-arriving revision to an already
+    /// closed candle). This only updates the buffer itself -- indicators,
+    /// aggregators, or anything else already derived from bars at or after
+    /// `index` are NOT recomputed; a caller that needs those consistent
+    /// with the correction has to rebuild them itself (e.g. by replaying
+    /// [`KlineBuffer::to_bars`] through a fresh
+    /// [`crate::indicator::IndicatorGraph::replace`]). Returns `false`
+    /// without writing if `index` is out of range.
+    pub fn update_at(&mut self, index: usize, bar: &Bar) -> bool {
+        if index >= self.len() {
+            return false;
+        }
+        self.ts.set(index, bar.ts);
+        self.open.set(index, bar.open);
+        self.high.set(index, bar.high);
+        self.low.set(index, bar.low);
+        self.close.set(index, bar.close);
+        self.volume.set(index, bar.volume);
+        true
+    }
+
+    /// Materialize the full retained history as owned [`Bar`]s, oldest
+    /// first, for callers that need to replay it (e.g.
+    /// [`crate::indicator::IndicatorGraph::replace`]'s backfill).
+    pub fn to_bars(&self) -> Vec<Bar> {
+        let ts = self.ts.to_vec();
+        let open = self.open.to_vec();
+        let high = self.high.to_vec();
+        let low = self.low.to_vec();
+        let close = self.close.to_vec();
+        let volume = self.volume.to_vec();
+        (0..self.len())
+            .map(|i| Bar { ts: ts[i], open: open[i], high: high[i], low: low[i], close: close[i], volume: volume[i] })
+            .collect()
+    }
+}
+
+/// An owned, contiguous sequence of bars that can round-trip through a
+/// compact binary format, e.g. for caching a fetched kline series to disk.
+///
+/// # Binary format
+///
+/// ```text
+/// version 1: byte 0 version, bytes 1..5 count (u32 LE), bytes 5..13 ts_base
+///            (i64 LE), then per bar: delta (i32 LE) + 5x f64 LE (OHLCV)
+/// version 2: same layout, delta widened to i64 LE
+/// version 3: byte 0 version, byte 1 columns (u8), bytes 2..6 count (u32 LE),
+///            bytes 6..14 ts_base (i64 LE), then per bar: delta (i64 LE) +
+///            `columns` x f64 LE, of which the first 5 are OHLCV and any
+///            beyond that are skipped
+/// ```
+///
+/// Version 1 stored the per-bar timestamp delta as `i32`, which silently
+/// wraps once a series spans more than `i32::MAX` milliseconds (~24.8
+/// days) from `ts_base`. Version 2 widens deltas to `i64`.
+///
+/// Version 3 adds the `columns` header byte so a future writer can append
+/// extra per-bar columns (e.g. buy/sell volume split) without breaking
+/// older readers: `from_binary` uses `columns` to know how many `f64`s
+/// actually follow each delta, reads the first 5 into the `Bar` fields
+/// `columns` always covers, and skips the rest rather than
+/// misinterpreting them as the next bar's delta. `to_binary` writes
+/// `columns = 5` today, since `Bar` has nowhere to put anything past
+/// OHLCV yet. `from_binary` still reads version 1 and 2 files (neither of
+/// which has a `columns` byte at all) by branching on the header byte.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KlineFrame {
+    pub bars: Vec<Bar>,
+}
+
+const KLINE_FRAME_VERSION: u8 = 3;
+const KLINE_FRAME_HEADER_LEN: usize = 1 + 1 + 4 + 8;
+/// OHLCV: the columns every version's `Bar` fields are read from.
+const KLINE_FRAME_BASE_COLUMNS: u8 = 5;
+
+impl KlineFrame {
+    pub fn new(bars: Vec<Bar>) -> Self {
+        Self { bars }
+    }
+
+    /// Serialize using the current (version 3) format.
+    pub fn to_binary(&self) -> Vec<u8> {
+        let ts_base = self.bars.first().map(|b| b.ts).unwrap_or(0);
+        let mut buf = Vec::with_capacity(KLINE_FRAME_HEADER_LEN + self.bars.len() * 48);
+        buf.push(KLINE_FRAME_VERSION);
+        buf.push(KLINE_FRAME_BASE_COLUMNS);
+        buf.extend_from_slice(&(self.bars.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&ts_base.to_le_bytes());
+        for bar in &self.bars {
+            buf.extend_from_slice(&(bar.ts - ts_base).to_le_bytes());
+            buf.extend_from_slice(&bar.open.to_le_bytes());
+            buf.extend_from_slice(&bar.high.to_le_bytes());
+            buf.extend_from_slice(&bar.low.to_le_bytes());
+            buf.extend_from_slice(&bar.close.to_le_bytes());
+            buf.extend_from_slice(&bar.volume.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Deserialize a frame written by `to_binary`, from this version or an
+    /// older one.
+    pub fn from_binary(data: &[u8]) -> crate::error::Result<Self> {
+        let mut cursor = BinCursor { data, pos: 0 };
+        let version = cursor.read_u8()?;
+        let columns = if version >= 3 { cursor.read_u8()? } else { KLINE_FRAME_BASE_COLUMNS };
+        if columns < KLINE_FRAME_BASE_COLUMNS {
+            return Err(crate::error::HQuantError::InvalidSpec(format!(
+                "KlineFrame binary format declares {columns} columns, need at least {KLINE_FRAME_BASE_COLUMNS} for OHLCV"
+            )));
+        }
+        let extra_columns = (columns - KLINE_FRAME_BASE_COLUMNS) as usize;
+        let count = cursor.read_u32()? as usize;
+        let ts_base = cursor.read_i64()?;
+        let mut bars = Vec::with_capacity(count);
+        for _ in 0..count {
+            let delta: i64 = match version {
+                1 => cursor.read_i32()? as i64,
+                2 | 3 => cursor.read_i64()?,
+                other => {
+                    return Err(crate::error::HQuantError::InvalidSpec(format!(
+                        "unsupported KlineFrame binary format version {other}"
+                    )))
+                }
+            };
+            let bar = Bar {
+                ts: ts_base + delta,
+                open: cursor.read_f64()?,
+                high: cursor.read_f64()?,
+                low: cursor.read_f64()?,
+                close: cursor.read_f64()?,
+                volume: cursor.read_f64()?,
+            };
+            for _ in 0..extra_columns {
+                cursor.read_f64()?;
+            }
+            bars.push(bar);
+        }
+        Ok(Self { bars })
+    }
+}
+
+impl KlineFrame {
+    /// Parse a CSV export into a frame. The header row maps columns by
+    /// name (case-insensitive, any order); `open`/`high`/`low`/`close`/
+    /// `volume` are required, and a timestamp column named `timestamp`,
+    /// `ts` or `time` is required and may hold either epoch-millisecond
+    /// integers or ISO-8601 strings (`2021-01-01T00:00:00Z`, with or
+    /// without fractional seconds). Any other column (e.g. a `side` or
+    /// `buy_sell` marker some exchange exports include) is ignored, since
+    /// [`Bar`] has nowhere to put it.
+    ///
+    /// Like [`KlineFrame::from_binary`], this has no `capacity` argument:
+    /// a `KlineFrame` is just an owned `Vec<Bar>`, with no fixed-size
+    /// backing store the way [`crate::kline::KlineBuffer`] has.
+    pub fn from_csv(csv: &str) -> crate::error::Result<Self> {
+        let mut lines = csv.lines();
+        let header = lines
+            .next()
+            .ok_or_else(|| crate::error::HQuantError::InvalidSpec("empty CSV: missing header row".to_string()))?;
+        let columns: Vec<&str> = header.split(',').map(|c| c.trim()).collect();
+        let column = |name: &str| columns.iter().position(|c| c.eq_ignore_ascii_case(name));
+        let ts_idx = column("timestamp").or_else(|| column("ts")).or_else(|| column("time")).ok_or_else(|| {
+            crate::error::HQuantError::InvalidSpec("CSV header missing a timestamp/ts/time column".to_string())
+        })?;
+        let open_idx = column("open")
+            .ok_or_else(|| crate::error::HQuantError::InvalidSpec("CSV header missing an open column".to_string()))?;
+        let high_idx = column("high")
+            .ok_or_else(|| crate::error::HQuantError::InvalidSpec("CSV header missing a high column".to_string()))?;
+        let low_idx = column("low")
+            .ok_or_else(|| crate::error::HQuantError::InvalidSpec("CSV header missing a low column".to_string()))?;
+        let close_idx = column("close")
+            .ok_or_else(|| crate::error::HQuantError::InvalidSpec("CSV header missing a close column".to_string()))?;
+        let volume_idx = column("volume")
+            .ok_or_else(|| crate::error::HQuantError::InvalidSpec("CSV header missing a volume column".to_string()))?;
+
+        let mut bars = Vec::new();
+        for (offset, line) in lines.enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let row_number = offset + 2; // 1-indexed, plus the header row
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            let field = |idx: usize| {
+                fields.get(idx).copied().ok_or_else(|| {
+                    crate::error::HQuantError::InvalidSpec(format!("CSV row {row_number} is missing a column"))
+                })
+            };
+            let number = |idx: usize| -> crate::error::Result<f64> {
+                field(idx)?.parse::<f64>().map_err(|_| {
+                    crate::error::HQuantError::InvalidSpec(format!("CSV row {row_number} has a non-numeric value"))
+                })
+            };
+            bars.push(Bar {
+                ts: parse_timestamp(field(ts_idx)?, row_number)?,
+                open: number(open_idx)?,
+                high: number(high_idx)?,
+                low: number(low_idx)?,
+                close: number(close_idx)?,
+                volume: number(volume_idx)?,
+            });
+        }
+        Ok(Self { bars })
+    }
+
+    /// Render as CSV: an epoch-millisecond `timestamp` column followed by
+    /// `open,high,low,close,volume`. Always round-trips through
+    /// [`KlineFrame::from_csv`].
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("timestamp,open,high,low,close,volume\n");
+        for bar in &self.bars {
+            out.push_str(&format!("{},{},{},{},{},{}\n", bar.ts, bar.open, bar.high, bar.low, bar.close, bar.volume));
+        }
+        out
+    }
+}
+
+/// Parse a timestamp cell as either an epoch-millisecond integer or an
+/// ISO-8601 string (`YYYY-MM-DDTHH:MM:SS`, optional `.fff` fraction,
+/// optional trailing `Z`).
+fn parse_timestamp(raw: &str, row_number: usize) -> crate::error::Result<i64> {
+    if let Ok(ms) = raw.parse::<i64>() {
+        return Ok(ms);
+    }
+    parse_iso8601_to_epoch_ms(raw)
+        .ok_or_else(|| crate::error::HQuantError::InvalidSpec(format!("CSV row {row_number} has an unrecognized timestamp: {raw}")))
+}
+
+fn parse_iso8601_to_epoch_ms(raw: &str) -> Option<i64> {
+    let s = raw.trim().trim_end_matches('Z');
+    let (date_part, time_part) = s.split_once('T')?;
+    let mut date_fields = date_part.split('-');
+    let year: i64 = date_fields.next()?.parse().ok()?;
+    let month: i64 = date_fields.next()?.parse().ok()?;
+    let day: i64 = date_fields.next()?.parse().ok()?;
+
+    let (time_main, millis) = match time_part.split_once('.') {
+        Some((t, frac)) => {
+            let frac = format!("{frac:0<3}");
+            (t, frac[..3].parse::<i64>().ok()?)
+        }
+        None => (time_part, 0),
+    };
+    let mut time_fields = time_main.split(':');
+    let hour: i64 = time_fields.next()?.parse().ok()?;
+    let minute: i64 = time_fields.next()?.parse().ok()?;
+    let second: i64 = time_fields.next()?.parse().ok()?;
+
+    let days = days_from_civil(year, month, day);
+    Some(days * 86_400_000 + hour * 3_600_000 + minute * 60_000 + second * 1000 + millis)
+}
+
+/// Days since the Unix epoch (1970-01-01) for a Gregorian calendar date.
+/// Howard Hinnant's `days_from_civil` algorithm, valid for years before
+/// 1970 and before year 0 alike as long as the day count fits in an `i64`,
+/// without relying on a `chrono`-style calendar dependency.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Minimal little-endian byte reader with bounds checking, local to the
+/// kline binary format.
+struct BinCursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BinCursor<'a> {
+    fn take(&mut self, n: usize) -> crate::error::Result<&'a [u8]> {
+        let slice = self.data.get(self.pos..self.pos + n).ok_or_else(|| {
+            crate::error::HQuantError::InvalidSpec("truncated KlineFrame binary data".to_string())
+        })?;
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn read_u8(&mut self) -> crate::error::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> crate::error::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_i32(&mut self) -> crate::error::Result<i32> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_i64(&mut self) -> crate::error::Result<i64> {
+        Ok(i64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn read_f64(&mut self) -> crate::error::Result<f64> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(ts: i64, close: f64) -> Bar {
+        Bar { ts, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn index_of_timestamp_binary_searches_a_wrapped_buffer() {
+        let mut buf = KlineBuffer::new(3);
+        // Capacity 3, four bars pushed: ts=0 gets evicted.
+        for ts in [0, 60_000, 120_000, 180_000] {
+            buf.push(&bar(ts, ts as f64));
+        }
+
+        assert_eq!(buf.index_of_timestamp(0), None); // evicted
+        assert_eq!(buf.index_of_timestamp(60_000), Some(0));
+        assert_eq!(buf.index_of_timestamp(120_000), Some(1));
+        assert_eq!(buf.index_of_timestamp(180_000), Some(2));
+        assert_eq!(buf.index_of_timestamp(90_000), None); // no such bar
+    }
+
+    #[test]
+    fn get_by_timestamp_returns_the_matching_bar_on_a_wrapped_buffer() {
+        let mut buf = KlineBuffer::new(3);
+        for ts in [0, 60_000, 120_000, 180_000] {
+            buf.push(&bar(ts, ts as f64));
+        }
+
+        let found = buf.get_by_timestamp(120_000).unwrap();
+        assert_eq!(found.ts, 120_000);
+        assert_eq!(found.close, 120_000.0);
+        assert_eq!(buf.get_by_timestamp(0), None);
+    }
+
+    #[test]
+    fn update_at_corrects_a_historical_bar_on_a_wrapped_buffer() {
+        let mut buf = KlineBuffer::new(3);
+        for ts in [0, 60_000, 120_000, 180_000] {
+            buf.push(&bar(ts, ts as f64));
+        }
+        // Chronological order is now [60_000, 120_000, 180_000]; correct
+        // the middle one's close without touching its neighbors.
+        assert!(buf.update_at(1, &bar(120_000, 999.0)));
+
+        assert_eq!(buf.to_bars().iter().map(|b| b.close).collect::<Vec<_>>(), vec![60_000.0, 999.0, 180_000.0]);
+        assert!(!buf.update_at(3, &bar(240_000, 1.0)));
+    }
+
+    #[test]
+    fn round_trips_through_current_binary_format() {
+        let frame = KlineFrame::new(vec![bar(0, 1.0), bar(60_000, 2.0), bar(120_000, 3.0)]);
+        let bytes = frame.to_binary();
+        assert_eq!(KlineFrame::from_binary(&bytes).unwrap(), frame);
+    }
+
+    #[test]
+    fn round_trips_timestamps_one_month_apart() {
+        // ~30 days in milliseconds, comfortably past i32::MAX (~24.8 days)
+        // in a single delta.
+        const ONE_MONTH_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+        let frame = KlineFrame::new(vec![bar(0, 100.0), bar(ONE_MONTH_MS, 105.0)]);
+        let bytes = frame.to_binary();
+        let restored = KlineFrame::from_binary(&bytes).unwrap();
+        assert_eq!(restored, frame);
+    }
+
+    #[test]
+    fn reads_legacy_version_1_frames() {
+        let mut buf = Vec::new();
+        buf.push(1u8); // version
+        buf.extend_from_slice(&2u32.to_le_bytes());
+        buf.extend_from_slice(&0i64.to_le_bytes()); // ts_base
+        for (delta, close) in [(0i32, 1.0f64), (60_000, 2.0)] {
+            buf.extend_from_slice(&delta.to_le_bytes());
+            buf.extend_from_slice(&close.to_le_bytes()); // open
+            buf.extend_from_slice(&close.to_le_bytes()); // high
+            buf.extend_from_slice(&close.to_le_bytes()); // low
+            buf.extend_from_slice(&close.to_le_bytes()); // close
+            buf.extend_from_slice(&1.0f64.to_le_bytes()); // volume
+        }
+        let restored = KlineFrame::from_binary(&buf).unwrap();
+        assert_eq!(restored, KlineFrame::new(vec![bar(0, 1.0), bar(60_000, 2.0)]));
+    }
+
+    #[test]
+    fn reads_a_version_3_frame_with_extra_columns_beyond_ohlcv() {
+        let mut buf = Vec::new();
+        buf.push(3u8); // version
+        buf.push(7u8); // columns: OHLCV plus 2 extra (e.g. buy/sell volume)
+        buf.extend_from_slice(&2u32.to_le_bytes());
+        buf.extend_from_slice(&0i64.to_le_bytes()); // ts_base
+        for (delta, close) in [(0i64, 1.0f64), (60_000, 2.0)] {
+            buf.extend_from_slice(&delta.to_le_bytes());
+            buf.extend_from_slice(&close.to_le_bytes()); // open
+            buf.extend_from_slice(&close.to_le_bytes()); // high
+            buf.extend_from_slice(&close.to_le_bytes()); // low
+            buf.extend_from_slice(&close.to_le_bytes()); // close
+            buf.extend_from_slice(&1.0f64.to_le_bytes()); // volume
+            buf.extend_from_slice(&99.0f64.to_le_bytes()); // extra column 1
+            buf.extend_from_slice(&99.0f64.to_le_bytes()); // extra column 2
+        }
+        let restored = KlineFrame::from_binary(&buf).unwrap();
+        assert_eq!(restored, KlineFrame::new(vec![bar(0, 1.0), bar(60_000, 2.0)]));
+    }
+
+    #[test]
+    fn a_declared_column_count_below_ohlcv_is_an_error() {
+        let mut buf = Vec::new();
+        buf.push(3u8); // version
+        buf.push(4u8); // columns: fewer than the 5 OHLCV needs
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        buf.extend_from_slice(&0i64.to_le_bytes());
+        assert!(KlineFrame::from_binary(&buf).is_err());
+    }
+
+    #[test]
+    fn round_trips_through_csv() {
+        let frame = KlineFrame::new(vec![bar(0, 1.0), bar(60_000, 2.0), bar(120_000, 3.0)]);
+        let csv = frame.to_csv();
+        assert_eq!(KlineFrame::from_csv(&csv).unwrap(), frame);
+    }
+
+    #[test]
+    fn parses_reordered_columns_an_extra_side_column_and_an_iso8601_timestamp() {
+        let csv = "close,side,timestamp,open,volume,high,low\n\
+                    105.0,buy,2021-01-01T00:00:01.500Z,100.0,10.0,110.0,95.0\n";
+        let frame = KlineFrame::from_csv(csv).unwrap();
+        assert_eq!(
+            frame.bars,
+            vec![Bar { ts: 1_609_459_201_500, open: 100.0, high: 110.0, low: 95.0, close: 105.0, volume: 10.0 }]
+        );
+    }
+
+    #[test]
+    fn missing_required_column_is_an_error() {
+        let csv = "open,high,low,close\n1.0,1.0,1.0,1.0\n";
+        assert!(KlineFrame::from_csv(csv).is_err());
+    }
+}
@@ -0,0 +1,15 @@
+pub mod aggregator;
+pub mod alert;
+pub mod backtest;
+pub mod common;
+pub mod engine;
+pub mod error;
+pub mod ffi;
+pub mod heikin_ashi;
+pub mod indicator;
+pub mod kline;
+pub mod pair_engine;
+pub mod spread;
+pub mod strategy;
+
+pub use error::{HQuantError, Result};
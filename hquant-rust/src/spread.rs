@@ -0,0 +1,201 @@
+//! Synthetic spread construction for spread trading (calendar or
+//! inter-market spreads): combine two aligned bar streams into a single
+//! synthetic [`Bar`] stream that every existing indicator/strategy can run
+//! on unmodified, the same way [`crate::heikin_ashi::HeikinAshi`] produces
+//! a transformed `Bar` stream from a raw one.
+
+use crate::common::RollingCorrelation;
+use crate::error::{HQuantError, Result};
+use crate::kline::Bar;
+
+/// How the two legs are combined into a single spread value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpreadMode {
+    /// `leg_a - ratio * leg_b`, the usual fixed-hedge-ratio spread.
+    Linear(f64),
+    /// `ln(leg_a / leg_b)`, scale-free and preferred when the two legs
+    /// trade at very different price levels.
+    LogRatio,
+    /// `leg_a - beta * leg_b`, where `beta` is [`leg_a`'s regression
+    /// coefficient on `leg_b`][RollingCorrelation::beta] over the trailing
+    /// `period` closes, re-estimated bar by bar instead of fixed up front
+    /// like [`SpreadMode::Linear`] -- for pairs whose hedge ratio drifts
+    /// over time. Falls back to a ratio of `1.0` while the window has no
+    /// variance to regress against (flat legs, or still warming up on the
+    /// first bar).
+    RollingBeta { period: usize },
+}
+
+impl SpreadMode {
+    fn combine(&self, a: f64, b: f64, ratio: f64) -> f64 {
+        match self {
+            SpreadMode::Linear(ratio) => a - ratio * b,
+            SpreadMode::LogRatio => (a / b).ln(),
+            SpreadMode::RollingBeta { .. } => a - ratio * b,
+        }
+    }
+}
+
+/// Builds a synthetic spread `Bar` stream from two leg bar streams.
+///
+/// The two legs must arrive bar-for-bar aligned on `ts`; there is no
+/// resampling or interpolation here, since misaligning two legs even by
+/// one bar silently corrupts the spread's mean-reversion behavior in a way
+/// that's hard to detect downstream. [`push_legs`](Self::push_legs) checks
+/// this and returns [`HQuantError::InvalidSpec`] on a mismatch.
+#[derive(Debug, Clone)]
+pub struct SpreadBuilder {
+    mode: SpreadMode,
+    /// Tracks the incremental regression beta of `leg_a`'s close on
+    /// `leg_b`'s, for [`SpreadMode::RollingBeta`]. `None` for modes that
+    /// don't need it.
+    beta: Option<RollingCorrelation>,
+}
+
+impl SpreadBuilder {
+    pub fn new(mode: SpreadMode) -> Self {
+        let beta = match mode {
+            SpreadMode::RollingBeta { period } => Some(RollingCorrelation::new(period)),
+            _ => None,
+        };
+        Self { mode, beta }
+    }
+
+    /// Combine one bar from each leg into a single synthetic spread bar.
+    /// OHLC fields are combined independently (so the spread bar's own
+    /// high/low reflect the intrabar range of the spread, not just its
+    /// open/close); volume is the smaller of the two legs', since that's
+    /// the most either leg could actually trade. For
+    /// [`SpreadMode::RollingBeta`], folds this bar's closes into the
+    /// running regression *after* combining, so the beta used here always
+    /// reflects bars strictly before this one -- the same
+    /// commit-on-next-push discipline [`crate::indicator::Ema`] uses,
+    /// applied to avoid a spread bar leaking its own close into the beta
+    /// that produced it.
+    pub fn push_legs(&mut self, leg_a: &Bar, leg_b: &Bar) -> Result<Bar> {
+        if leg_a.ts != leg_b.ts {
+            return Err(HQuantError::InvalidSpec(format!(
+                "spread legs are not aligned: leg_a.ts={} leg_b.ts={}",
+                leg_a.ts, leg_b.ts
+            )));
+        }
+        let ratio = match (&self.mode, &self.beta) {
+            (SpreadMode::RollingBeta { .. }, Some(beta)) => {
+                let b = beta.beta();
+                if b == 0.0 || b.is_nan() { 1.0 } else { b }
+            }
+            _ => 0.0,
+        };
+        let bar = Bar {
+            ts: leg_a.ts,
+            open: self.mode.combine(leg_a.open, leg_b.open, ratio),
+            high: self.mode.combine(leg_a.high, leg_b.high, ratio),
+            low: self.mode.combine(leg_a.low, leg_b.low, ratio),
+            close: self.mode.combine(leg_a.close, leg_b.close, ratio),
+            volume: leg_a.volume.min(leg_b.volume),
+        };
+        if let Some(beta) = &mut self.beta {
+            beta.push(leg_b.close, leg_a.close);
+        }
+        Ok(bar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(ts: i64, open: f64, high: f64, low: f64, close: f64) -> Bar {
+        Bar { ts, open, high, low, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn linear_spread_is_leg_a_minus_ratio_times_leg_b() {
+        let mut builder = SpreadBuilder::new(SpreadMode::Linear(2.0));
+        let a = bar(0, 100.0, 101.0, 99.0, 100.0);
+        let b = bar(0, 40.0, 41.0, 39.0, 40.0);
+        let spread = builder.push_legs(&a, &b).unwrap();
+        assert!((spread.close - (100.0 - 2.0 * 40.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn log_ratio_spread_is_zero_when_legs_move_together() {
+        let mut builder = SpreadBuilder::new(SpreadMode::LogRatio);
+        let a = bar(0, 100.0, 101.0, 99.0, 100.0);
+        let b = bar(0, 100.0, 101.0, 99.0, 100.0);
+        let spread = builder.push_legs(&a, &b).unwrap();
+        assert!((spread.close - 0.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn misaligned_leg_timestamps_are_rejected() {
+        let mut builder = SpreadBuilder::new(SpreadMode::Linear(1.0));
+        let a = bar(0, 100.0, 101.0, 99.0, 100.0);
+        let b = bar(60_000, 100.0, 101.0, 99.0, 100.0);
+        assert!(builder.push_legs(&a, &b).is_err());
+    }
+
+    #[test]
+    fn rolling_beta_tracks_the_legs_regression_coefficient() {
+        let mut builder = SpreadBuilder::new(SpreadMode::RollingBeta { period: 10 });
+        let mut last_close: Option<f64> = None;
+        let mut max_drift = 0.0_f64;
+        for i in 0..15 {
+            let a_close = 100.0 + i as f64;
+            let b_close = 50.0 + 2.0 * i as f64; // leg_a moves at half leg_b's rate: beta ~= 0.5
+            let a = bar(i, a_close, a_close, a_close, a_close);
+            let b = bar(i, b_close, b_close, b_close, b_close);
+            let spread = builder.push_legs(&a, &b).unwrap();
+            // Skip the first couple of bars: with fewer than two distinct
+            // points the beta estimate has no variance to regress against
+            // yet and falls back to a ratio of 1.0.
+            if i >= 3 {
+                if let Some(prev) = last_close {
+                    max_drift = max_drift.max((spread.close - prev).abs());
+                }
+                last_close = Some(spread.close);
+            }
+        }
+        // Once the beta estimate has picked up leg_a's ~0.5x rate relative
+        // to leg_b, the spread should stay essentially flat bar to bar --
+        // very unlike the steady drift a fixed ratio of 1.0
+        // (`SpreadMode::Linear(1.0)`) would produce here.
+        assert!(max_drift < 0.5, "max_drift={max_drift}");
+    }
+
+    /// Build a spread from two legs that drift apart mid-series (breaking
+    /// their usual co-movement), then run a rolling z-score over the
+    /// spread's close to detect the divergence -- the whole point of
+    /// putting the two legs into one synthetic `Bar` stream in the first
+    /// place.
+    #[test]
+    fn a_zscore_over_the_spread_detects_a_divergence_between_correlated_legs() {
+        use crate::common::ring_buffer::F64RingBuffer;
+
+        let mut builder = SpreadBuilder::new(SpreadMode::Linear(1.0));
+        let mut window = F64RingBuffer::new(10);
+        let mut max_abs_zscore = 0.0_f64;
+
+        for i in 0..30 {
+            let base = 100.0 + i as f64;
+            // The two legs move together for the first 20 bars, then leg_b
+            // stalls while leg_a keeps climbing -- a classic spread
+            // divergence.
+            let b_close = if i < 20 { base } else { 100.0 + 19.0 };
+            let a = bar(i, base, base + 1.0, base - 1.0, base);
+            let b = bar(i, b_close, b_close + 1.0, b_close - 1.0, b_close);
+            let spread = builder.push_legs(&a, &b).unwrap();
+
+            window.push(spread.close);
+            if i >= 5 {
+                let std_dev = window.std_dev();
+                if std_dev > 0.0 {
+                    let zscore = (spread.close - window.mean()) / std_dev;
+                    max_abs_zscore = max_abs_zscore.max(zscore.abs());
+                }
+            }
+        }
+
+        assert!(max_abs_zscore > 1.5, "max_abs_zscore={max_abs_zscore}");
+    }
+}
@@ -0,0 +1,836 @@
+//! Hand-written recursive-descent parser for the strategy DSL.
+//!
+//! One rule per line:
+//!
+//! ```text
+//! CROSS_ABOVE(EMA_12, EMA_26) => BUY
+//! CLOSE < SMA_50 => SELL
+//! ```
+//!
+//! Identifiers are resolved against an [`IndicatorGraph`] at parse time:
+//! known price fields (`OPEN`, `HIGH`, `LOW`, `CLOSE`, `VOLUME`, and the
+//! derived `TYPICAL`/`HLC3` and `MEDIAN`) become
+//! [`Operand::Field`], everything else must already exist as a graph node.
+//!
+//! This is the crate's only strategy parser -- there's no separate
+//! pest-based grammar and no second hand-written fallback to keep in
+//! sync, so `!=` (lexed as [`CompareOp::Ne`]) and the `BETWEEN lo AND hi`
+//! range form below both live here, once.
+//!
+//! A program may open with `COOLDOWN n` and/or `EDGE_TRIGGERED` directive
+//! lines (in either order) before its rules, e.g.:
+//!
+//! ```text
+//! COOLDOWN 5
+//! EDGE_TRIGGERED
+//! CROSS_ABOVE(EMA_12, EMA_26) => BUY
+//! ```
+//!
+//! See [`CompiledStrategy::cooldown_bars`] and
+//! [`CompiledStrategy::edge_triggered`].
+
+use super::{Action, BoolExpr, Bracket, CompareOp, CompiledStrategy, Expr, Operand, Rule};
+use crate::error::{HQuantError, Result};
+use crate::indicator::IndicatorGraph;
+use crate::kline::Field;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Symbol(&'static str),
+    Newline,
+    Eof,
+}
+
+struct Lexer<'a> {
+    src: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src, chars: src.char_indices().peekable(), line: 1, column: 1 }
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let (_, c) = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn peek_char(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Token, usize, usize)>> {
+        let mut tokens = Vec::new();
+        loop {
+            while matches!(self.peek_char(), Some(c) if c == ' ' || c == '\t' || c == '\r') {
+                self.advance();
+            }
+            if let Some('#') = self.peek_char() {
+                while !matches!(self.peek_char(), Some('\n') | None) {
+                    self.advance();
+                }
+            }
+            let (line, column) = (self.line, self.column);
+            let Some(c) = self.peek_char() else {
+                tokens.push((Token::Eof, line, column));
+                break;
+            };
+            if c == '\n' {
+                self.advance();
+                tokens.push((Token::Newline, line, column));
+                continue;
+            }
+            if c.is_ascii_digit() {
+                let start = self.chars.peek().unwrap().0;
+                while matches!(self.peek_char(), Some(c) if c.is_ascii_digit() || c == '.') {
+                    self.advance();
+                }
+                let end = self.chars.peek().map(|&(i, _)| i).unwrap_or(self.src.len());
+                let text = &self.src[start..end];
+                let value: f64 = text.parse().map_err(|_| HQuantError::Parse {
+                    line,
+                    column,
+                    message: format!("invalid number literal `{text}`"),
+                })?;
+                tokens.push((Token::Number(value), line, column));
+                continue;
+            }
+            if c.is_alphabetic() || c == '_' {
+                let start = self.chars.peek().unwrap().0;
+                while matches!(self.peek_char(), Some(c) if c.is_alphanumeric() || c == '_') {
+                    self.advance();
+                }
+                let end = self.chars.peek().map(|&(i, _)| i).unwrap_or(self.src.len());
+                tokens.push((Token::Ident(self.src[start..end].to_string()), line, column));
+                continue;
+            }
+            let symbol = match c {
+                '(' => { self.advance(); "(" }
+                ')' => { self.advance(); ")" }
+                ',' => { self.advance(); "," }
+                '%' => { self.advance(); "%" }
+                '+' => { self.advance(); "+" }
+                '-' => { self.advance(); "-" }
+                '*' => { self.advance(); "*" }
+                '/' => { self.advance(); "/" }
+                '>' => {
+                    self.advance();
+                    if self.peek_char() == Some('=') { self.advance(); ">=" } else { ">" }
+                }
+                '<' => {
+                    self.advance();
+                    if self.peek_char() == Some('=') { self.advance(); "<=" } else { "<" }
+                }
+                '=' => {
+                    self.advance();
+                    match self.peek_char() {
+                        Some('=') => { self.advance(); "==" }
+                        Some('>') => { self.advance(); "=>" }
+                        _ => {
+                            return Err(HQuantError::Parse {
+                                line,
+                                column,
+                                message: "unexpected `=`".to_string(),
+                            })
+                        }
+                    }
+                }
+                '!' => {
+                    self.advance();
+                    if self.peek_char() == Some('=') {
+                        self.advance();
+                        "!="
+                    } else {
+                        return Err(HQuantError::Parse { line, column, message: "unexpected `!`".to_string() });
+                    }
+                }
+                other => {
+                    return Err(HQuantError::Parse {
+                        line,
+                        column,
+                        message: format!("unexpected character `{other}`"),
+                    })
+                }
+            };
+            tokens.push((Token::Symbol(symbol), line, column));
+        }
+        Ok(tokens)
+    }
+}
+
+struct Parser<'g> {
+    tokens: Vec<(Token, usize, usize)>,
+    pos: usize,
+    graph: &'g IndicatorGraph,
+}
+
+impl<'g> Parser<'g> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn pos_info(&self) -> (usize, usize) {
+        let (_, line, column) = self.tokens[self.pos];
+        (line, column)
+    }
+
+    fn bump(&mut self) -> Token {
+        let tok = self.tokens[self.pos].0.clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn skip_newlines(&mut self) {
+        while matches!(self.peek(), Token::Newline) {
+            self.bump();
+        }
+    }
+
+    fn expect_symbol(&mut self, sym: &str) -> Result<()> {
+        let (line, column) = self.pos_info();
+        match self.bump() {
+            Token::Symbol(s) if s == sym => Ok(()),
+            other => Err(HQuantError::Parse {
+                line,
+                column,
+                message: format!("expected `{sym}`, found {other:?}"),
+            }),
+        }
+    }
+
+    fn parse_program(&mut self) -> Result<(Vec<Rule>, usize, bool)> {
+        let mut rules = Vec::new();
+        let mut cooldown_bars = 0;
+        let mut edge_triggered = false;
+        self.skip_newlines();
+        loop {
+            match self.peek() {
+                Token::Ident(name) if name.eq_ignore_ascii_case("COOLDOWN") => {
+                    self.bump();
+                    cooldown_bars = self.parse_cooldown_value()?;
+                }
+                Token::Ident(name) if name.eq_ignore_ascii_case("EDGE_TRIGGERED") => {
+                    self.bump();
+                    edge_triggered = true;
+                }
+                _ => break,
+            }
+            self.skip_newlines();
+        }
+        while !matches!(self.peek(), Token::Eof) {
+            rules.push(self.parse_rule()?);
+            self.skip_newlines();
+        }
+        Ok((rules, cooldown_bars, edge_triggered))
+    }
+
+    /// Parse a leading `COOLDOWN n` directive's `n`, e.g. `COOLDOWN 5`
+    /// before a strategy's rules -- see [`CompiledStrategy::cooldown_bars`].
+    /// Called after the `COOLDOWN` keyword itself has been consumed.
+    fn parse_cooldown_value(&mut self) -> Result<usize> {
+        let (line, column) = self.pos_info();
+        match self.bump() {
+            Token::Number(n) if n >= 0.0 && n.fract() == 0.0 => Ok(n as usize),
+            other => Err(HQuantError::Parse {
+                line,
+                column,
+                message: format!("expected a non-negative integer bar count, found {other:?}"),
+            }),
+        }
+    }
+
+    fn parse_rule(&mut self) -> Result<Rule> {
+        let condition = self.parse_or()?;
+        self.expect_symbol("=>")?;
+        let action = self.parse_action()?;
+        let bracket = self.parse_optional_bracket()?;
+        Ok(Rule { condition, action, bracket })
+    }
+
+    fn parse_action(&mut self) -> Result<Action> {
+        let (line, column) = self.pos_info();
+        match self.bump() {
+            Token::Ident(name) => match name.to_ascii_uppercase().as_str() {
+                "BUY" => Ok(Action::Buy),
+                "SELL" => Ok(Action::Sell),
+                "HOLD" => Ok(Action::Hold),
+                "CLOSE" => Ok(Action::Close),
+                "GUARD" => Ok(Action::Guard),
+                other => Err(HQuantError::Parse {
+                    line,
+                    column,
+                    message: format!("unknown action `{other}`"),
+                }),
+            },
+            other => Err(HQuantError::Parse {
+                line,
+                column,
+                message: format!("expected an action, found {other:?}"),
+            }),
+        }
+    }
+
+    /// Parse an optional `WITH STOP x% TARGET y%` suffix arming a bracket
+    /// atomically with the entry it follows, e.g.
+    /// `CROSS_ABOVE(EMA_12, EMA_26) => BUY WITH STOP 2% TARGET 5%`.
+    fn parse_optional_bracket(&mut self) -> Result<Option<Bracket>> {
+        if !matches!(self.peek(), Token::Ident(name) if name.eq_ignore_ascii_case("WITH")) {
+            return Ok(None);
+        }
+        self.bump(); // WITH
+        self.expect_ident("STOP")?;
+        let stop_pct = self.parse_percent()?;
+        self.expect_ident("TARGET")?;
+        let target_pct = self.parse_percent()?;
+        Ok(Some(Bracket { stop_pct, target_pct }))
+    }
+
+    fn expect_ident(&mut self, expected: &str) -> Result<()> {
+        let (line, column) = self.pos_info();
+        match self.bump() {
+            Token::Ident(name) if name.eq_ignore_ascii_case(expected) => Ok(()),
+            other => Err(HQuantError::Parse {
+                line,
+                column,
+                message: format!("expected `{expected}`, found {other:?}"),
+            }),
+        }
+    }
+
+    fn parse_percent(&mut self) -> Result<f64> {
+        let (line, column) = self.pos_info();
+        let value = match self.bump() {
+            Token::Number(n) => n,
+            other => {
+                return Err(HQuantError::Parse {
+                    line,
+                    column,
+                    message: format!("expected a percentage, found {other:?}"),
+                })
+            }
+        };
+        self.expect_symbol("%")?;
+        Ok(value)
+    }
+
+    fn parse_or(&mut self) -> Result<BoolExpr> {
+        let mut lhs = self.parse_and()?;
+        while let Token::Ident(name) = self.peek() {
+            if name.eq_ignore_ascii_case("OR") {
+                self.bump();
+                let rhs = self.parse_and()?;
+                lhs = BoolExpr::Or(Box::new(lhs), Box::new(rhs));
+            } else {
+                break;
+            }
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<BoolExpr> {
+        let mut lhs = self.parse_unary()?;
+        while let Token::Ident(name) = self.peek() {
+            if name.eq_ignore_ascii_case("AND") {
+                self.bump();
+                let rhs = self.parse_unary()?;
+                lhs = BoolExpr::And(Box::new(lhs), Box::new(rhs));
+            } else {
+                break;
+            }
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<BoolExpr> {
+        if let Token::Ident(name) = self.peek() {
+            if name.eq_ignore_ascii_case("NOT") {
+                self.bump();
+                return Ok(BoolExpr::Not(Box::new(self.parse_unary()?)));
+            }
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<BoolExpr> {
+        if matches!(self.peek(), Token::Symbol("(")) {
+            self.bump();
+            let inner = self.parse_or()?;
+            self.expect_symbol(")")?;
+            return Ok(inner);
+        }
+        // Function-call style boolean predicates: CROSS_ABOVE(a, b) / CROSS_BELOW(a, b).
+        if let Token::Ident(name) = self.peek().clone() {
+            let upper = name.to_ascii_uppercase();
+            if (upper == "CROSS_ABOVE" || upper == "CROSS_BELOW")
+                && self.tokens.get(self.pos + 1).map(|t| &t.0) == Some(&Token::Symbol("("))
+            {
+                self.bump(); // ident
+                self.bump(); // (
+                let a = self.parse_operand()?;
+                self.expect_symbol(",")?;
+                let b = self.parse_operand()?;
+                self.expect_symbol(")")?;
+                return Ok(if upper == "CROSS_ABOVE" {
+                    BoolExpr::CrossAbove { a, b }
+                } else {
+                    BoolExpr::CrossBelow { a, b }
+                });
+            }
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<BoolExpr> {
+        let lhs = self.parse_expr()?;
+        if matches!(self.peek(), Token::Ident(name) if name.eq_ignore_ascii_case("BETWEEN")) {
+            return self.parse_between(lhs);
+        }
+        let (line, column) = self.pos_info();
+        let op = match self.bump() {
+            Token::Symbol(">") => CompareOp::Gt,
+            Token::Symbol("<") => CompareOp::Lt,
+            Token::Symbol(">=") => CompareOp::Ge,
+            Token::Symbol("<=") => CompareOp::Le,
+            Token::Symbol("==") => CompareOp::Eq,
+            Token::Symbol("!=") => CompareOp::Ne,
+            other => {
+                return Err(HQuantError::Parse {
+                    line,
+                    column,
+                    message: format!("expected a comparison operator, found {other:?}"),
+                })
+            }
+        };
+        let rhs = self.parse_expr()?;
+        Ok(BoolExpr::Compare { op, lhs, rhs })
+    }
+
+    /// `x BETWEEN lo AND hi`, lowered straight to `(x >= lo AND x <= hi)`
+    /// rather than giving `BoolExpr` its own inclusive-range variant --
+    /// this is the only place that shape is built, so a dedicated variant
+    /// would just be a second way to say the same thing.
+    fn parse_between(&mut self, x: Expr) -> Result<BoolExpr> {
+        self.bump(); // BETWEEN
+        let lo = self.parse_expr()?;
+        self.expect_ident("AND")?;
+        let hi = self.parse_expr()?;
+        Ok(BoolExpr::And(
+            Box::new(BoolExpr::Compare { op: CompareOp::Ge, lhs: x.clone(), rhs: lo }),
+            Box::new(BoolExpr::Compare { op: CompareOp::Le, lhs: x, rhs: hi }),
+        ))
+    }
+
+    /// `+`/`-`, the lowest-precedence arithmetic level -- e.g. the `+ 5`
+    /// in `SMA_20 * 1.02 + 5 > CLOSE`.
+    fn parse_expr(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_arith_term()?;
+        loop {
+            match self.peek() {
+                Token::Symbol("+") => {
+                    self.bump();
+                    let rhs = self.parse_arith_term()?;
+                    lhs = Expr::Add(Box::new(lhs), Box::new(rhs));
+                }
+                Token::Symbol("-") => {
+                    self.bump();
+                    let rhs = self.parse_arith_term()?;
+                    lhs = Expr::Sub(Box::new(lhs), Box::new(rhs));
+                }
+                _ => break,
+            }
+        }
+        Ok(lhs)
+    }
+
+    /// `*`/`/`, binding tighter than `+`/`-` above.
+    fn parse_arith_term(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_expr_primary()?;
+        loop {
+            match self.peek() {
+                Token::Symbol("*") => {
+                    self.bump();
+                    let rhs = self.parse_expr_primary()?;
+                    lhs = Expr::Mul(Box::new(lhs), Box::new(rhs));
+                }
+                Token::Symbol("/") => {
+                    self.bump();
+                    let rhs = self.parse_expr_primary()?;
+                    lhs = Expr::Div(Box::new(lhs), Box::new(rhs));
+                }
+                _ => break,
+            }
+        }
+        Ok(lhs)
+    }
+
+    /// A bare operand, `PREV(...)`, or a fully parenthesized arithmetic
+    /// sub-expression. Note the plain `(` case is a *narrower* grouping
+    /// than [`Parser::parse_primary`]'s: by the time control reaches here
+    /// a comparison has already started, so there's no ambiguity with
+    /// that outer rule's boolean-grouping `(`.
+    fn parse_expr_primary(&mut self) -> Result<Expr> {
+        if matches!(self.peek(), Token::Symbol("(")) {
+            self.bump();
+            let inner = self.parse_expr()?;
+            self.expect_symbol(")")?;
+            return Ok(inner);
+        }
+        // `PREV(expr)`: function-call style, like `CROSS_ABOVE(a, b)`
+        // above and `POSITION()` in `parse_operand` -- not `expr[1]`,
+        // which would need a new `[`/`]` token pair for one accessor.
+        if let Token::Ident(name) = self.peek().clone() {
+            if name.eq_ignore_ascii_case("PREV")
+                && self.tokens.get(self.pos + 1).map(|t| &t.0) == Some(&Token::Symbol("("))
+            {
+                self.bump(); // PREV
+                self.bump(); // (
+                let inner = self.parse_expr()?;
+                self.expect_symbol(")")?;
+                return Ok(Expr::Prev(Box::new(inner)));
+            }
+        }
+        Ok(Expr::Operand(self.parse_operand()?))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand> {
+        let (line, column) = self.pos_info();
+        match self.bump() {
+            Token::Number(n) => Ok(Operand::Constant(n)),
+            Token::Ident(name) => {
+                let upper = name.to_ascii_uppercase();
+                if (upper == "POSITION" || upper == "UNREALIZED_PNL_PCT")
+                    && matches!(self.peek(), Token::Symbol("("))
+                {
+                    self.bump(); // (
+                    self.expect_symbol(")")?;
+                    return Ok(if upper == "POSITION" { Operand::Position } else { Operand::UnrealizedPnlPct });
+                }
+                if let Some(field) = Field::from_name(&name) {
+                    Ok(Operand::Field(field))
+                } else if let Some(id) = self.graph.node_id(&name) {
+                    Ok(Operand::Indicator(id))
+                } else {
+                    Err(HQuantError::UnknownIndicator(name))
+                }
+            }
+            other => Err(HQuantError::Parse {
+                line,
+                column,
+                message: format!("expected an operand, found {other:?}"),
+            }),
+        }
+    }
+}
+
+/// Append a caret-pointer snippet of the offending line to a
+/// [`HQuantError::Parse`]'s message, so a long DSL file doesn't leave the
+/// column number to be counted by hand, e.g.:
+///
+/// ```text
+/// parse error at line 2, column 8: expected an operand, found Eof
+/// CLOSE >
+///        ^
+/// ```
+///
+/// Every other variant (e.g. [`HQuantError::UnknownIndicator`], which
+/// carries no position) passes through unchanged.
+fn with_snippet(source: &str, err: HQuantError) -> HQuantError {
+    let HQuantError::Parse { line, column, message } = err else { return err };
+    let offending_line = source.lines().nth(line.saturating_sub(1)).unwrap_or("");
+    let caret = " ".repeat(column.saturating_sub(1)) + "^";
+    HQuantError::Parse { line, column, message: format!("{message}\n{offending_line}\n{caret}") }
+}
+
+/// Parse `source` into a [`CompiledStrategy`], resolving every identifier
+/// against `graph`.
+pub fn compile(source: &str, graph: &IndicatorGraph) -> Result<CompiledStrategy> {
+    let tokens = Lexer::new(source).tokenize().map_err(|e| with_snippet(source, e))?;
+    let mut parser = Parser { tokens, pos: 0, graph };
+    let (rules, cooldown_bars, edge_triggered) =
+        parser.parse_program().map_err(|e| with_snippet(source, e))?;
+    Ok(CompiledStrategy { rules, cooldown_bars, edge_triggered })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicator::Ema;
+    use crate::kline::{Bar, Field as F};
+    use crate::strategy::{FieldHistory, Signal};
+
+    #[test]
+    fn compiles_cross_above_rule() {
+        let mut graph = IndicatorGraph::new();
+        graph.add_field_indicator("EMA_12", F::Close, Box::new(Ema::new(12))).unwrap();
+        graph.add_field_indicator("EMA_26", F::Close, Box::new(Ema::new(26))).unwrap();
+        let strategy = compile("CROSS_ABOVE(EMA_12, EMA_26) => BUY", &graph).unwrap();
+        assert_eq!(strategy.rules.len(), 1);
+        assert_eq!(strategy.rules[0].action, Action::Buy);
+        assert!(matches!(strategy.rules[0].condition, BoolExpr::CrossAbove { .. }));
+    }
+
+    #[test]
+    fn compiles_a_leading_cooldown_directive_and_defaults_to_zero_without_one() {
+        let graph = IndicatorGraph::new();
+        let strategy = compile("COOLDOWN 5\nCLOSE > 0 => BUY", &graph).unwrap();
+        assert_eq!(strategy.cooldown_bars, 5);
+        assert_eq!(strategy.rules.len(), 1);
+
+        let strategy = compile("CLOSE > 0 => BUY", &graph).unwrap();
+        assert_eq!(strategy.cooldown_bars, 0);
+    }
+
+    #[test]
+    fn compiles_an_edge_triggered_directive_in_either_order_alongside_cooldown() {
+        let graph = IndicatorGraph::new();
+        let strategy = compile("EDGE_TRIGGERED\nCLOSE > 0 => BUY", &graph).unwrap();
+        assert!(strategy.edge_triggered);
+        assert_eq!(strategy.cooldown_bars, 0);
+
+        let strategy = compile("COOLDOWN 5\nEDGE_TRIGGERED\nCLOSE > 0 => BUY", &graph).unwrap();
+        assert!(strategy.edge_triggered);
+        assert_eq!(strategy.cooldown_bars, 5);
+
+        let strategy = compile("EDGE_TRIGGERED\nCOOLDOWN 5\nCLOSE > 0 => BUY", &graph).unwrap();
+        assert!(strategy.edge_triggered);
+        assert_eq!(strategy.cooldown_bars, 5);
+
+        let strategy = compile("CLOSE > 0 => BUY", &graph).unwrap();
+        assert!(!strategy.edge_triggered);
+    }
+
+    #[test]
+    fn compiles_a_guard_rule_that_suppresses_a_later_buy() {
+        let mut graph = IndicatorGraph::new();
+        graph.add_field_indicator("RSI_14", F::Close, Box::new(Ema::new(14))).unwrap();
+        let src = "RSI_14 > 70 => GUARD\nRSI_14 < 999999 => BUY\n";
+        let strategy = compile(src, &graph).unwrap();
+        assert_eq!(strategy.rules.len(), 2);
+        assert_eq!(strategy.rules[0].action, Action::Guard);
+
+        // RSI_14 (an EMA of close, here) starts below 70, so the guard
+        // doesn't yet suppress the buy.
+        graph.push_bar(&Bar { ts: 0, open: 10.0, high: 10.0, low: 10.0, close: 10.0, volume: 1.0 });
+        assert_eq!(strategy.evaluate(&graph, &FieldHistory::default()).map(|s| s.action), Some(Action::Buy));
+
+        // A run of sharp closes drags RSI_14 (an EMA proxy here) above 70,
+        // so the guard now fires first and the buy rule after it never
+        // gets evaluated.
+        for _ in 0..10 {
+            graph.push_bar(&Bar { ts: 1, open: 200.0, high: 200.0, low: 200.0, close: 200.0, volume: 1.0 });
+        }
+        assert_eq!(strategy.evaluate(&graph, &FieldHistory::default()), None);
+    }
+
+    #[test]
+    fn compiles_multi_rule_program_with_and_or() {
+        let mut graph = IndicatorGraph::new();
+        graph.add_field_indicator("RSI_14", F::Close, Box::new(Ema::new(14))).unwrap();
+        let src = "CLOSE > 100 AND RSI_14 < 30 => BUY\nCLOSE < 50 OR RSI_14 > 70 => SELL\n";
+        let strategy = compile(src, &graph).unwrap();
+        assert_eq!(strategy.rules.len(), 2);
+        assert_eq!(strategy.rules[1].action, Action::Sell);
+    }
+
+    #[test]
+    fn compiles_a_typical_price_condition_and_evaluates_it_against_history() {
+        let graph = IndicatorGraph::new();
+        let strategy = compile("TYPICAL > 9 => BUY\n", &graph).unwrap();
+
+        let history = crate::strategy::FieldHistory {
+            high: vec![12.0],
+            low: vec![8.0],
+            close: vec![10.0],
+            ..Default::default()
+        };
+        // TYPICAL = (12 + 8 + 10) / 3 = 10, above the 9 threshold.
+        assert_eq!(strategy.evaluate(&graph, &history).map(|s| s.action), Some(Action::Buy));
+    }
+
+    #[test]
+    fn unknown_indicator_is_an_error() {
+        let graph = IndicatorGraph::new();
+        let err = compile("CROSS_ABOVE(EMA_12, EMA_26) => BUY", &graph).unwrap_err();
+        assert!(matches!(err, HQuantError::UnknownIndicator(_)));
+    }
+
+    #[test]
+    fn a_malformed_condition_names_the_offending_line_and_column_with_a_caret() {
+        let graph = IndicatorGraph::new();
+        // "CLOSE >" trails off with no right-hand side, so the parser fails
+        // reaching for one right after the `>` -- column 8, one past it.
+        let err = compile("CLOSE >", &graph).unwrap_err();
+        let HQuantError::Parse { line, column, message } = err else {
+            panic!("expected a Parse error, got {err:?}");
+        };
+        assert_eq!(line, 1);
+        assert_eq!(column, 8);
+        // The caret line should point at column 8 under the source snippet.
+        let caret_line = message.lines().last().unwrap();
+        assert_eq!(caret_line, "       ^");
+    }
+
+    #[test]
+    fn compiles_bracketed_entry() {
+        let mut graph = IndicatorGraph::new();
+        graph.add_field_indicator("EMA_12", F::Close, Box::new(Ema::new(12))).unwrap();
+        graph.add_field_indicator("EMA_26", F::Close, Box::new(Ema::new(26))).unwrap();
+        let strategy = compile("CROSS_ABOVE(EMA_12, EMA_26) => BUY WITH STOP 2% TARGET 5%", &graph).unwrap();
+        assert_eq!(strategy.rules.len(), 1);
+        assert_eq!(strategy.rules[0].bracket, Some(Bracket { stop_pct: 2.0, target_pct: 5.0 }));
+    }
+
+    #[test]
+    fn not_equal_is_a_valid_comparison_operator() {
+        let src = "CLOSE != 100 => SELL";
+        let strategy = compile(src, &IndicatorGraph::new()).unwrap();
+        assert!(matches!(
+            strategy.rules[0].condition,
+            BoolExpr::Compare { op: CompareOp::Ne, .. }
+        ));
+    }
+
+    #[test]
+    fn between_lowers_to_an_inclusive_and_of_ge_and_le() {
+        let mut graph = IndicatorGraph::new();
+        graph.add_field_indicator("RSI_14", F::Close, Box::new(Ema::new(14))).unwrap();
+        let strategy = compile("RSI_14 BETWEEN 30 AND 70 => BUY", &graph).unwrap();
+        assert_eq!(strategy.rules.len(), 1);
+        match &strategy.rules[0].condition {
+            BoolExpr::And(lhs, rhs) => {
+                assert!(matches!(
+                    **lhs,
+                    BoolExpr::Compare { op: CompareOp::Ge, rhs: Expr::Operand(Operand::Constant(30.0)), .. }
+                ));
+                assert!(matches!(
+                    **rhs,
+                    BoolExpr::Compare { op: CompareOp::Le, rhs: Expr::Operand(Operand::Constant(70.0)), .. }
+                ));
+            }
+            other => panic!("expected BETWEEN to lower to an And, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn between_matches_hand_rolled_ge_and_le_over_a_full_bar_history() {
+        // `BETWEEN` is sugar over `(x >= lo AND x <= hi)`, not a second
+        // implementation of range checking, so this just confirms the two
+        // spellings agree across a run rather than comparing against a
+        // second parser -- there isn't one (see the module docs above).
+        let mut sugar_graph = IndicatorGraph::new();
+        sugar_graph.add_field_indicator("EMA_5", F::Close, Box::new(Ema::new(5))).unwrap();
+        let mut spelled_out_graph = IndicatorGraph::new();
+        spelled_out_graph.add_field_indicator("EMA_5", F::Close, Box::new(Ema::new(5))).unwrap();
+
+        let sugar = compile("EMA_5 BETWEEN 10 AND 20 => BUY", &sugar_graph).unwrap();
+        let spelled_out = compile("EMA_5 >= 10 AND EMA_5 <= 20 => BUY", &spelled_out_graph).unwrap();
+
+        let closes = [5.0, 8.0, 12.0, 15.0, 18.0, 22.0, 25.0, 14.0, 9.0];
+        for &close in &closes {
+            let bar = crate::kline::Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 };
+            sugar_graph.push_bar(&bar);
+            spelled_out_graph.push_bar(&bar);
+            let history = FieldHistory::default();
+            assert_eq!(
+                sugar.evaluate(&sugar_graph, &history),
+                spelled_out.evaluate(&spelled_out_graph, &history),
+                "close={close}"
+            );
+        }
+    }
+
+    #[test]
+    fn arithmetic_multiplication_binds_tighter_than_addition() {
+        let mut graph = IndicatorGraph::new();
+        graph.add_field_indicator("SMA_20", F::Close, Box::new(Ema::new(20))).unwrap();
+        // `SMA_20 * 1.02 + 5` must parse as `(SMA_20 * 1.02) + 5`, not
+        // `SMA_20 * (1.02 + 5)`.
+        let strategy = compile("SMA_20 * 1.02 + 5 > CLOSE => BUY", &graph).unwrap();
+        let BoolExpr::Compare { lhs, .. } = &strategy.rules[0].condition else {
+            panic!("expected a Compare condition");
+        };
+        match lhs {
+            Expr::Add(mul, five) => {
+                assert!(matches!(**mul, Expr::Mul(_, _)), "expected the `*` to be nested inside the `+`");
+                assert!(matches!(**five, Expr::Operand(Operand::Constant(5.0))));
+            }
+            other => panic!("expected `SMA_20 * 1.02 + 5` to lower to an Add, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn arithmetic_expression_evaluates_a_breakout_buffer_against_a_real_bar_history() {
+        let mut graph = IndicatorGraph::new();
+        graph.add_field_indicator("SMA_2", F::Close, Box::new(crate::indicator::Sma::new(2))).unwrap();
+        let strategy = compile("SMA_2 * 1.1 > CLOSE => BUY", &graph).unwrap();
+
+        let mut push = |close: f64| {
+            let bar = crate::kline::Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 };
+            graph.push_bar(&bar);
+        };
+        push(100.0);
+        push(100.0);
+        // SMA_2 == (100 + 105) / 2 == 102.5, 102.5 * 1.1 == 112.75 > 105
+        // -- breakout buffer trips.
+        push(105.0);
+        let history = FieldHistory { close: vec![100.0, 105.0], ..Default::default() };
+        assert_eq!(strategy.evaluate(&graph, &history), Some(Signal { action: Action::Buy, bracket: None }));
+    }
+
+    #[test]
+    fn prev_reproduces_a_manual_cross_below_a_constant_level() {
+        // Stands `EMA_5 < 30 AND PREV(EMA_5) >= 30` in for
+        // `RSI_14 < 30 AND PREV(RSI_14) >= 30`; this predates RSI's own
+        // arrival in `IndicatorSpec` (see `indicator::rsi`) and an EMA
+        // exercises the same `PREV`-crossing mechanism just as well.
+        let mut graph = IndicatorGraph::new();
+        graph.add_field_indicator("EMA_5", F::Close, Box::new(Ema::new(5))).unwrap();
+        let strategy = compile("EMA_5 < 30 AND PREV(EMA_5) >= 30 => SELL", &graph).unwrap();
+
+        let history = FieldHistory::default();
+        let mut fired = 0;
+        // A steady decline that dips under 30 partway through; the
+        // manual `PREV` cross should fire exactly once, on the bar the
+        // dip actually crosses the level.
+        for close in [50.0, 45.0, 40.0, 35.0, 32.0, 20.0, 10.0, 5.0] {
+            graph.push_bar(&crate::kline::Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 });
+            if strategy.evaluate(&graph, &history).map(|s| s.action) == Some(Action::Sell) {
+                fired += 1;
+            }
+        }
+        assert_eq!(fired, 1);
+    }
+
+    #[test]
+    fn prev_reads_none_before_enough_history_exists() {
+        let mut graph = IndicatorGraph::new();
+        graph.add_field_indicator("EMA_5", F::Close, Box::new(Ema::new(5))).unwrap();
+        let strategy = compile("PREV(EMA_5) > 0 => BUY", &graph).unwrap();
+        // Only one bar pushed -- there's no bar before it to read PREV from.
+        graph.push_bar(&crate::kline::Bar { ts: 0, open: 10.0, high: 10.0, low: 10.0, close: 10.0, volume: 1.0 });
+        assert_eq!(strategy.evaluate(&graph, &FieldHistory::default()), None);
+    }
+
+    #[test]
+    fn compiles_position_filter_rule() {
+        let graph = IndicatorGraph::new();
+        let src = "POSITION() > 0 AND UNREALIZED_PNL_PCT() > 5 => CLOSE";
+        let strategy = compile(src, &graph).unwrap();
+        assert_eq!(strategy.rules.len(), 1);
+        assert_eq!(strategy.rules[0].action, Action::Close);
+        assert!(matches!(strategy.rules[0].condition, BoolExpr::And(_, _)));
+    }
+}
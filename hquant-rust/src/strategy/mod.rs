@@ -0,0 +1,550 @@
+//! Compiled strategies: a small boolean-expression DSL evaluated against an
+//! [`IndicatorGraph`] and the current bar, producing at most one [`Action`]
+//! per bar.
+
+pub mod dsl_parser;
+
+use crate::indicator::{IndicatorGraph, NodeId};
+use crate::kline::Field;
+use std::collections::BTreeSet;
+
+/// A single value a condition can compare against: a constant, a raw price
+/// field, a computed indicator node, or a fact about the strategy's current
+/// open position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand {
+    Constant(f64),
+    Field(Field),
+    Indicator(NodeId),
+    /// `POSITION()`: `1.0` long, `-1.0` short, `0.0` flat.
+    Position,
+    /// `UNREALIZED_PNL_PCT()`: percent gain/loss of the open position, `0.0`
+    /// when flat.
+    UnrealizedPnlPct,
+}
+
+impl Operand {
+    /// Read the operand `n` bars back from the current one (`0` = current).
+    /// Position operands ignore `n` and `bar_history`: they only ever
+    /// reflect the current bar's position state.
+    fn value_at(
+        &self,
+        graph: &IndicatorGraph,
+        bar_history: &[f64],
+        n: usize,
+        position: &PositionContext,
+    ) -> Option<f64> {
+        match self {
+            Operand::Constant(c) => Some(*c),
+            Operand::Field(_) => bar_history.get(bar_history.len().checked_sub(1 + n)?).copied(),
+            Operand::Indicator(id) => graph.get_from_end(*id, n),
+            Operand::Position => Some(position.side),
+            Operand::UnrealizedPnlPct => Some(position.unrealized_pnl_pct),
+        }
+    }
+}
+
+/// The strategy's open position as of the bar being evaluated, fed in by
+/// the caller (engine/backtest) so DSL rules can react to it via
+/// `POSITION()` and `UNREALIZED_PNL_PCT()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionContext {
+    /// `1.0` long, `-1.0` short, `0.0` flat.
+    pub side: f64,
+    /// Percent gain/loss of the open position (e.g. `2.5` for +2.5%), `0.0`
+    /// when flat.
+    pub unrealized_pnl_pct: f64,
+}
+
+impl PositionContext {
+    pub const FLAT: PositionContext = PositionContext { side: 0.0, unrealized_pnl_pct: 0.0 };
+}
+
+impl Default for PositionContext {
+    fn default() -> Self {
+        Self::FLAT
+    }
+}
+
+/// An arithmetic expression over `Operand`s, evaluated to a scalar before
+/// a [`CompareOp`] is applied -- e.g. `SMA_20 * 1.02` as the left side of
+/// `SMA_20 * 1.02 > CLOSE`. Only [`BoolExpr::Compare`] uses this;
+/// `CrossAbove`/`CrossBelow` keep comparing bare [`Operand`]s, since cross
+/// detection is already about two raw series, not a derived one.
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Operand(Operand),
+    /// `PREV(inner)`: evaluate `inner` one bar further back than whatever
+    /// offset it would otherwise be read at. Nests, so `PREV(PREV(x))`
+    /// reads two bars back.
+    Prev(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+}
+
+impl From<Operand> for Expr {
+    fn from(operand: Operand) -> Self {
+        Expr::Operand(operand)
+    }
+}
+
+fn collect_expr_operands(expr: &Expr, ids: &mut BTreeSet<NodeId>) {
+    match expr {
+        Expr::Operand(op) => collect_operand(op, ids),
+        Expr::Prev(inner) => collect_expr_operands(inner, ids),
+        Expr::Add(l, r) | Expr::Sub(l, r) | Expr::Mul(l, r) | Expr::Div(l, r) => {
+            collect_expr_operands(l, ids);
+            collect_expr_operands(r, ids);
+        }
+    }
+}
+
+/// `None` propagates the same way a missing `Operand` value already does
+/// (e.g. an unresolved position field); a `NaN` operand also collapses to
+/// `None` here. Arithmetic on non-NaN operands can still produce a NaN
+/// (e.g. `0 / 0`), which the [`BoolExpr::Compare`] arm of `eval_bool`
+/// catches with its own NaN check.
+///
+/// `n` is the number of bars back the *leaves* of `expr` should be read
+/// at -- `0` for the top-level call, incremented by each `Expr::Prev` it
+/// passes through on the way down. Reading before enough history exists
+/// falls out of `Operand::value_at`'s own bounds check (`None`), the same
+/// as any other missing value.
+fn eval_expr(expr: &Expr, graph: &IndicatorGraph, history: &FieldHistory, n: usize, position: &PositionContext) -> Option<f64> {
+    match expr {
+        Expr::Operand(op) => operand_value(op, graph, history, n, position).filter(|v| !v.is_nan()),
+        Expr::Prev(inner) => eval_expr(inner, graph, history, n + 1, position),
+        Expr::Add(l, r) => Some(eval_expr(l, graph, history, n, position)? + eval_expr(r, graph, history, n, position)?),
+        Expr::Sub(l, r) => Some(eval_expr(l, graph, history, n, position)? - eval_expr(r, graph, history, n, position)?),
+        Expr::Mul(l, r) => Some(eval_expr(l, graph, history, n, position)? * eval_expr(r, graph, history, n, position)?),
+        Expr::Div(l, r) => Some(eval_expr(l, graph, history, n, position)? / eval_expr(r, graph, history, n, position)?),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Gt,
+    Lt,
+    Ge,
+    Le,
+    Eq,
+    Ne,
+}
+
+impl CompareOp {
+    fn apply(&self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            CompareOp::Gt => lhs > rhs,
+            CompareOp::Lt => lhs < rhs,
+            CompareOp::Ge => lhs >= rhs,
+            CompareOp::Le => lhs <= rhs,
+            CompareOp::Eq => lhs == rhs,
+            CompareOp::Ne => lhs != rhs,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BoolExpr {
+    Compare {
+        op: CompareOp,
+        lhs: Expr,
+        rhs: Expr,
+    },
+    /// True on the bar where `a` transitions from at-or-below `b` to
+    /// strictly above it.
+    CrossAbove { a: Operand, b: Operand },
+    /// True on the bar where `a` transitions from at-or-above `b` to
+    /// strictly below it.
+    CrossBelow { a: Operand, b: Operand },
+    And(Box<BoolExpr>, Box<BoolExpr>),
+    Or(Box<BoolExpr>, Box<BoolExpr>),
+    Not(Box<BoolExpr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Buy,
+    Sell,
+    Hold,
+    Close,
+    /// A short-circuit: when a `Guard` rule's condition matches,
+    /// [`CompiledStrategy::evaluate_with`] stops evaluating and returns
+    /// `None` for this bar instead of falling through to the rules after
+    /// it -- for suppressing an entry under some condition (e.g. `RSI(14) >
+    /// 70 => GUARD` ahead of a buy rule) without every downstream consumer
+    /// needing to recognize and filter out a `Hold` signal itself.
+    Guard,
+}
+
+/// Protective levels an entry can arm atomically via `WITH STOP x% TARGET
+/// y%`, expressed as a percent distance from the entry price. Interpreting
+/// these against the position's side (long vs. short) is the backtest's
+/// job, not the DSL's; see [`crate::backtest::Position::open`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bracket {
+    pub stop_pct: f64,
+    pub target_pct: f64,
+}
+
+/// What a matching rule produces: an action, plus the bracket to arm with
+/// it, if any. Kept as one type (rather than the backtest re-deriving a
+/// bracket from a separately tracked "pending bracket" field) so a bracket
+/// can never be registered without the entry it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Signal {
+    pub action: Action,
+    pub bracket: Option<Bracket>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub condition: BoolExpr,
+    pub action: Action,
+    pub bracket: Option<Bracket>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompiledStrategy {
+    pub rules: Vec<Rule>,
+    /// Minimum number of bars that must pass between this strategy's
+    /// emitted signals, e.g. from a `COOLDOWN 5` directive at the top of
+    /// the DSL source. `0` (the default) means every matching bar emits.
+    /// `evaluate`/`evaluate_with` don't consult this themselves -- there's
+    /// no per-bar state to track it against here (see the note on
+    /// `evaluate_with` below) -- it's [`crate::engine::HQuant`] that owns
+    /// the "last emitted bar" bookkeeping and skips a strategy still
+    /// within its cooldown before calling `evaluate`.
+    pub cooldown_bars: usize,
+    /// If set, e.g. from an `EDGE_TRIGGERED` directive at the top of the
+    /// DSL source, a signal is only emitted on the bar this strategy's
+    /// condition transitions from not-matching to matching -- a rising
+    /// edge -- rather than on every bar it holds true. `false` (the
+    /// default) is level-triggered: every matching bar emits, same as
+    /// before this flag existed. Like `cooldown_bars`, this needs the
+    /// previous bar's match state to act on, which `evaluate`/
+    /// `evaluate_with` have no way to remember between calls -- it's
+    /// [`crate::engine::HQuant`] that tracks it and only calls `evaluate`
+    /// through on a rising edge.
+    pub edge_triggered: bool,
+}
+
+impl CompiledStrategy {
+    /// Evaluate rules in order, returning the first matching signal.
+    /// `field_history` supplies enough trailing bar values for `Field`
+    /// operands to support cross detection; it must end with the current
+    /// bar's values. Equivalent to `evaluate_with` with no open position.
+    pub fn evaluate(&self, graph: &IndicatorGraph, field_history: &FieldHistory) -> Option<Signal> {
+        self.evaluate_with(graph, field_history, &PositionContext::FLAT)
+    }
+
+    /// Evaluate rules in order, returning the first matching signal. Like
+    /// `evaluate`, but also feeds `position` to any `POSITION()` /
+    /// `UNREALIZED_PNL_PCT()` operands, letting a rule act as a filter or
+    /// take-profit/stop-loss condition on the strategy's current position.
+    pub fn evaluate_with(
+        &self,
+        graph: &IndicatorGraph,
+        field_history: &FieldHistory,
+        position: &PositionContext,
+    ) -> Option<Signal> {
+        for rule in &self.rules {
+            if eval_bool(&rule.condition, graph, field_history, position) {
+                if rule.action == Action::Guard {
+                    return None;
+                }
+                return Some(Signal { action: rule.action, bracket: rule.bracket });
+            }
+        }
+        None
+    }
+
+    /// The distinct indicator node ids this strategy's rules read from.
+    /// `dsl_parser::compile` resolves indicator names against a shared
+    /// [`IndicatorGraph`] by looking up existing nodes rather than creating
+    /// new ones, so two strategies compiled against the same graph that
+    /// both reference the same indicator name naturally resolve to the
+    /// same id here -- this just surfaces that fact instead of the caller
+    /// having to walk `rules` itself.
+    pub fn indicator_ids(&self) -> BTreeSet<NodeId> {
+        let mut ids = BTreeSet::new();
+        for rule in &self.rules {
+            collect_indicator_ids(&rule.condition, &mut ids);
+        }
+        ids
+    }
+}
+
+fn collect_indicator_ids(expr: &BoolExpr, ids: &mut BTreeSet<NodeId>) {
+    match expr {
+        BoolExpr::Compare { lhs, rhs, .. } => {
+            collect_expr_operands(lhs, ids);
+            collect_expr_operands(rhs, ids);
+        }
+        BoolExpr::CrossAbove { a, b } | BoolExpr::CrossBelow { a, b } => {
+            collect_operand(a, ids);
+            collect_operand(b, ids);
+        }
+        BoolExpr::And(l, r) | BoolExpr::Or(l, r) => {
+            collect_indicator_ids(l, ids);
+            collect_indicator_ids(r, ids);
+        }
+        BoolExpr::Not(inner) => collect_indicator_ids(inner, ids),
+    }
+}
+
+fn collect_operand(op: &Operand, ids: &mut BTreeSet<NodeId>) {
+    if let Operand::Indicator(id) = op {
+        ids.insert(*id);
+    }
+}
+
+/// Trailing per-field bar values, oldest first, ending at the current bar.
+/// Only as much history as the DSL needs (currently one prior bar for
+/// cross detection) needs to be kept by callers.
+#[derive(Debug, Clone, Default)]
+pub struct FieldHistory {
+    pub open: Vec<f64>,
+    pub high: Vec<f64>,
+    pub low: Vec<f64>,
+    pub close: Vec<f64>,
+    pub volume: Vec<f64>,
+}
+
+impl FieldHistory {
+    fn series(&self, field: Field) -> &[f64] {
+        match field {
+            Field::Open => &self.open,
+            Field::High => &self.high,
+            Field::Low => &self.low,
+            Field::Close => &self.close,
+            Field::Volume => &self.volume,
+            // Derived fields have no stored column of their own -- see
+            // `value_at` below, which computes them from `high`/`low`/
+            // `close` directly instead of calling this.
+            Field::Typical | Field::Median => &[],
+        }
+    }
+
+    /// Read `field` `n` bars back from the current one (`0` = current),
+    /// the same indexing [`Operand::value_at`] uses for other operands.
+    /// [`Field::Typical`]/[`Field::Median`] have no stored column, so
+    /// they're combined here from `high`/`low`/`close` on every read
+    /// instead.
+    fn value_at(&self, field: Field, n: usize) -> Option<f64> {
+        let at = |series: &[f64]| series.get(series.len().checked_sub(1 + n)?).copied();
+        match field {
+            Field::Typical => Some((at(&self.high)? + at(&self.low)? + at(&self.close)?) / 3.0),
+            Field::Median => Some((at(&self.high)? + at(&self.low)?) / 2.0),
+            _ => at(self.series(field)),
+        }
+    }
+}
+
+fn operand_value(
+    op: &Operand,
+    graph: &IndicatorGraph,
+    history: &FieldHistory,
+    n: usize,
+    position: &PositionContext,
+) -> Option<f64> {
+    match op {
+        Operand::Field(f) => history.value_at(*f, n),
+        _ => op.value_at(graph, &[], n, position),
+    }
+}
+
+/// A cross is only reported once both the current and previous bars have
+/// valid (non-NaN) readings for both operands; this naturally suppresses
+/// emission during indicator warm-up and on the very first bar, where no
+/// previous value exists at all.
+fn crossed(
+    a: &Operand,
+    b: &Operand,
+    graph: &IndicatorGraph,
+    history: &FieldHistory,
+    position: &PositionContext,
+    from_le_to_gt: bool,
+) -> bool {
+    let (Some(a0), Some(b0), Some(a1), Some(b1)) = (
+        operand_value(a, graph, history, 0, position),
+        operand_value(b, graph, history, 0, position),
+        operand_value(a, graph, history, 1, position),
+        operand_value(b, graph, history, 1, position),
+    ) else {
+        return false;
+    };
+    if [a0, b0, a1, b1].iter().any(|v| v.is_nan()) {
+        return false;
+    }
+    if from_le_to_gt {
+        a1 <= b1 && a0 > b0
+    } else {
+        a1 >= b1 && a0 < b0
+    }
+}
+
+fn eval_bool(expr: &BoolExpr, graph: &IndicatorGraph, history: &FieldHistory, position: &PositionContext) -> bool {
+    match expr {
+        BoolExpr::Compare { op, lhs, rhs } => {
+            match (eval_expr(lhs, graph, history, 0, position), eval_expr(rhs, graph, history, 0, position)) {
+                (Some(l), Some(r)) if !l.is_nan() && !r.is_nan() => op.apply(l, r),
+                _ => false,
+            }
+        }
+        BoolExpr::CrossAbove { a, b } => crossed(a, b, graph, history, position, true),
+        BoolExpr::CrossBelow { a, b } => crossed(a, b, graph, history, position, false),
+        BoolExpr::And(l, r) => eval_bool(l, graph, history, position) && eval_bool(r, graph, history, position),
+        BoolExpr::Or(l, r) => eval_bool(l, graph, history, position) || eval_bool(r, graph, history, position),
+        BoolExpr::Not(e) => !eval_bool(e, graph, history, position),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicator::Ema;
+    use crate::kline::Bar;
+
+    fn bar(close: f64) -> Bar {
+        Bar { ts: 0, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn cross_above_fires_once_on_transition() {
+        let mut graph = IndicatorGraph::new();
+        let fast = graph.add_field_indicator("fast", Field::Close, Box::new(Ema::new(2))).unwrap();
+        let slow = graph.add_field_indicator("slow", Field::Close, Box::new(Ema::new(20))).unwrap();
+        let strategy = CompiledStrategy {
+            rules: vec![Rule {
+                condition: BoolExpr::CrossAbove { a: Operand::Indicator(fast), b: Operand::Indicator(slow) },
+                action: Action::Buy,
+                bracket: None,
+            }],
+            ..Default::default()
+        };
+
+        let history = FieldHistory::default();
+        let mut fired = 0;
+        // Falling then sharply rising closes push the fast EMA above the
+        // slow one partway through.
+        for close in [10.0, 9.0, 8.0, 7.0, 20.0, 21.0, 22.0] {
+            graph.push_bar(&bar(close));
+            if strategy.evaluate(&graph, &history).map(|s| s.action) == Some(Action::Buy) {
+                fired += 1;
+            }
+        }
+        assert_eq!(fired, 1);
+    }
+
+    #[test]
+    fn no_cross_on_first_bar() {
+        let mut graph = IndicatorGraph::new();
+        let fast = graph.add_field_indicator("fast", Field::Close, Box::new(Ema::new(2))).unwrap();
+        let slow = graph.add_field_indicator("slow", Field::Close, Box::new(Ema::new(20))).unwrap();
+        let condition = BoolExpr::CrossAbove { a: Operand::Indicator(fast), b: Operand::Indicator(slow) };
+        graph.push_bar(&bar(10.0));
+        assert!(!eval_bool(&condition, &graph, &FieldHistory::default(), &PositionContext::FLAT));
+    }
+
+    #[test]
+    fn a_nan_operand_anywhere_in_an_arithmetic_expression_makes_the_comparison_false() {
+        let graph = IndicatorGraph::new();
+        let history = FieldHistory::default();
+        // (NaN * 2) > 0 -- the NaN must propagate through the multiply
+        // rather than, say, comparing false against a stray zero.
+        let condition = BoolExpr::Compare {
+            op: CompareOp::Gt,
+            lhs: Expr::Mul(
+                Box::new(Expr::Operand(Operand::Constant(f64::NAN))),
+                Box::new(Expr::Operand(Operand::Constant(2.0))),
+            ),
+            rhs: Expr::Operand(Operand::Constant(0.0)),
+        };
+        assert!(!eval_bool(&condition, &graph, &history, &PositionContext::FLAT));
+    }
+
+    #[test]
+    fn take_profit_rule_closes_only_a_profitable_position() {
+        let graph = IndicatorGraph::new();
+        let history = FieldHistory::default();
+        let strategy = CompiledStrategy {
+            rules: vec![Rule {
+                condition: BoolExpr::Compare {
+                    op: CompareOp::Gt,
+                    lhs: Expr::Operand(Operand::UnrealizedPnlPct),
+                    rhs: Expr::Operand(Operand::Constant(5.0)),
+                },
+                action: Action::Close,
+                bracket: None,
+            }],
+            ..Default::default()
+        };
+
+        let profitable = PositionContext { side: 1.0, unrealized_pnl_pct: 7.5 };
+        assert_eq!(strategy.evaluate_with(&graph, &history, &profitable), Some(Signal { action: Action::Close, bracket: None }));
+
+        let losing = PositionContext { side: 1.0, unrealized_pnl_pct: -3.0 };
+        assert_eq!(strategy.evaluate_with(&graph, &history, &losing), None);
+    }
+
+    #[test]
+    fn a_guard_rule_suppresses_a_would_be_buy_that_follows_it() {
+        let graph = IndicatorGraph::new();
+        let history = FieldHistory::default();
+        let strategy = CompiledStrategy {
+            rules: vec![
+                Rule {
+                    condition: BoolExpr::Compare {
+                        op: CompareOp::Gt,
+                        lhs: Expr::Operand(Operand::Constant(75.0)),
+                        rhs: Expr::Operand(Operand::Constant(70.0)),
+                    },
+                    action: Action::Guard,
+                    bracket: None,
+                },
+                Rule { condition: BoolExpr::Compare {
+                    op: CompareOp::Gt,
+                    lhs: Expr::Operand(Operand::Constant(1.0)),
+                    rhs: Expr::Operand(Operand::Constant(0.0)),
+                }, action: Action::Buy, bracket: None },
+            ],
+            ..Default::default()
+        };
+
+        // The guard's condition matches, so the buy rule after it never
+        // gets a chance to fire, even though its own condition holds too.
+        assert_eq!(strategy.evaluate(&graph, &history), None);
+    }
+
+    #[test]
+    fn typical_and_median_operands_read_the_hand_computed_values_from_history() {
+        let graph = IndicatorGraph::new();
+        let history = FieldHistory {
+            high: vec![12.0],
+            low: vec![8.0],
+            close: vec![10.0],
+            ..Default::default()
+        };
+
+        // TYPICAL = (12 + 8 + 10) / 3 = 10, MEDIAN = (12 + 8) / 2 = 10.
+        assert_eq!(operand_value(&Operand::Field(Field::Typical), &graph, &history, 0, &PositionContext::FLAT), Some(10.0));
+        assert_eq!(operand_value(&Operand::Field(Field::Median), &graph, &history, 0, &PositionContext::FLAT), Some(10.0));
+
+        let strategy = CompiledStrategy {
+            rules: vec![Rule {
+                condition: BoolExpr::Compare {
+                    op: CompareOp::Gt,
+                    lhs: Expr::Operand(Operand::Field(Field::Typical)),
+                    rhs: Expr::Operand(Operand::Constant(9.0)),
+                },
+                action: Action::Buy,
+                bracket: None,
+            }],
+            ..Default::default()
+        };
+        assert_eq!(strategy.evaluate(&graph, &history).map(|s| s.action), Some(Action::Buy));
+    }
+}